@@ -0,0 +1,119 @@
+// Throughput of Environment::step on a level with a floor, a few dynamic blocks and a far away goal,
+// which is the workload of the training algorithms.
+
+use criterion::{black_box, criterion_group, criterion_main, BatchSize, Criterion, Throughput};
+use physics_reinforcement_learning_environment::{
+    Environment, Move, ObjectAndTransform, World, WorldObject,
+};
+
+const STEPS: u64 = 1000;
+
+fn object(object: WorldObject, position: [f32; 2], scale: [f32; 2]) -> ObjectAndTransform {
+    ObjectAndTransform {
+        object,
+        position: [position[0], position[1], 0.0],
+        scale,
+        rotation: 0.0,
+        color: None,
+    }
+}
+
+fn world() -> World {
+    let mut objects = vec![
+        object(
+            WorldObject::Block {
+                fixed: true,
+                animation: None,
+                ccd: false,
+                friction: None,
+                restitution: None,
+                density: 1.0,
+                material: None,
+            },
+            [0.0, -100.0],
+            [2000.0, 20.0],
+        ),
+        object(
+            WorldObject::Goal {
+                reward: 1.0,
+                optional: false,
+            },
+            [900.0, 0.0],
+            [50.0, 50.0],
+        ),
+    ];
+    for index in 0..5 {
+        objects.push(object(
+            WorldObject::Block {
+                fixed: false,
+                animation: None,
+                ccd: false,
+                friction: None,
+                restitution: None,
+                density: 1.0,
+                material: None,
+            },
+            [100.0 + index as f32 * 60.0, -70.0],
+            [30.0, 30.0],
+        ));
+    }
+    World {
+        player_position: [0.0, -60.0],
+        objects,
+        ..World::default()
+    }
+}
+
+fn step(criterion: &mut Criterion) {
+    let world = world();
+    // Runs right, jumps while running, then stands still, changing every 50 steps.
+    let moves = [
+        Move {
+            right: true,
+            ..Move::default()
+        },
+        Move {
+            right: true,
+            up: true,
+            ..Move::default()
+        },
+        Move::default(),
+    ];
+
+    let mut group = criterion.benchmark_group("step");
+    group.throughput(Throughput::Elements(STEPS));
+    group.bench_function("step", |bencher| {
+        bencher.iter_batched_ref(
+            || Environment::from_world(&world).0,
+            |environment| {
+                for step in 0..STEPS {
+                    environment.step(moves[(step / 50) as usize % moves.len()]);
+                }
+                black_box(environment.player_position());
+            },
+            BatchSize::LargeInput,
+        );
+    });
+    group.bench_function("step_with_callbacks", |bencher| {
+        bencher.iter_batched_ref(
+            || {
+                let mut environment = Environment::from_world(&world).0;
+                environment.on_contact(|event, _| {
+                    black_box(event);
+                });
+                environment
+            },
+            |environment| {
+                for step in 0..STEPS {
+                    environment.step(moves[(step / 50) as usize % moves.len()]);
+                }
+                black_box(environment.player_position());
+            },
+            BatchSize::LargeInput,
+        );
+    });
+    group.finish();
+}
+
+criterion_group!(benches, step);
+criterion_main!(benches);
@@ -0,0 +1,312 @@
+// Iterative deepening brute force search over sequences of moves.
+// Each move in a sequence is held for a fixed number of physics steps, the environment's decision interval.
+// The environment is snapshotted at every node of the search tree,
+// so the shared prefixes of sequences are only simulated once per iteration.
+
+use physics_reinforcement_learning_environment::{
+    egui::{self, DragValue, RichText, Ui},
+    sparkline_path, sparkline_ui, Agent, Algorithm, EnvConfig, Environment, Move, Policy, Receiver,
+    Sender, TrainingDetails, World,
+};
+use std::ops::ControlFlow;
+
+fn main() {
+    physics_reinforcement_learning_environment::run::<
+        BruteForceAgent,
+        BruteForceMessage,
+        BruteForceTrainingDetails,
+        BruteForceAlgorithm,
+    >();
+}
+
+#[derive(PartialEq, Clone, Copy)]
+pub struct BruteForceAlgorithm {
+    decision_interval: usize,
+    max_depth: usize,
+    step_budget: usize,
+}
+
+impl Default for BruteForceAlgorithm {
+    fn default() -> Self {
+        BruteForceAlgorithm {
+            decision_interval: 20,
+            max_depth: 6,
+            step_budget: 1_000_000,
+        }
+    }
+}
+
+// The score of a sequence at a step, lower is better: the distance to the goals, or for worlds without goals
+// (like worlds won by collecting coins) one more than the number of coins remaining. Winning scores 0.
+fn environment_score(environment: &Environment) -> f32 {
+    if environment.won() {
+        return 0.0;
+    }
+    environment
+        .distance_to_goals()
+        .unwrap_or_else(|_| (environment.coins_remaining() + 1) as f32)
+}
+
+struct SearchState {
+    moves: Vec<Move>,
+    sequences_explored: usize,
+    steps_simulated: usize,
+    best_score: f32,
+}
+
+impl BruteForceAlgorithm {
+    // Explores all the extensions of the current sequence up to the given depth.
+    // `score` is the minimum distance to goals reached by the current sequence.
+    fn search(
+        &self,
+        world: &World,
+        environment: &mut Environment,
+        depth: usize,
+        score: f32,
+        state: &mut SearchState,
+        sender: &Sender<BruteForceMessage>,
+    ) -> ControlFlow<()> {
+        if state.moves.len() == depth {
+            state.sequences_explored += 1;
+            if state.sequences_explored.is_multiple_of(100) {
+                self.send_progress(depth, state, sender)?;
+            }
+            return ControlFlow::Continue(());
+        }
+
+        let snapshot = environment.snapshot();
+        for player_move in Move::BASIC {
+            environment.restore(&snapshot);
+            state.moves.push(player_move);
+
+            environment.step(player_move);
+            state.steps_simulated += self.decision_interval;
+            let move_score = score.min(environment_score(environment));
+
+            if move_score < state.best_score {
+                state.best_score = move_score;
+                let agent = BruteForceAgent {
+                    moves: state.moves.clone(),
+                    curr: 0,
+                    decision_interval: self.decision_interval,
+                };
+                let path = sparkline_path(world, &agent, state.moves.len());
+                if sender
+                    .send(BruteForceMessage::Agent(move_score, agent, path))
+                    .is_err()
+                {
+                    return ControlFlow::Break(());
+                }
+            }
+
+            if environment.won() || state.steps_simulated >= self.step_budget {
+                return ControlFlow::Break(());
+            }
+
+            self.search(world, environment, depth, move_score, state, sender)?;
+            state.moves.pop();
+        }
+        ControlFlow::Continue(())
+    }
+
+    fn send_progress(
+        &self,
+        depth: usize,
+        state: &SearchState,
+        sender: &Sender<BruteForceMessage>,
+    ) -> ControlFlow<()> {
+        let progress = BruteForceMessage::Progress {
+            depth,
+            sequences_explored: state.sequences_explored,
+            steps_simulated: state.steps_simulated,
+        };
+        if sender.send(progress).is_err() {
+            ControlFlow::Break(())
+        } else {
+            ControlFlow::Continue(())
+        }
+    }
+}
+
+impl Algorithm<BruteForceAgent, BruteForceMessage, BruteForceTrainingDetails>
+    for BruteForceAlgorithm
+{
+    fn train(&self, world: World, sender: Sender<BruteForceMessage>) {
+        let (mut environment, _) = Environment::from_world(&world);
+        environment.set_decision_interval(self.decision_interval);
+        let initial_snapshot = environment.snapshot();
+        let initial_score = environment_score(&environment);
+
+        let mut state = SearchState {
+            moves: vec![],
+            sequences_explored: 0,
+            steps_simulated: 0,
+            best_score: f32::INFINITY,
+        };
+
+        for depth in 1..=self.max_depth {
+            environment.restore(&initial_snapshot);
+            state.moves.clear();
+            let result = self.search(
+                &world,
+                &mut environment,
+                depth,
+                initial_score,
+                &mut state,
+                &sender,
+            );
+            // Always report the final counts for the iteration.
+            if self.send_progress(depth, &state, &sender).is_break() || result.is_break() {
+                return;
+            }
+        }
+    }
+
+    fn env_config(&self) -> EnvConfig {
+        EnvConfig {
+            decision_interval: self.decision_interval,
+            ..EnvConfig::default()
+        }
+    }
+
+    fn selection_ui(&mut self, ui: &mut Ui) {
+        egui::Grid::new("Selection grid")
+            .spacing([25.0, 5.0])
+            .show(ui, |ui| {
+                ui.label("Decision interval: ");
+                ui.add(DragValue::new(&mut self.decision_interval).clamp_range(1..=100));
+                ui.end_row();
+                ui.label("Maximum depth: ");
+                ui.add(DragValue::new(&mut self.max_depth).clamp_range(1..=20));
+                ui.end_row();
+                ui.label("Step budget: ");
+                ui.add(DragValue::new(&mut self.step_budget).clamp_range(1000..=100_000_000));
+                ui.end_row();
+            });
+    }
+
+    fn training_details_receiver(
+        &self,
+        _world: &World,
+        receiver: Receiver<BruteForceMessage>,
+    ) -> BruteForceTrainingDetails {
+        BruteForceTrainingDetails {
+            agents: vec![],
+            depth: 0,
+            sequences_explored: 0,
+            steps_simulated: 0,
+            receiver,
+        }
+    }
+}
+
+pub enum BruteForceMessage {
+    Progress {
+        depth: usize,
+        sequences_explored: usize,
+        steps_simulated: usize,
+    },
+    // The score, the agent and its sparkline path.
+    Agent(f32, BruteForceAgent, Vec<[f32; 2]>),
+}
+
+pub struct BruteForceTrainingDetails {
+    agents: Vec<(f32, BruteForceAgent, Vec<[f32; 2]>)>,
+    depth: usize,
+    sequences_explored: usize,
+    steps_simulated: usize,
+    receiver: Receiver<BruteForceMessage>,
+}
+
+impl TrainingDetails<BruteForceAgent, BruteForceMessage> for BruteForceTrainingDetails {
+    fn receive_messages(&mut self) {
+        for message in self.receiver.try_iter().take(1000) {
+            match message {
+                BruteForceMessage::Progress {
+                    depth,
+                    sequences_explored,
+                    steps_simulated,
+                } => {
+                    self.depth = depth;
+                    self.sequences_explored = sequences_explored;
+                    self.steps_simulated = steps_simulated;
+                }
+                BruteForceMessage::Agent(score, agent, path) => {
+                    self.agents.push((score, agent, path))
+                }
+            }
+        }
+    }
+
+    fn details_ui(&mut self, ui: &mut Ui) -> Option<&BruteForceAgent> {
+        ui.label(format!("Depth: {}", self.depth));
+        ui.label(format!("Sequences explored: {}", self.sequences_explored));
+        ui.label(format!("Steps simulated: {}", self.steps_simulated));
+        ui.add_space(10.0);
+
+        let mut selected_agent = None;
+        for (score, agent, path) in self.agents.iter().rev() {
+            ui.horizontal(|ui| {
+                sparkline_ui(ui, path);
+                ui.label(format!("Score {}", score));
+                ui.label(format!("Moves {}", agent.moves.len()));
+                if ui.button("Visualize agent").clicked() {
+                    selected_agent = Some(agent);
+                }
+            });
+        }
+        selected_agent
+    }
+}
+
+#[derive(Clone)]
+pub struct BruteForceAgent {
+    moves: Vec<Move>,
+    curr: usize,
+    decision_interval: usize,
+}
+
+impl Policy for BruteForceAgent {
+    fn get_move(&mut self, _environment: &Environment) -> Move {
+        if self.curr < self.moves.len() {
+            let player_move = self.moves[self.curr];
+            self.curr += 1;
+            player_move
+        } else {
+            Move::default()
+        }
+    }
+}
+
+impl Agent for BruteForceAgent {
+    fn details_ui(&self, ui: &mut Ui, _environment: &Environment) {
+        ui.label(format!("Decision interval: {}", self.decision_interval));
+        ui.add_space(10.0);
+
+        for (index, player_move) in self.moves.iter().enumerate() {
+            let mut text = format!("{}. ", index + 1);
+            if player_move.up {
+                text += " UP ";
+            }
+            if player_move.left {
+                text += " LEFT ";
+            }
+            if player_move.right {
+                text += " RIGHT ";
+            }
+
+            if self.curr == index {
+                ui.label(RichText::new(text).strong());
+            } else {
+                ui.label(text);
+            }
+        }
+    }
+
+    fn env_config(&self) -> EnvConfig {
+        EnvConfig {
+            decision_interval: self.decision_interval,
+            ..EnvConfig::default()
+        }
+    }
+}
@@ -1,13 +1,34 @@
 use bevy_egui::egui::Ui;
 use crossbeam::channel::{Receiver, Sender};
 
-use crate::{common::Move, Environment, World};
+use crate::{
+    common::EnvConfig,
+    overlay::OverlayPainter,
+    policy::{Policy, PolicyTable},
+    Environment, World,
+};
 
 // https://stackoverflow.com/questions/75989070/does-static-in-generic-type-definition-refer-to-the-lifetime-of-the-type-itself
 
-pub trait Agent: Clone + Send + Sync + 'static {
-    fn get_move(&mut self, environment: &Environment) -> Move;
+/// A policy shown in the app, see [`Policy`] for the moves it chooses.
+pub trait Agent: Policy + Clone + Send + Sync + 'static {
     fn details_ui(&self, ui: &mut Ui, environment: &Environment);
+
+    /// The environment configuration the agent was trained with.
+    /// The agent is visualized in an environment with this configuration.
+    fn env_config(&self) -> EnvConfig {
+        EnvConfig::default()
+    }
+
+    /// The agent as a policy table, if it can be expressed as one.
+    /// Agents returning a table can be exported from the visualization.
+    fn policy_table(&self) -> Option<PolicyTable> {
+        None
+    }
+
+    /// Draws shapes over the world while the agent is visualized, such as planned paths,
+    /// value estimates or sensor rays. Called every frame with the environment after the step.
+    fn draw_overlay(&self, _painter: &mut OverlayPainter, _environment: &Environment) {}
 }
 
 pub trait TrainingDetails<AgentType: Agent, Message: Send + Sync + 'static>:
@@ -25,6 +46,23 @@ pub trait Algorithm<
 {
     fn selection_ui(&mut self, ui: &mut Ui);
     fn train(&self, world: World, sender: Sender<Message>);
+
+    /// The environment configuration used for training.
+    /// A warning is shown when visualizing an agent whose [`Agent::env_config`] differs from this.
+    fn env_config(&self) -> EnvConfig {
+        EnvConfig::default()
+    }
+
+    /// Mutable access to the environment configuration used for training, if the algorithm supports changing it.
+    /// When this returns Some, the environment settings (such as disturbances) can be edited before training.
+    fn env_config_mut(&mut self) -> Option<&mut EnvConfig> {
+        None
+    }
+
+    /// Called with the run's seed before training starts, see [`Seeds`](crate::Seeds).
+    /// Algorithms should seed their random number generators with it, so runs can be reproduced.
+    fn set_seed(&mut self, _seed: u64) {}
+
     fn training_details_receiver(
         &self,
         world: &World,
@@ -0,0 +1,269 @@
+#[cfg(feature = "gui")]
+use bevy_egui::egui::{self, Color32, DragValue, Pos2, Sense, Stroke, Ui, Vec2};
+use serde::{Deserialize, Serialize};
+#[cfg(feature = "gui")]
+use std::f32::consts::PI;
+
+#[cfg(feature = "gui")]
+const TIMELINE_SIZE: [f32; 2] = [240.0, 24.0];
+
+/// A pose of an animated object at a point in time, relative to the object's transform in the world.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, Default, PartialEq)]
+pub struct Keyframe {
+    /// Time since the start of the animation, in seconds.
+    pub time: f32,
+    /// Offset from the object's position, in world units.
+    pub offset: [f32; 2],
+    /// Rotation added to the object's rotation, in radians.
+    pub rotation: f32,
+}
+
+/// Keyframed motion of a fixed block, which is simulated as a kinematic body
+/// so it carries the bodies in contact with it.
+/// The pose is linearly interpolated between keyframes.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct Animation {
+    /// Sorted by time.
+    pub keyframes: Vec<Keyframe>,
+    /// Whether the animation restarts after the last keyframe, otherwise the object stays at the last keyframe.
+    /// The last keyframe should match the first one for a smooth loop.
+    pub looping: bool,
+}
+
+impl Default for Animation {
+    /// Moves right and back over 4 seconds.
+    fn default() -> Self {
+        Animation {
+            keyframes: vec![
+                Keyframe::default(),
+                Keyframe {
+                    time: 2.0,
+                    offset: [200.0, 0.0],
+                    rotation: 0.0,
+                },
+                Keyframe {
+                    time: 4.0,
+                    ..Keyframe::default()
+                },
+            ],
+            looping: true,
+        }
+    }
+}
+
+/// How a [`WorldObject::MovingPlatform`](crate::WorldObject::MovingPlatform) continues after its last waypoint.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum LoopMode {
+    /// Goes back to its position and starts again.
+    #[default]
+    Loop,
+    /// Goes back through the waypoints in reverse order, then starts again.
+    PingPong,
+    /// Stays at the last waypoint.
+    Once,
+}
+
+impl LoopMode {
+    pub fn name(&self) -> &'static str {
+        match self {
+            LoopMode::Loop => "Loop",
+            LoopMode::PingPong => "Ping-pong",
+            LoopMode::Once => "Once",
+        }
+    }
+}
+
+impl Animation {
+    /// The motion of a moving platform, which starts at its position and visits the waypoints
+    /// (offsets from its position, in world units) at `speed` world units per second.
+    /// Platforms without a positive speed don't move.
+    pub fn from_waypoints(waypoints: &[[f32; 2]], speed: f32, loop_mode: LoopMode) -> Animation {
+        if speed <= 0.0 {
+            return Animation {
+                keyframes: vec![Keyframe::default()],
+                looping: false,
+            };
+        }
+
+        let mut path = vec![[0.0, 0.0]];
+        path.extend_from_slice(waypoints);
+        match loop_mode {
+            LoopMode::Loop => path.push([0.0, 0.0]),
+            LoopMode::PingPong => {
+                let returning: Vec<_> = path.iter().rev().skip(1).copied().collect();
+                path.extend(returning);
+            }
+            LoopMode::Once => {}
+        }
+
+        let mut time = 0.0;
+        let mut keyframes = Vec::with_capacity(path.len());
+        for (index, offset) in path.iter().enumerate() {
+            if let Some(previous) = index.checked_sub(1).map(|previous| path[previous]) {
+                let distance =
+                    ((offset[0] - previous[0]).powi(2) + (offset[1] - previous[1]).powi(2)).sqrt();
+                time += distance / speed;
+            }
+            keyframes.push(Keyframe {
+                time,
+                offset: *offset,
+                rotation: 0.0,
+            });
+        }
+        Animation {
+            keyframes,
+            looping: loop_mode != LoopMode::Once,
+        }
+    }
+
+    /// Time of the last keyframe.
+    pub fn duration(&self) -> f32 {
+        self.keyframes.last().map_or(0.0, |keyframe| keyframe.time)
+    }
+
+    /// The offset and rotation at the given time.
+    /// Before the first keyframe the object is at the first keyframe, and without keyframes it doesn't move.
+    pub fn sample(&self, time: f32) -> ([f32; 2], f32) {
+        let duration = self.duration();
+        let time = if self.looping && duration > 0.0 {
+            time.rem_euclid(duration)
+        } else {
+            time
+        };
+
+        let next = self
+            .keyframes
+            .iter()
+            .position(|keyframe| keyframe.time > time);
+        let (previous, next) = match next {
+            Some(0) => (self.keyframes[0], self.keyframes[0]),
+            Some(next) => (self.keyframes[next - 1], self.keyframes[next]),
+            None => match self.keyframes.last() {
+                Some(last) => (*last, *last),
+                None => return ([0.0, 0.0], 0.0),
+            },
+        };
+
+        let fraction = if next.time > previous.time {
+            (time - previous.time) / (next.time - previous.time)
+        } else {
+            0.0
+        };
+        let lerp = |a: f32, b: f32| a + (b - a) * fraction;
+        (
+            [
+                lerp(previous.offset[0], next.offset[0]),
+                lerp(previous.offset[1], next.offset[1]),
+            ],
+            lerp(previous.rotation, next.rotation),
+        )
+    }
+
+    /// Sorts the keyframes by time.
+    pub fn sort(&mut self) {
+        self.keyframes
+            .sort_by(|keyframe1, keyframe2| keyframe1.time.total_cmp(&keyframe2.time));
+    }
+
+    /// A timeline with the keyframes, and the properties of the keyframe with the index `selected`.
+    /// Clicking a keyframe on the timeline selects it.
+    #[cfg(feature = "gui")]
+    pub fn timeline_ui(&mut self, ui: &mut Ui, selected: &mut usize) {
+        let (response, painter) = ui.allocate_painter(Vec2::from(TIMELINE_SIZE), Sense::click());
+        let rect = response.rect.shrink(6.0);
+        painter.rect_filled(response.rect, 2.0, Color32::from_gray(235));
+        painter.line_segment(
+            [rect.left_center(), rect.right_center()],
+            Stroke::new(1.0, Color32::GRAY),
+        );
+
+        let duration = self.duration().max(1e-3);
+        let keyframe_position = |keyframe: &Keyframe| {
+            Pos2::new(
+                rect.left() + rect.width() * keyframe.time / duration,
+                rect.center().y,
+            )
+        };
+        for (index, keyframe) in self.keyframes.iter().enumerate() {
+            let color = if index == *selected {
+                Color32::DARK_BLUE
+            } else {
+                Color32::GRAY
+            };
+            painter.circle_filled(keyframe_position(keyframe), 4.0, color);
+        }
+
+        if let Some(pointer) = response.interact_pointer_pos() {
+            if let Some((index, _)) = self
+                .keyframes
+                .iter()
+                .enumerate()
+                .map(|(index, keyframe)| (index, keyframe_position(keyframe).distance(pointer)))
+                .min_by(|(_, distance1), (_, distance2)| distance1.total_cmp(distance2))
+            {
+                *selected = index;
+            }
+        }
+
+        ui.horizontal(|ui| {
+            if ui.button("Add keyframe").clicked() {
+                let last = self.keyframes.last().copied().unwrap_or_default();
+                self.keyframes.push(Keyframe {
+                    time: last.time + 1.0,
+                    ..last
+                });
+                *selected = self.keyframes.len() - 1;
+            }
+            if self.keyframes.len() > 1 && ui.button("Remove keyframe").clicked() {
+                self.keyframes
+                    .remove((*selected).min(self.keyframes.len() - 1));
+            }
+            ui.checkbox(&mut self.looping, "Loop");
+        });
+
+        if self.keyframes.is_empty() {
+            return;
+        }
+        *selected = (*selected).min(self.keyframes.len() - 1);
+
+        let keyframe = &mut self.keyframes[*selected];
+        let mut time_changed = false;
+        egui::Grid::new("Keyframe grid")
+            .spacing([25.0, 5.0])
+            .show(ui, |ui| {
+                ui.label("Time:");
+                time_changed = ui
+                    .add(
+                        DragValue::new(&mut keyframe.time)
+                            .speed(0.1)
+                            .clamp_range(0.0..=f32::INFINITY),
+                    )
+                    .changed();
+                ui.end_row();
+
+                ui.label("Offset:");
+                ui.horizontal(|ui| {
+                    ui.add(DragValue::new(&mut keyframe.offset[0]));
+                    ui.add(DragValue::new(&mut keyframe.offset[1]));
+                });
+                ui.end_row();
+
+                ui.label("Rotation:");
+                let mut rotation = keyframe.rotation * 180.0 / PI;
+                ui.add(DragValue::new(&mut rotation));
+                keyframe.rotation = rotation * PI / 180.0;
+                ui.end_row();
+            });
+
+        if time_changed {
+            // Keep the same keyframe selected after sorting.
+            let keyframe = self.keyframes[*selected];
+            self.sort();
+            *selected = self
+                .keyframes
+                .iter()
+                .position(|other| *other == keyframe)
+                .unwrap_or(0);
+        }
+    }
+}
@@ -0,0 +1,134 @@
+use std::collections::HashMap;
+
+use bincode::Options;
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+
+use crate::error::Result;
+
+// bincode doesn't store field names, so it can't read worlds which skip the fields with default values.
+// Instead the binary format stores the world's JSON values, with each object key stored once.
+#[derive(Serialize, Deserialize)]
+struct BinaryWorld {
+    keys: Vec<String>,
+    world: BinaryValue,
+}
+
+#[derive(Serialize, Deserialize)]
+enum BinaryValue {
+    Null,
+    Bool(bool),
+    Unsigned(u64),
+    Signed(i64),
+    // Numbers which are exactly f32, which is the case for the world's numbers, use half the space.
+    Float(f32),
+    Double(f64),
+    String(String),
+    Array(Vec<BinaryValue>),
+    // The index of each key in BinaryWorld::keys, with its value.
+    Object(Vec<(u32, BinaryValue)>),
+}
+
+// The object keys of the world, in the order they are first used.
+#[derive(Default)]
+struct Keys {
+    keys: Vec<String>,
+    indices: HashMap<String, u32>,
+}
+
+impl Keys {
+    fn index(&mut self, key: String) -> u32 {
+        if let Some(index) = self.indices.get(&key) {
+            return *index;
+        }
+        let index = self.keys.len() as u32;
+        self.keys.push(key.clone());
+        self.indices.insert(key, index);
+        index
+    }
+}
+
+impl BinaryValue {
+    fn from_value(value: Value, keys: &mut Keys) -> BinaryValue {
+        match value {
+            Value::Null => BinaryValue::Null,
+            Value::Bool(value) => BinaryValue::Bool(value),
+            Value::Number(number) => {
+                if let Some(number) = number.as_u64() {
+                    BinaryValue::Unsigned(number)
+                } else if let Some(number) = number.as_i64() {
+                    BinaryValue::Signed(number)
+                } else {
+                    // Numbers which aren't integers are f64.
+                    let number = number.as_f64().unwrap();
+                    if number as f32 as f64 == number {
+                        BinaryValue::Float(number as f32)
+                    } else {
+                        BinaryValue::Double(number)
+                    }
+                }
+            }
+            Value::String(string) => BinaryValue::String(string),
+            Value::Array(values) => BinaryValue::Array(
+                values
+                    .into_iter()
+                    .map(|value| BinaryValue::from_value(value, keys))
+                    .collect(),
+            ),
+            Value::Object(map) => BinaryValue::Object(
+                map.into_iter()
+                    .map(|(key, value)| (keys.index(key), BinaryValue::from_value(value, keys)))
+                    .collect(),
+            ),
+        }
+    }
+
+    fn into_value(self, keys: &[String]) -> Result<Value> {
+        Ok(match self {
+            BinaryValue::Null => Value::Null,
+            BinaryValue::Bool(value) => Value::Bool(value),
+            BinaryValue::Unsigned(number) => Value::from(number),
+            BinaryValue::Signed(number) => Value::from(number),
+            BinaryValue::Float(number) => Value::from(number),
+            BinaryValue::Double(number) => Value::from(number),
+            BinaryValue::String(string) => Value::String(string),
+            BinaryValue::Array(values) => Value::Array(
+                values
+                    .into_iter()
+                    .map(|value| value.into_value(keys))
+                    .collect::<Result<_>>()?,
+            ),
+            BinaryValue::Object(entries) => {
+                let mut map = Map::new();
+                for (index, value) in entries {
+                    let key = keys.get(index as usize).ok_or_else(|| {
+                        Box::new(bincode::ErrorKind::Custom(format!(
+                            "key {index} is out of range"
+                        )))
+                    })?;
+                    map.insert(key.clone(), value.into_value(keys)?);
+                }
+                Value::Object(map)
+            }
+        })
+    }
+}
+
+// Variable length integers keep the lengths and enum tags small.
+fn options() -> impl Options {
+    bincode::DefaultOptions::new()
+}
+
+pub(crate) fn encode(world: Value) -> Result<Vec<u8>> {
+    let mut keys = Keys::default();
+    let world = BinaryValue::from_value(world, &mut keys);
+    Ok(options().serialize(&BinaryWorld {
+        keys: keys.keys,
+        world,
+    })?)
+}
+
+pub(crate) fn decode(bytes: &[u8]) -> Result<Value> {
+    let BinaryWorld { keys, world } = options().deserialize(bytes)?;
+    world.into_value(&keys)
+}
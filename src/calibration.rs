@@ -0,0 +1,183 @@
+#[cfg(feature = "gui")]
+use bevy_egui::egui::{self, DragValue, Ui};
+
+use crate::common::{
+    Environment, Move, ObjectAndTransform, PhysicsConfig, PlayerShape, World, WorldObject,
+};
+
+const SETTLE_STEPS: usize = 30;
+// Number of steps the player runs before a running jump.
+const RUN_STEPS: usize = 60;
+const MAX_AIR_STEPS: usize = 600;
+const SEARCH_ITERATIONS: usize = 30;
+
+/// The obstacles which should be just barely clearable, in world units.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct CalibrationTarget {
+    /// Height the player's center rises by when jumping from standing still.
+    pub jump_height: f32,
+    /// Horizontal distance the player's center travels during a jump
+    /// after running for one second, landing at the height it jumped from.
+    pub gap: f32,
+}
+
+impl Default for CalibrationTarget {
+    fn default() -> Self {
+        CalibrationTarget {
+            jump_height: 150.0,
+            gap: 300.0,
+        }
+    }
+}
+
+impl CalibrationTarget {
+    /// Returns `physics` with the smallest jump and move impulses which clear the target
+    /// for a player with the given shape.
+    /// The jump impulse is found first, as the gap also depends on it.
+    pub fn calibrate(&self, physics: PhysicsConfig, player_shape: PlayerShape) -> PhysicsConfig {
+        let jump_impulse = search(physics.jump_impulse, self.jump_height, |jump_impulse| {
+            jump_height(
+                PhysicsConfig {
+                    jump_impulse,
+                    ..physics
+                },
+                player_shape,
+            )
+        });
+        let physics = PhysicsConfig {
+            jump_impulse,
+            ..physics
+        };
+        let player_impulse = search(physics.player_impulse, self.gap, |player_impulse| {
+            jump_distance(
+                PhysicsConfig {
+                    player_impulse,
+                    ..physics
+                },
+                player_shape,
+            )
+        });
+        PhysicsConfig {
+            player_impulse,
+            ..physics
+        }
+    }
+
+    /// Shows the target, and calibrates `physics` when the button is clicked.
+    #[cfg(feature = "gui")]
+    pub fn ui(&mut self, ui: &mut Ui, physics: &mut PhysicsConfig, player_shape: PlayerShape) {
+        egui::Grid::new("Calibration grid")
+            .spacing([25.0, 5.0])
+            .show(ui, |ui| {
+                ui.label("Jump height:");
+                ui.add(DragValue::new(&mut self.jump_height).clamp_range(1.0..=10_000.0));
+                ui.end_row();
+                ui.label("Running jump gap:");
+                ui.add(DragValue::new(&mut self.gap).clamp_range(1.0..=10_000.0));
+                ui.end_row();
+            });
+        if ui.button("Calibrate").clicked() {
+            *physics = self.calibrate(*physics, player_shape);
+        }
+    }
+}
+
+// Binary search for the smallest value for which `measure` reaches `target`,
+// assuming `measure` increases with the value.
+fn search(initial: f32, target: f32, measure: impl Fn(f32) -> f32) -> f32 {
+    let mut low = 0.0;
+    let mut high = initial.max(1e-4);
+    for _ in 0..SEARCH_ITERATIONS {
+        if measure(high) >= target {
+            break;
+        }
+        low = high;
+        high *= 2.0;
+    }
+    for _ in 0..SEARCH_ITERATIONS {
+        let middle = (low + high) / 2.0;
+        if measure(middle) >= target {
+            high = middle;
+        } else {
+            low = middle;
+        }
+    }
+    high
+}
+
+// A flat floor, long enough for running jumps, with the player standing at its left end.
+fn flat_environment(physics: PhysicsConfig, player_shape: PlayerShape) -> Environment {
+    let floor_thickness = 100.0;
+    let floor_length = 1_000_000.0;
+    let world = World {
+        player_position: [0.0, player_shape.half_height()],
+        objects: vec![ObjectAndTransform {
+            object: WorldObject::Block {
+                fixed: true,
+                animation: None,
+                ccd: false,
+                friction: None,
+                restitution: None,
+                density: 1.0,
+                material: None,
+            },
+            position: [floor_length / 2.0 - 100.0, -floor_thickness / 2.0, 0.0],
+            scale: [floor_length, floor_thickness],
+            rotation: 0.0,
+            color: None,
+        }],
+        physics,
+        player_shape,
+        ..World::default()
+    };
+    let (mut environment, _) = Environment::from_world(&world);
+    for _ in 0..SETTLE_STEPS {
+        environment.step(Move::default());
+    }
+    environment
+}
+
+/// How high the player's center rises when jumping from standing still, in world units.
+pub fn jump_height(physics: PhysicsConfig, player_shape: PlayerShape) -> f32 {
+    let mut environment = flat_environment(physics, player_shape);
+    let start = environment.player_position()[1];
+    environment.step(Move {
+        up: true,
+        ..Move::default()
+    });
+    let mut apex = environment.player_position()[1];
+    for _ in 0..MAX_AIR_STEPS {
+        environment.step(Move::default());
+        let y = environment.player_position()[1];
+        if y <= apex {
+            break;
+        }
+        apex = y;
+    }
+    apex - start
+}
+
+/// How far the player's center travels horizontally during a jump after running for one second,
+/// in world units.
+pub fn jump_distance(physics: PhysicsConfig, player_shape: PlayerShape) -> f32 {
+    let mut environment = flat_environment(physics, player_shape);
+    let run = Move {
+        right: true,
+        ..Move::default()
+    };
+    for _ in 0..RUN_STEPS {
+        environment.step(run);
+    }
+    let start = environment.player_position()[0];
+    environment.step(Move { up: true, ..run });
+    let mut airborne = false;
+    for _ in 0..MAX_AIR_STEPS {
+        if !environment.on_ground() {
+            airborne = true;
+        } else if airborne {
+            break;
+        }
+        environment.step(run);
+    }
+    environment.player_position()[0] - start
+}
@@ -1,13 +1,54 @@
-use std::cmp::Ordering;
+use std::{cmp::Ordering, collections::HashMap, ffi::OsStr, fs, path::Path, sync::Arc};
 
-use bevy::prelude::*;
+#[cfg(feature = "gui")]
+use crate::polygon::polygon_mesh;
+use crate::{
+    animation::{Animation, LoopMode},
+    binary,
+    error::{Error, Result},
+    events::{
+        Bounds, BoundsEvent, CollisionCollector, ContactEvent, EnvEvent, EventCallbacks, GoalEvent,
+    },
+    joint::{JointKind, WorldJoint},
+    material::SurfaceMaterial,
+    migration::{migrate, Migration, RonWorld, VersionedWorld, WORLD_VERSION},
+    path_distance::DistanceField,
+    randomization::Randomization,
+    reward::{DenseReward, RewardFn, Transition},
+    trajectory::{Trajectory, TrajectoryStep},
+};
+#[cfg(feature = "gui")]
+use bevy::prelude::{Color, Component, Mesh, Resource, States, Transform};
+use glam::{Quat, Vec2, Vec3};
+use rand::{rngs::StdRng, Rng, SeedableRng};
 use rapier2d::prelude::*;
 use serde::{Deserialize, Serialize};
 
 pub const PLAYER_DEPTH: f32 = 20.0;
 pub const PLAYER_RADIUS: f32 = 20.0;
 pub const BEVY_TO_PHYSICS_SCALE: f32 = 0.25 / (2.0 * PLAYER_RADIUS);
+// Physics constants, in physics units.
+pub const GRAVITY: f32 = 2.0;
+pub const MOVE_IMPULSE: f32 = 0.003;
+pub const JUMP_IMPULSE: f32 = 0.1;
+// Impulse applied to the player for each pressed direction while in water, away from the ground.
+pub const SWIM_IMPULSE: f32 = 0.003;
+// Impulses for the optional moves, see Capabilities.
+pub const FAST_FALL_IMPULSE: f32 = 0.003;
+pub const DASH_IMPULSE: f32 = 0.1;
+pub const DASH_COOLDOWN_STEPS: usize = 60;
+// Largest distance between the player and a block it can grab, in world units.
+pub const GRAB_DISTANCE: f32 = 20.0;
+// Fraction of the velocity lost per second in water, per unit of water density.
+pub const WATER_DRAG: f32 = 1.0;
+// Fraction of the extra length of overstretched ropes removed by each step.
+pub const ROPE_CORRECTION: f32 = 0.2;
+// Depth of background decorations, behind the other objects (which are at depths of 0 and above)
+// while still in front of the far plane of the 2D camera.
+#[cfg(feature = "gui")]
+const BACKGROUND_DEPTH: f32 = -0.05;
 
+#[cfg(feature = "gui")]
 #[derive(Debug, Hash, Eq, PartialEq, Clone, Copy, Default, States)]
 pub enum AppState {
     #[default]
@@ -16,36 +57,646 @@ pub enum AppState {
     Train,
 }
 
-#[derive(Serialize, Deserialize, Default, Resource, Debug, Clone)]
+#[derive(Serialize, Deserialize, Default, Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "gui", derive(Resource))]
 pub struct World {
     pub player_position: [f32; 2],
     pub objects: Vec<ObjectAndTransform>,
+    #[serde(default)]
+    pub capabilities: Capabilities,
+    #[serde(default)]
+    pub win_condition: WinCondition,
+    #[serde(default)]
+    pub physics: PhysicsConfig,
+    #[serde(default)]
+    pub solver: SolverConfig,
+    #[serde(default)]
+    pub player_shape: PlayerShape,
+    /// Positions of the players other than the one at `player_position`, see [`Environment::add_player`].
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub other_players: Vec<[f32; 2]>,
+    /// The rectangle the players should stay in, leaving it ends the episode.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub bounds: Option<Bounds>,
+    /// When set, the first player starts at a random position in this rectangle instead of at `player_position`,
+    /// see [`Environment::set_spawn_region`].
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub spawn_region: Option<Bounds>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub stamina: Option<Stamina>,
+    /// Used by [`Environment::from_world_randomized`].
+    #[serde(default)]
+    pub randomization: Randomization,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub joints: Vec<WorldJoint>,
+}
+
+/// Physics settings of a world, in physics units.
+/// The defaults are the values used before the settings could be changed.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+#[serde(default)]
+pub struct PhysicsConfig {
+    /// Downwards acceleration.
+    pub gravity: f32,
+    /// Impulse applied to the player for moving left or right on the ground.
+    pub player_impulse: f32,
+    /// Impulse applied to the player for jumping.
+    pub jump_impulse: f32,
+    /// Friction coefficient of the player and the blocks.
+    pub friction: f32,
+    /// Restitution (bounciness) of the player and the blocks.
+    pub restitution: f32,
+}
+
+impl Default for PhysicsConfig {
+    fn default() -> Self {
+        PhysicsConfig {
+            gravity: GRAVITY,
+            player_impulse: MOVE_IMPULSE,
+            jump_impulse: JUMP_IMPULSE,
+            friction: ColliderBuilder::default_friction(),
+            restitution: 0.0,
+        }
+    }
+}
+
+/// Settings of the physics solver, trading accuracy for simulation speed.
+/// The defaults are Rapier's defaults.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+#[serde(default)]
+pub struct SolverConfig {
+    /// Duration of a physics step in seconds.
+    /// Longer physics steps simulate more time per step but are less accurate.
+    pub dt: f32,
+    /// Number of iterations used to solve the contact velocities.
+    pub velocity_iterations: usize,
+    /// Number of iterations used to correct the penetrations between colliders.
+    pub stabilization_iterations: usize,
+}
+
+impl SolverConfig {
+    fn integration_parameters(&self) -> IntegrationParameters {
+        IntegrationParameters {
+            dt: self.dt,
+            max_velocity_iterations: self.velocity_iterations,
+            max_stabilization_iterations: self.stabilization_iterations,
+            ..IntegrationParameters::default()
+        }
+    }
+}
+
+impl Default for SolverConfig {
+    fn default() -> Self {
+        let parameters = IntegrationParameters::default();
+        SolverConfig {
+            dt: parameters.dt,
+            velocity_iterations: parameters.max_velocity_iterations,
+            stabilization_iterations: parameters.max_stabilization_iterations,
+        }
+    }
+}
+
+/// The color of the player with the given index, the other players are lighter than the first one.
+#[cfg(feature = "gui")]
+pub fn player_color(player: usize) -> Color {
+    if player == 0 {
+        Color::GRAY
+    } else {
+        Color::SILVER
+    }
+}
+
+/// Size and mass of the player's capsule, in world units.
+/// The defaults are [`PLAYER_RADIUS`] and [`PLAYER_DEPTH`].
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+#[serde(default)]
+pub struct PlayerShape {
+    /// Radius of the capsule's semicircles.
+    pub radius: f32,
+    /// Distance between the centers of the capsule's semicircles.
+    pub depth: f32,
+    /// Mass of the player in physics units, or None for the mass of a capsule with density 1.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub mass: Option<f32>,
+}
+
+impl Default for PlayerShape {
+    fn default() -> Self {
+        PlayerShape {
+            radius: PLAYER_RADIUS,
+            depth: PLAYER_DEPTH,
+            mass: None,
+        }
+    }
+}
+
+impl PlayerShape {
+    /// Half of the capsule's height, from its center to its lowest point.
+    pub fn half_height(&self) -> f32 {
+        self.radius + self.depth / 2.0
+    }
+
+    /// A mesh of the capsule, for displaying the player.
+    #[cfg(feature = "gui")]
+    pub fn mesh(&self) -> Mesh {
+        Mesh::from(bevy::prelude::shape::Capsule {
+            radius: self.radius,
+            rings: 20,
+            depth: self.depth,
+            latitudes: 20,
+            longitudes: 20,
+            uv_profile: bevy::prelude::shape::CapsuleUvProfile::Uniform,
+        })
+    }
+
+    fn collider(&self) -> ColliderBuilder {
+        let collider = ColliderBuilder::capsule_y(
+            0.5 * self.depth * BEVY_TO_PHYSICS_SCALE,
+            self.radius * BEVY_TO_PHYSICS_SCALE,
+        )
+        .active_events(ActiveEvents::COLLISION_EVENTS);
+        match self.mass {
+            Some(mass) => collider.mass(mass),
+            None => collider,
+        }
+    }
+}
+
+/// The optional moves the player can use in a world.
+/// Disabled moves are ignored, so worlds created before a move was added behave the same.
+#[derive(Serialize, Deserialize, Default, Debug, Clone, Copy, PartialEq)]
+#[serde(default)]
+pub struct Capabilities {
+    /// [`Move::down`] pushes the player down while in the air.
+    pub down: bool,
+    /// [`Move::dash`] gives the player a horizontal burst in the last direction it moved in.
+    pub dash: bool,
+    /// Fraction of the move impulse applied for [`Move::left`] and [`Move::right`] while in the air,
+    /// 0.0 for no control in the air.
+    pub air_control: f32,
+    /// Number of times the player can jump in the air before touching the ground again, 1 for a double jump.
+    /// Each jump needs [`Move::up`] to be released and pressed again.
+    pub air_jumps: usize,
+    /// [`Move::grab`] attaches the nearest dynamic block to the player, until it is released.
+    pub grab: bool,
+}
+
+/// An energy meter which jumping uses up and standing still refills, see [`Environment::energy`].
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+#[serde(default)]
+pub struct Stamina {
+    /// Energy of the players at the start of an episode, and the most they can have.
+    pub max_energy: f32,
+    /// Energy used by each jump, the player can't jump with less energy.
+    pub jump_cost: f32,
+    /// Energy regained per step while standing on the ground without pressing any button.
+    pub regeneration: f32,
+}
+
+impl Default for Stamina {
+    fn default() -> Self {
+        Stamina {
+            max_energy: 1.0,
+            jump_cost: 0.25,
+            regeneration: 0.01,
+        }
+    }
+}
+
+/// What the players have to do to win a world.
+#[derive(Serialize, Deserialize, Default, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WinCondition {
+    /// A player touches a goal.
+    #[default]
+    ReachGoal,
+    /// The episode lasts `steps` steps without a player leaving the bounds.
+    Survive { steps: usize },
+    /// The players collect every coin, the player collecting the last one wins.
+    /// Worlds without coins can't be won.
+    CollectCoins,
+    /// The center of a non-fixed block is in a goal.
+    PushBlock,
+}
+
+impl WinCondition {
+    pub fn name(&self) -> &'static str {
+        match self {
+            WinCondition::ReachGoal => "Reach a goal",
+            WinCondition::Survive { .. } => "Survive",
+            WinCondition::CollectCoins => "Collect all coins",
+            WinCondition::PushBlock => "Push a block into a goal",
+        }
+    }
+}
+
+impl World {
+    /// Reads a world from a file, updating worlds saved in older formats.
+    /// Files with the `ron` extension are read as RON, files with the `bin` extension
+    /// as the binary format, and the others as JSON.
+    pub fn load(path: impl AsRef<Path>) -> Result<World> {
+        Ok(World::load_migrated(path)?.0)
+    }
+
+    /// Reads a world from a file, and returns the migrations applied to update it from an older format.
+    pub fn load_migrated(path: impl AsRef<Path>) -> Result<(World, Vec<Migration>)> {
+        let path = path.as_ref();
+        let io_error = |source| Error::WorldIo {
+            path: path.to_path_buf(),
+            source,
+        };
+        match WorldFormat::from_path(path) {
+            WorldFormat::Json => {
+                World::from_json_migrated(&fs::read_to_string(path).map_err(io_error)?)
+            }
+            WorldFormat::Ron => Ok((
+                World::from_ron(&fs::read_to_string(path).map_err(io_error)?)?,
+                vec![],
+            )),
+            WorldFormat::Binary => World::from_binary_migrated(&fs::read(path).map_err(io_error)?),
+        }
+    }
+
+    /// Writes the world to a file, in the format given by the file's extension, see [`World::load`].
+    pub fn save(&self, path: impl AsRef<Path>) -> Result<()> {
+        let path = path.as_ref();
+        let contents = match WorldFormat::from_path(path) {
+            WorldFormat::Json => self.to_json()?.into_bytes(),
+            WorldFormat::Ron => self.to_ron()?.into_bytes(),
+            WorldFormat::Binary => self.to_binary()?,
+        };
+        fs::write(path, contents).map_err(|source| Error::WorldIo {
+            path: path.to_path_buf(),
+            source,
+        })
+    }
+
+    pub fn from_json(json: &str) -> Result<World> {
+        Ok(World::from_json_migrated(json)?.0)
+    }
+
+    /// Reads a world from JSON, and returns the migrations applied to update it from an older format.
+    pub fn from_json_migrated(json: &str) -> Result<(World, Vec<Migration>)> {
+        World::from_value_migrated(serde_json::from_str(json)?)
+    }
+
+    fn from_value_migrated(mut world: serde_json::Value) -> Result<(World, Vec<Migration>)> {
+        let migrations = migrate(&mut world)?;
+        Ok((serde_json::from_value(world)?, migrations))
+    }
+
+    /// The world as JSON, with the version of the format, see [`WORLD_VERSION`](crate::WORLD_VERSION).
+    pub fn to_json(&self) -> Result<String> {
+        Ok(serde_json::to_string(&VersionedWorld::new(self))?)
+    }
+
+    /// Reads a world from RON, which is easier to edit by hand than JSON.
+    pub fn from_ron(ron: &str) -> Result<World> {
+        let RonWorld { version, world } = ron::from_str(ron)?;
+        // RON worlds were added in version 1, so there are no older RON worlds to migrate.
+        if version > WORLD_VERSION {
+            return Err(Error::WorldVersion {
+                version,
+                supported: WORLD_VERSION,
+            });
+        }
+        Ok(world)
+    }
+
+    /// The world as pretty printed RON, with the version of the format.
+    pub fn to_ron(&self) -> Result<String> {
+        let ron_world = RonWorld {
+            version: WORLD_VERSION,
+            world: self,
+        };
+        Ok(ron::ser::to_string_pretty(
+            &ron_world,
+            ron::ser::PrettyConfig::default(),
+        )?)
+    }
+
+    /// Reads a world from the binary format, which is smaller and faster to read than JSON for large worlds.
+    pub fn from_binary(bytes: &[u8]) -> Result<World> {
+        Ok(World::from_binary_migrated(bytes)?.0)
+    }
+
+    /// Reads a world from the binary format, and returns the migrations applied to update it from an older format.
+    pub fn from_binary_migrated(bytes: &[u8]) -> Result<(World, Vec<Migration>)> {
+        World::from_value_migrated(binary::decode(bytes)?)
+    }
+
+    /// The world in the binary format, with the version of the format.
+    pub fn to_binary(&self) -> Result<Vec<u8>> {
+        binary::encode(serde_json::to_value(VersionedWorld::new(self))?)
+    }
+
+    /// Whether [`Move::down`] does anything in the world,
+    /// which is the case with the down capability or ladders to climb down.
+    pub fn uses_down(&self) -> bool {
+        self.capabilities.down
+            || self
+                .objects
+                .iter()
+                .any(|object| matches!(object.object, WorldObject::Ladder { .. }))
+    }
+}
+
+// The format of a world file, given by its extension.
+enum WorldFormat {
+    Json,
+    Ron,
+    Binary,
+}
+
+impl WorldFormat {
+    fn from_path(path: &Path) -> WorldFormat {
+        match path.extension().and_then(OsStr::to_str) {
+            Some("ron") => WorldFormat::Ron,
+            Some("bin") => WorldFormat::Binary,
+            _ => WorldFormat::Json,
+        }
+    }
 }
 
 // We don't store the transform as Bevy's Transform as it doesn't implement Serialize.
-#[derive(Serialize, Deserialize, Debug, Clone)]
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
 pub struct ObjectAndTransform {
     pub object: WorldObject,
     pub position: [f32; 3],
     pub scale: [f32; 2],
     pub rotation: f32,
+    /// A color shown instead of the object's default color, as RGBA from 0 to 1.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub color: Option<[f32; 4]>,
 }
 
 impl ObjectAndTransform {
+    /// The object's custom color, or its default color.
+    #[cfg(feature = "gui")]
+    pub fn color(&self) -> Color {
+        match self.color {
+            Some([red, green, blue, alpha]) => Color::rgba(red, green, blue, alpha),
+            None => self.object.color(),
+        }
+    }
+
+    #[cfg(feature = "gui")]
     pub fn transform(&self) -> Transform {
+        let mut position = self.position;
+        if let WorldObject::Decoration {
+            background: true, ..
+        } = self.object
+        {
+            position[2] = BACKGROUND_DEPTH;
+        }
         Transform {
-            translation: Vec3::from_array(self.position),
+            translation: Vec3::from_array(position),
             scale: Vec3::from_array([self.scale[0], self.scale[1], 1.0]),
             rotation: Quat::from_rotation_z(self.rotation),
         }
     }
 }
 
+fn default_goal_reward() -> f32 {
+    1.0
+}
+
+fn default_hazard_reward() -> f32 {
+    -1.0
+}
+
+fn default_density() -> f32 {
+    1.0
+}
+
 // We separate the transform and object as we want separate Bevy components.
-#[derive(Serialize, Deserialize, Component, Clone, Debug)]
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "gui", derive(Component))]
 pub enum WorldObject {
-    Block { fixed: bool },
-    Goal,
+    Block {
+        fixed: bool,
+        /// Only used by fixed blocks, which then move kinematically.
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        animation: Option<Animation>,
+        /// Enables continuous collision detection, so the block doesn't tunnel through thin blocks when moving fast.
+        /// Only used by non-fixed blocks.
+        #[serde(default)]
+        ccd: bool,
+        /// Friction coefficient of the block, the world's friction is used when None.
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        friction: Option<f32>,
+        /// Restitution (bounciness) of the block, the world's restitution is used when None.
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        restitution: Option<f32>,
+        /// Surface preset used for the friction and restitution which aren't set, and for drag.
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        material: Option<SurfaceMaterial>,
+        /// Mass per unit of area in physics units, 1.0 by default like the player.
+        /// Only used by non-fixed blocks.
+        #[serde(default = "default_density")]
+        density: f32,
+    },
+    Goal {
+        /// Added to [`Environment::goal_reward`] when a player first reaches the goal.
+        #[serde(default = "default_goal_reward")]
+        reward: f32,
+        /// Optional goals only give their reward, reaching them doesn't win the world.
+        /// They are ignored by the distances to goals.
+        #[serde(default)]
+        optional: bool,
+    },
+    /// Applies buoyancy and drag to the bodies whose center is inside it.
+    /// A body floats if the water's density is greater than its density (1.0 for the player and blocks by default).
+    Water { density: f32 },
+    /// A fixed block whose surface moves along the block's x axis at `speed` (in world units per second),
+    /// carrying the bodies in contact with it.
+    Conveyor { speed: f32 },
+    /// Pulls the player and dynamic blocks within `radius` of its center (in world units)
+    /// with an acceleration of `strength / distance²`. A negative strength pushes them away.
+    Attractor { strength: f32, radius: f32 },
+    /// Moves the player to the center of the teleporter with the id `pair` when the player enters it.
+    /// The player has to leave the destination teleporter before it can be used.
+    Teleporter {
+        id: u32,
+        pair: Option<u32>,
+        keep_velocity: bool,
+    },
+    /// Collected when a player touches it, see [`WinCondition::CollectCoins`].
+    Coin,
+    /// A block which moves kinematically through `waypoints` (offsets from its position, in world units)
+    /// at `speed` world units per second, carrying the bodies in contact with it.
+    /// See [`Animation::from_waypoints`].
+    MovingPlatform {
+        waypoints: Vec<[f32; 2]>,
+        speed: f32,
+        #[serde(default)]
+        loop_mode: LoopMode,
+        /// Id of the [`WorldObject::Switch`] which has to be active for the platform to move.
+        /// Platforms without a switch always move.
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        switch: Option<u32>,
+    },
+    /// A fixed block with the shape of a simple polygon, split into convex parts for the physics.
+    /// The vertices are scaled and rotated by the object's transform,
+    /// so the square from -0.5 to 0.5 covers the same area as a block with the same transform.
+    /// Polygons with less than 3 vertices are ignored.
+    Polygon { points: Vec<[f32; 2]> },
+    /// A fixed block which kills the players touching it, ending the episode.
+    /// See [`WorldObject::Enemy`] and [`WorldObject::Saw`] for moving hazards.
+    /// `reward` (usually negative) is added to [`Environment::hazard_reward`] when it kills a player.
+    Hazard {
+        #[serde(default = "default_hazard_reward")]
+        reward: f32,
+    },
+    /// A zone the player can climb while overlapping it, moving at `speed` world units per second
+    /// with up and down (even without the down capability), left and right, with gravity cancelled.
+    Ladder { speed: f32 },
+    /// A zone which becomes the respawn position of the players touching it.
+    /// Players with a checkpoint are moved back to its center instead of dying on a hazard or leaving the bounds.
+    Checkpoint,
+    /// A zone pressed while a player or a dynamic block overlaps it.
+    /// Pressed switches are active, activating the doors and moving platforms linked to their id.
+    /// Toggle switches instead change between active and inactive every time they're pressed.
+    Switch {
+        id: u32,
+        #[serde(default)]
+        toggle: bool,
+    },
+    /// A fixed block which is pressed while a player or a dynamic block rests on top of it.
+    /// Like a [`WorldObject::Switch`], pressed plates activate the doors and moving platforms linked to their id.
+    PressurePlate { id: u32 },
+    /// A fixed block which is removed while a [`WorldObject::Switch`] or [`WorldObject::PressurePlate`]
+    /// with the id `switch` is active.
+    Door { switch: u32 },
+    /// A block which moves kinematically back and forth between its position and its position plus `patrol`
+    /// (in world units) at `speed` world units per second, killing the players touching it like a [`WorldObject::Hazard`].
+    Enemy {
+        patrol: [f32; 2],
+        speed: f32,
+        #[serde(default = "default_hazard_reward")]
+        reward: f32,
+    },
+    /// A round hazard which moves kinematically through `waypoints` like a [`WorldObject::MovingPlatform`],
+    /// killing the players touching it like a [`WorldObject::Hazard`].
+    /// Its diameter is the smaller of its width and height.
+    Saw {
+        waypoints: Vec<[f32; 2]>,
+        speed: f32,
+        #[serde(default)]
+        loop_mode: LoopMode,
+        #[serde(default = "default_hazard_reward")]
+        reward: f32,
+    },
+    /// A fixed block which is solid for `period` physics steps then intangible for `period` physics steps, repeatedly.
+    /// The environment starts `phase` physics steps into this cycle, so the cycle doesn't depend on the decision interval.
+    TimedBlock { period: usize, phase: usize },
+    /// An invisible zone which doesn't affect the physics, whether the first player overlaps it is part of the observations.
+    /// See [`ObservationConfig::sensor_zones`].
+    SensorZone {
+        /// Only used to describe the zone in the editor.
+        #[serde(default)]
+        name: String,
+    },
+    /// A rectangle which is only drawn, it doesn't affect the physics or the observations.
+    Decoration {
+        /// Red, green, blue and alpha components, from 0.0 to 1.0.
+        color: [f32; 4],
+        /// Background decorations are drawn behind every other object,
+        /// other decorations in front of the objects added before them.
+        #[serde(default)]
+        background: bool,
+    },
+    /// A text label drawn at the object's position, for hints like "jump here".
+    /// It doesn't affect the physics or the observations.
+    Text { text: String },
+}
+
+impl WorldObject {
+    pub fn name(&self) -> &'static str {
+        match self {
+            WorldObject::Block { .. } => "Block",
+            WorldObject::Goal { .. } => "Goal",
+            WorldObject::Water { .. } => "Water",
+            WorldObject::Conveyor { .. } => "Conveyor",
+            WorldObject::Attractor { .. } => "Attractor",
+            WorldObject::Teleporter { .. } => "Teleporter",
+            WorldObject::Coin => "Coin",
+            WorldObject::MovingPlatform { .. } => "Moving platform",
+            WorldObject::Polygon { .. } => "Polygon",
+            WorldObject::Hazard { .. } => "Hazard",
+            WorldObject::Ladder { .. } => "Ladder",
+            WorldObject::Checkpoint => "Checkpoint",
+            WorldObject::Switch { .. } => "Switch",
+            WorldObject::PressurePlate { .. } => "Pressure plate",
+            WorldObject::Door { .. } => "Door",
+            WorldObject::Enemy { .. } => "Enemy",
+            WorldObject::Saw { .. } => "Saw",
+            WorldObject::TimedBlock { .. } => "Timed block",
+            WorldObject::SensorZone { .. } => "Sensor zone",
+            WorldObject::Decoration { .. } => "Decoration",
+            WorldObject::Text { .. } => "Text",
+        }
+    }
+
+    #[cfg(feature = "gui")]
+    pub fn color(&self) -> Color {
+        match self {
+            WorldObject::Block {
+                fixed,
+                material: Some(material),
+                ..
+            } => material.color(*fixed),
+            WorldObject::Block { fixed: true, .. } => Color::BLACK,
+            WorldObject::Block { fixed: false, .. } => Color::DARK_GRAY,
+            WorldObject::Goal {
+                optional: false, ..
+            } => Color::rgba(0.0, 1.0, 0.0, 0.5),
+            WorldObject::Goal { optional: true, .. } => Color::rgba(0.7, 1.0, 0.0, 0.35),
+            WorldObject::Water { .. } => Color::rgba(0.0, 0.3, 1.0, 0.4),
+            WorldObject::Conveyor { .. } => Color::rgb(0.2, 0.2, 0.35),
+            WorldObject::Attractor { .. } => Color::PURPLE,
+            WorldObject::Teleporter { pair: Some(_), .. } => Color::rgba(1.0, 0.6, 0.0, 0.5),
+            WorldObject::Teleporter { pair: None, .. } => Color::rgba(1.0, 0.6, 0.0, 0.2),
+            WorldObject::Coin => Color::GOLD,
+            WorldObject::MovingPlatform { .. } => Color::rgb(0.35, 0.2, 0.1),
+            WorldObject::Polygon { .. } => Color::BLACK,
+            WorldObject::Hazard { .. } => Color::RED,
+            WorldObject::Ladder { .. } => Color::rgba(0.6, 0.4, 0.2, 0.6),
+            WorldObject::Checkpoint => Color::rgba(0.0, 0.8, 1.0, 0.4),
+            WorldObject::Switch { .. } => Color::rgba(1.0, 0.0, 1.0, 0.5),
+            WorldObject::PressurePlate { .. } => Color::rgb(0.8, 0.4, 0.8),
+            WorldObject::Door { .. } => Color::rgb(0.5, 0.0, 0.5),
+            WorldObject::Enemy { .. } => Color::CRIMSON,
+            WorldObject::Saw { .. } => Color::ORANGE_RED,
+            WorldObject::TimedBlock { .. } => Color::rgb(0.3, 0.3, 0.45),
+            WorldObject::SensorZone { .. } => Color::rgba(0.5, 0.5, 0.5, 0.25),
+            WorldObject::Decoration {
+                color: [red, green, blue, alpha],
+                ..
+            } => Color::rgba(*red, *green, *blue, *alpha),
+            // Only drawn in the editor, to select the text.
+            WorldObject::Text { .. } => Color::rgba(1.0, 1.0, 0.6, 0.5),
+        }
+    }
+
+    /// Whether the object's shape is shown outside of the editor. Text objects only show their text.
+    pub fn visible(&self) -> bool {
+        !matches!(
+            self,
+            WorldObject::SensorZone { .. } | WorldObject::Text { .. }
+        )
+    }
+
+    /// The mesh used to render the object, scaled by the object's transform.
+    #[cfg(feature = "gui")]
+    pub fn mesh(&self) -> Mesh {
+        match self {
+            WorldObject::Attractor { .. } | WorldObject::Coin | WorldObject::Saw { .. } => {
+                bevy::prelude::shape::Circle::new(0.5).into()
+            }
+            WorldObject::Polygon { points } if points.len() >= 3 => polygon_mesh(points),
+            _ => bevy::prelude::shape::Quad::new(Vec2::ONE).into(),
+        }
+    }
 }
 
 /// The environment for reinforcement learning.
@@ -61,13 +712,83 @@ pub struct Environment {
     rigid_body_set: RigidBodySet,
     collider_set: ColliderSet,
     query_pipeline: QueryPipeline,
-    player_handle: RigidBodyHandle,
-    goals: Vec<GoalDimensions>,
-    won: bool,
+    // The first player is created with the environment, see Environment::add_player.
+    players: Vec<PlayerState>,
+    goals: Vec<ZoneDimensions>,
+    // The rewards of the goals and whether they are optional, in the same order as the goals.
+    goal_properties: Vec<GoalProperties>,
+    // Whether each goal was reached since the start of the episode.
+    reached_goals: Vec<bool>,
+    // Sum of the rewards of the reached goals.
+    goal_reward: f32,
+    // Water zones along with their densities.
+    water: Vec<(ZoneDimensions, f32)>,
+    // Conveyor colliders along with their speeds, in physics units.
+    conveyors: HashMap<ColliderHandle, f32>,
+    // The reward of each hazard, including the enemies.
+    hazards: HashMap<ColliderHandle, f32>,
+    // Ladder sensor colliders along with their climbing speeds, in physics units.
+    ladders: HashMap<ColliderHandle, f32>,
+    attractors: Vec<Attractor>,
+    teleporters: Vec<Teleporter>,
+    // Sensor colliders.
+    checkpoints: Vec<ColliderHandle>,
+    switches: Vec<Switch>,
+    // Fixed rigid bodies along with the id of their switch, disabled while the switch is active.
+    doors: Vec<(RigidBodyHandle, u32)>,
+    animated_blocks: Vec<AnimatedBlock>,
+    // Fixed rigid bodies with a sensor collider, disabled once collected.
+    coins: Vec<RigidBodyHandle>,
+    // The friction and restitution of the block colliders which don't use the world's ones.
+    block_materials: HashMap<ColliderHandle, (Option<f32>, Option<f32>)>,
+    // The drag of the block colliders whose material has drag, see SurfaceMaterial::drag.
+    surface_drag: HashMap<ColliderHandle, f32>,
+    // Rope joints, which Rapier doesn't have, see Environment::apply_rope_constraints.
+    ropes: Vec<Rope>,
+    // Kinematic rigid bodies, in the order of the world's objects.
+    enemies: Vec<RigidBodyHandle>,
+    // Fixed rigid bodies along with their period and phase, disabled while intangible.
+    timed_blocks: Vec<(RigidBodyHandle, usize, usize)>,
+    // Sensor colliders, in the order of the world's objects.
+    sensor_zones: Vec<ColliderHandle>,
+    win_condition: WinCondition,
+    // Simulated time in seconds, used by animations.
+    time: f32,
+    capabilities: Capabilities,
+    stamina: Option<Stamina>,
+    physics: PhysicsConfig,
+    player_shape: PlayerShape,
+    // Used by action noise.
+    rng: StdRng,
+    target_goal: Option<usize>,
+    bounds: Option<Bounds>,
+    spawn_region: Option<Bounds>,
+    // Not restored by reset, so every episode starts at a different position.
+    spawn_rng: StdRng,
+    callbacks: EventCallbacks,
+    // Events raised during the current step, dispatched to the callbacks at the end of the step.
+    pending_events: Vec<EnvEvent>,
+    // The trajectory being recorded, see Environment::record.
+    recording: Option<Trajectory>,
+    distance_field: Option<Arc<DistanceField>>,
+    scratch: StepScratch,
+    config: EnvConfig,
+    reward_fn: Arc<dyn RewardFn>,
+    // The first player to reach a goal.
+    winner: Option<usize>,
+    steps: usize,
+    // Physics steps taken, several per step with a decision interval, merged steps counting as several.
+    physics_steps: usize,
+    // Captured before the first step, used by reset.
+    initial_state: Option<Box<EnvSnapshot>>,
 }
 
 impl Environment {
     pub fn new(player_position: [f32; 2]) -> Environment {
+        Environment::new_with_config(player_position, EnvConfig::default())
+    }
+
+    pub fn new_with_config(player_position: [f32; 2], config: EnvConfig) -> Environment {
         let mut rigid_body_set = RigidBodySet::new();
         let mut collider_set = ColliderSet::new();
 
@@ -78,11 +799,8 @@ impl Environment {
                 player_position[1] * BEVY_TO_PHYSICS_SCALE
             ]);
         let player_handle = rigid_body_set.insert(player_rigid_body);
-        let player_collider = ColliderBuilder::capsule_y(
-            0.5 * PLAYER_DEPTH * BEVY_TO_PHYSICS_SCALE,
-            PLAYER_RADIUS * BEVY_TO_PHYSICS_SCALE,
-        )
-        .build();
+        let player_shape = PlayerShape::default();
+        let player_collider = player_shape.collider().build();
         collider_set.insert_with_parent(player_collider, player_handle, &mut rigid_body_set);
 
         Environment {
@@ -97,12 +815,73 @@ impl Environment {
             rigid_body_set,
             collider_set,
             query_pipeline: QueryPipeline::new(),
-            player_handle,
+            players: vec![PlayerState::new(player_handle)],
             goals: vec![],
-            won: false,
+            goal_properties: vec![],
+            reached_goals: vec![],
+            goal_reward: 0.0,
+            water: vec![],
+            conveyors: HashMap::new(),
+            hazards: HashMap::new(),
+            ladders: HashMap::new(),
+            attractors: vec![],
+            teleporters: vec![],
+            checkpoints: vec![],
+            switches: vec![],
+            doors: vec![],
+            animated_blocks: vec![],
+            coins: vec![],
+            block_materials: HashMap::new(),
+            surface_drag: HashMap::new(),
+            ropes: vec![],
+            enemies: vec![],
+            timed_blocks: vec![],
+            sensor_zones: vec![],
+            win_condition: WinCondition::default(),
+            time: 0.0,
+            capabilities: Capabilities::default(),
+            stamina: None,
+            physics: PhysicsConfig::default(),
+            player_shape,
+            rng: StdRng::seed_from_u64(config.action_noise.map_or(0, |noise| noise.seed)),
+            target_goal: None,
+            bounds: None,
+            spawn_region: None,
+            spawn_rng: StdRng::seed_from_u64(config.spawn_seed),
+            callbacks: EventCallbacks::default(),
+            pending_events: vec![],
+            recording: None,
+            distance_field: None,
+            scratch: StepScratch::default(),
+            config,
+            reward_fn: Arc::new(DenseReward::default()),
+            winner: None,
+            steps: 0,
+            physics_steps: 0,
+            initial_state: None,
         }
     }
 
+    /// Adds a player at the given position and returns its rigid body handle.
+    /// Players are indexed in the order they were added, the first player being the one the environment was created with.
+    /// The other players are controlled with [`Environment::step_multi`], and stand still during [`Environment::step`].
+    pub fn add_player(&mut self, position: [f32; 2]) -> RigidBodyHandle {
+        let rigid_body = RigidBodyBuilder::dynamic()
+            .lock_rotations()
+            .translation(vector![
+                position[0] * BEVY_TO_PHYSICS_SCALE,
+                position[1] * BEVY_TO_PHYSICS_SCALE
+            ]);
+        let handle = self.rigid_body_set.insert(rigid_body);
+        let collider = self.build_collider(self.player_shape.collider());
+        self.collider_set
+            .insert_with_parent(collider, handle, &mut self.rigid_body_set);
+        let mut state = PlayerState::new(handle);
+        state.energy = self.stamina.map_or(0.0, |stamina| stamina.max_energy);
+        self.players.push(state);
+        handle
+    }
+
     /// Adds an object to the environment and returns a RigidBodyHandle if it is a rigid body.
     pub fn add_object(
         &mut self,
@@ -110,19 +889,72 @@ impl Environment {
     ) -> Option<RigidBodyHandle> {
         let object = &object_and_transform.object;
         match object {
-            WorldObject::Block { fixed } => {
+            WorldObject::Block {
+                fixed: true,
+                animation: Some(animation),
+                ..
+            } => Some(self.add_animated_block(object_and_transform, animation.clone(), None)),
+            WorldObject::MovingPlatform {
+                waypoints,
+                speed,
+                loop_mode,
+                switch,
+            } => {
+                let animation = Animation::from_waypoints(waypoints, *speed, *loop_mode);
+                Some(self.add_animated_block(object_and_transform, animation, *switch))
+            }
+            WorldObject::Enemy {
+                patrol,
+                speed,
+                reward,
+            } => {
+                let animation = Animation::from_waypoints(&[*patrol], *speed, LoopMode::PingPong);
+                let rigid_body_handle =
+                    self.add_animated_block(object_and_transform, animation, None);
+                self.hazards.insert(
+                    self.rigid_body_set[rigid_body_handle].colliders()[0],
+                    *reward,
+                );
+                self.enemies.push(rigid_body_handle);
+                Some(rigid_body_handle)
+            }
+            WorldObject::Saw {
+                waypoints,
+                speed,
+                loop_mode,
+                reward,
+            } => {
+                let animation = Animation::from_waypoints(waypoints, *speed, *loop_mode);
+                let radius = 0.5
+                    * object_and_transform.scale[0]
+                        .abs()
+                        .min(object_and_transform.scale[1].abs())
+                    * BEVY_TO_PHYSICS_SCALE;
+                let rigid_body_handle = self.add_animated_body(
+                    object_and_transform,
+                    animation,
+                    None,
+                    ColliderBuilder::ball(radius),
+                );
+                self.hazards.insert(
+                    self.rigid_body_set[rigid_body_handle].colliders()[0],
+                    *reward,
+                );
+                self.enemies.push(rigid_body_handle);
+                Some(rigid_body_handle)
+            }
+            WorldObject::Block {
+                fixed,
+                ccd,
+                density,
+                ..
+            } => {
                 if *fixed {
-                    let collider = ColliderBuilder::cuboid(
-                        0.5 * object_and_transform.scale[0].abs() * BEVY_TO_PHYSICS_SCALE,
-                        0.5 * object_and_transform.scale[1].abs() * BEVY_TO_PHYSICS_SCALE,
-                    )
-                    .translation(vector![
-                        object_and_transform.position[0] * BEVY_TO_PHYSICS_SCALE,
-                        object_and_transform.position[1] * BEVY_TO_PHYSICS_SCALE
-                    ])
-                    .rotation(object_and_transform.rotation)
-                    .build();
-                    self.collider_set.insert(collider);
+                    self.insert_block_collider(
+                        object,
+                        fixed_block_collider(object_and_transform),
+                        None,
+                    );
                     None
                 } else {
                     let rigid_body = RigidBodyBuilder::dynamic()
@@ -130,120 +962,2143 @@ impl Environment {
                             object_and_transform.position[0] * BEVY_TO_PHYSICS_SCALE,
                             object_and_transform.position[1] * BEVY_TO_PHYSICS_SCALE
                         ])
-                        .rotation(object_and_transform.rotation);
+                        .rotation(object_and_transform.rotation)
+                        .ccd_enabled(*ccd);
                     let rigid_body_handle = self.rigid_body_set.insert(rigid_body);
-                    let collider = ColliderBuilder::cuboid(
-                        0.5 * object_and_transform.scale[0].abs() * BEVY_TO_PHYSICS_SCALE,
-                        0.5 * object_and_transform.scale[1].abs() * BEVY_TO_PHYSICS_SCALE,
-                    )
-                    .build();
-                    self.collider_set.insert_with_parent(
-                        collider,
-                        rigid_body_handle,
-                        &mut self.rigid_body_set,
+                    self.insert_block_collider(
+                        object,
+                        ColliderBuilder::cuboid(
+                            0.5 * object_and_transform.scale[0].abs() * BEVY_TO_PHYSICS_SCALE,
+                            0.5 * object_and_transform.scale[1].abs() * BEVY_TO_PHYSICS_SCALE,
+                        )
+                        .density(*density),
+                        Some(rigid_body_handle),
                     );
                     Some(rigid_body_handle)
                 }
             }
-            WorldObject::Goal => {
-                self.goals.push(GoalDimensions {
+            WorldObject::Goal { reward, optional } => {
+                self.goals.push(ZoneDimensions::from_object_and_transform(
+                    object_and_transform,
+                ));
+                self.goal_properties.push(GoalProperties {
+                    reward: *reward,
+                    optional: *optional,
+                });
+                self.reached_goals.push(false);
+                None
+            }
+            WorldObject::Water { density } => {
+                self.water.push((
+                    ZoneDimensions::from_object_and_transform(object_and_transform),
+                    *density,
+                ));
+                None
+            }
+            WorldObject::Conveyor { speed } => {
+                let collider = self.build_collider(
+                    fixed_block_collider(object_and_transform)
+                        .active_hooks(ActiveHooks::MODIFY_SOLVER_CONTACTS),
+                );
+                let collider_handle = self.collider_set.insert(collider);
+                self.conveyors
+                    .insert(collider_handle, speed * BEVY_TO_PHYSICS_SCALE);
+                None
+            }
+            WorldObject::Hazard { reward } => {
+                let collider = self.build_collider(fixed_block_collider(object_and_transform));
+                let collider_handle = self.collider_set.insert(collider);
+                self.hazards.insert(collider_handle, *reward);
+                None
+            }
+            WorldObject::Ladder { speed } => {
+                let collider = fixed_block_collider(object_and_transform)
+                    .sensor(true)
+                    .build();
+                let collider_handle = self.collider_set.insert(collider);
+                self.ladders
+                    .insert(collider_handle, speed * BEVY_TO_PHYSICS_SCALE);
+                None
+            }
+            WorldObject::Decoration { .. } | WorldObject::Text { .. } => None,
+            WorldObject::SensorZone { .. } => {
+                let collider = fixed_block_collider(object_and_transform)
+                    .sensor(true)
+                    .build();
+                self.sensor_zones.push(self.collider_set.insert(collider));
+                None
+            }
+            WorldObject::Checkpoint => {
+                let collider = fixed_block_collider(object_and_transform)
+                    .sensor(true)
+                    .build();
+                self.checkpoints.push(self.collider_set.insert(collider));
+                None
+            }
+            WorldObject::Switch { id, toggle } => {
+                let collider = fixed_block_collider(object_and_transform)
+                    .sensor(true)
+                    .build();
+                self.switches.push(Switch {
+                    collider: self.collider_set.insert(collider),
+                    id: *id,
+                    toggle: *toggle,
+                    plate: false,
+                    pressed: false,
+                    active: false,
+                });
+                None
+            }
+            WorldObject::PressurePlate { id } => {
+                let collider = self.build_collider(fixed_block_collider(object_and_transform));
+                self.switches.push(Switch {
+                    collider: self.collider_set.insert(collider),
+                    id: *id,
+                    toggle: false,
+                    plate: true,
+                    pressed: false,
+                    active: false,
+                });
+                None
+            }
+            WorldObject::Door { switch } => {
+                let rigid_body_handle = self.add_removable_block(object_and_transform);
+                self.doors.push((rigid_body_handle, *switch));
+                Some(rigid_body_handle)
+            }
+            WorldObject::TimedBlock { period, phase } => {
+                let rigid_body_handle = self.add_removable_block(object_and_transform);
+                self.timed_blocks.push((rigid_body_handle, *period, *phase));
+                Some(rigid_body_handle)
+            }
+            WorldObject::Teleporter {
+                id,
+                pair,
+                keep_velocity,
+            } => {
+                let collider = fixed_block_collider(object_and_transform)
+                    .sensor(true)
+                    .build();
+                self.teleporters.push(Teleporter {
+                    collider: self.collider_set.insert(collider),
+                    id: *id,
+                    pair: *pair,
+                    keep_velocity: *keep_velocity,
+                });
+                None
+            }
+            WorldObject::Attractor { strength, radius } => {
+                self.attractors.push(Attractor {
                     x: object_and_transform.position[0] * BEVY_TO_PHYSICS_SCALE,
                     y: object_and_transform.position[1] * BEVY_TO_PHYSICS_SCALE,
-                    width: object_and_transform.scale[0].abs() * BEVY_TO_PHYSICS_SCALE,
-                    height: object_and_transform.scale[1].abs() * BEVY_TO_PHYSICS_SCALE,
-                    rotation: object_and_transform.rotation,
+                    strength: strength * BEVY_TO_PHYSICS_SCALE.powi(3),
+                    radius: radius * BEVY_TO_PHYSICS_SCALE,
                 });
                 None
             }
+            WorldObject::Polygon { points } => {
+                if points.len() < 3 {
+                    return None;
+                }
+                let vertices: Vec<_> = points
+                    .iter()
+                    .map(|[x, y]| {
+                        point![
+                            x * object_and_transform.scale[0] * BEVY_TO_PHYSICS_SCALE,
+                            y * object_and_transform.scale[1] * BEVY_TO_PHYSICS_SCALE
+                        ]
+                    })
+                    .collect();
+                let count = vertices.len() as u32;
+                let segments: Vec<_> = (0..count)
+                    .map(|index| [index, (index + 1) % count])
+                    .collect();
+                let collider = self.build_collider(
+                    ColliderBuilder::convex_decomposition(&vertices, &segments)
+                        .translation(vector![
+                            object_and_transform.position[0] * BEVY_TO_PHYSICS_SCALE,
+                            object_and_transform.position[1] * BEVY_TO_PHYSICS_SCALE
+                        ])
+                        .rotation(object_and_transform.rotation),
+                );
+                self.collider_set.insert(collider);
+                None
+            }
+            WorldObject::Coin => {
+                let rigid_body = RigidBodyBuilder::fixed().translation(vector![
+                    object_and_transform.position[0] * BEVY_TO_PHYSICS_SCALE,
+                    object_and_transform.position[1] * BEVY_TO_PHYSICS_SCALE
+                ]);
+                let rigid_body_handle = self.rigid_body_set.insert(rigid_body);
+                let radius = 0.25
+                    * (object_and_transform.scale[0].abs() + object_and_transform.scale[1].abs());
+                let collider = ColliderBuilder::ball(radius * BEVY_TO_PHYSICS_SCALE)
+                    .sensor(true)
+                    .build();
+                self.collider_set.insert_with_parent(
+                    collider,
+                    rigid_body_handle,
+                    &mut self.rigid_body_set,
+                );
+                self.coins.push(rigid_body_handle);
+                Some(rigid_body_handle)
+            }
+        }
+    }
+
+    // Adds a block moved kinematically by the animation, only while the switch is active if it has one.
+    fn add_animated_block(
+        &mut self,
+        object_and_transform: &ObjectAndTransform,
+        animation: Animation,
+        switch: Option<u32>,
+    ) -> RigidBodyHandle {
+        let collider = ColliderBuilder::cuboid(
+            0.5 * object_and_transform.scale[0].abs() * BEVY_TO_PHYSICS_SCALE,
+            0.5 * object_and_transform.scale[1].abs() * BEVY_TO_PHYSICS_SCALE,
+        );
+        self.add_animated_body(object_and_transform, animation, switch, collider)
+    }
+
+    // Adds a kinematic rigid body with the collider, moved by the animation, see Environment::add_animated_block.
+    fn add_animated_body(
+        &mut self,
+        object_and_transform: &ObjectAndTransform,
+        animation: Animation,
+        switch: Option<u32>,
+        collider: ColliderBuilder,
+    ) -> RigidBodyHandle {
+        let (offset, rotation) = animation.sample(0.0);
+        let position = vector![
+            object_and_transform.position[0] * BEVY_TO_PHYSICS_SCALE,
+            object_and_transform.position[1] * BEVY_TO_PHYSICS_SCALE
+        ];
+        let rigid_body = RigidBodyBuilder::kinematic_position_based()
+            .translation(position + vector![offset[0], offset[1]] * BEVY_TO_PHYSICS_SCALE)
+            .rotation(object_and_transform.rotation + rotation);
+        let rigid_body_handle = self.rigid_body_set.insert(rigid_body);
+        self.insert_block_collider(
+            &object_and_transform.object,
+            collider,
+            Some(rigid_body_handle),
+        );
+        self.animated_blocks.push(AnimatedBlock {
+            rigid_body: rigid_body_handle,
+            position,
+            rotation: object_and_transform.rotation,
+            animation,
+            switch,
+            time: 0.0,
+        });
+        rigid_body_handle
+    }
+
+    // Adds a block with a fixed rigid body, so it can be removed by disabling the rigid body.
+    fn add_removable_block(
+        &mut self,
+        object_and_transform: &ObjectAndTransform,
+    ) -> RigidBodyHandle {
+        let rigid_body = RigidBodyBuilder::fixed()
+            .translation(vector![
+                object_and_transform.position[0] * BEVY_TO_PHYSICS_SCALE,
+                object_and_transform.position[1] * BEVY_TO_PHYSICS_SCALE
+            ])
+            .rotation(object_and_transform.rotation);
+        let rigid_body_handle = self.rigid_body_set.insert(rigid_body);
+        let collider = self.build_collider(ColliderBuilder::cuboid(
+            0.5 * object_and_transform.scale[0].abs() * BEVY_TO_PHYSICS_SCALE,
+            0.5 * object_and_transform.scale[1].abs() * BEVY_TO_PHYSICS_SCALE,
+        ));
+        self.collider_set
+            .insert_with_parent(collider, rigid_body_handle, &mut self.rigid_body_set);
+        rigid_body_handle
+    }
+
+    // A fixed rigid body without colliders at the object's position, to attach joints to.
+    fn add_anchor_body(&mut self, object_and_transform: &ObjectAndTransform) -> RigidBodyHandle {
+        self.rigid_body_set.insert(
+            RigidBodyBuilder::fixed()
+                .translation(vector![
+                    object_and_transform.position[0] * BEVY_TO_PHYSICS_SCALE,
+                    object_and_transform.position[1] * BEVY_TO_PHYSICS_SCALE
+                ])
+                .rotation(object_and_transform.rotation),
+        )
+    }
+
+    fn add_joint(&mut self, joint: &WorldJoint, rigid_bodies: [RigidBodyHandle; 2]) {
+        let anchors = joint
+            .anchors
+            .map(|[x, y]| point![x * BEVY_TO_PHYSICS_SCALE, y * BEVY_TO_PHYSICS_SCALE]);
+        let generic_joint: GenericJoint = match joint.kind {
+            JointKind::Revolute => RevoluteJointBuilder::new()
+                .local_anchor1(anchors[0])
+                .local_anchor2(anchors[1])
+                .into(),
+            JointKind::Prismatic { axis, limits } => {
+                let Some(axis) = UnitVector::try_new(vector![axis[0], axis[1]], 1e-6) else {
+                    return;
+                };
+                let mut builder = PrismaticJointBuilder::new(axis)
+                    .local_anchor1(anchors[0])
+                    .local_anchor2(anchors[1]);
+                if let Some([min, max]) = limits {
+                    builder =
+                        builder.limits([min * BEVY_TO_PHYSICS_SCALE, max * BEVY_TO_PHYSICS_SCALE]);
+                }
+                builder.into()
+            }
+            JointKind::Rope { length } => {
+                self.ropes.push(Rope {
+                    rigid_bodies,
+                    anchors,
+                    length: length * BEVY_TO_PHYSICS_SCALE,
+                });
+                return;
+            }
+        };
+        self.impulse_joint_set
+            .insert(rigid_bodies[0], rigid_bodies[1], generic_joint, true);
+    }
+
+    // Builds a collider with the world's friction and restitution.
+    fn build_collider(&self, collider: ColliderBuilder) -> Collider {
+        collider
+            .friction(self.physics.friction)
+            .restitution(self.physics.restitution)
+            .build()
+    }
+
+    // Inserts the collider of an object, with the friction and restitution of the object if it's a block which has them,
+    // either directly or through its material.
+    fn insert_block_collider(
+        &mut self,
+        object: &WorldObject,
+        collider: ColliderBuilder,
+        parent: Option<RigidBodyHandle>,
+    ) -> ColliderHandle {
+        let mut collider = self.build_collider(collider);
+        let (friction, restitution, material) = match object {
+            WorldObject::Block {
+                friction,
+                restitution,
+                material,
+                ..
+            } => (
+                friction.or(material.map(|material| material.friction())),
+                restitution.or(material.map(|material| material.restitution())),
+                *material,
+            ),
+            _ => (None, None, None),
+        };
+        if let Some(friction) = friction {
+            collider.set_friction(friction);
+        }
+        if let Some(restitution) = restitution {
+            collider.set_restitution(restitution);
+        }
+        let handle = match parent {
+            Some(parent) => {
+                self.collider_set
+                    .insert_with_parent(collider, parent, &mut self.rigid_body_set)
+            }
+            None => self.collider_set.insert(collider),
+        };
+        if friction.is_some() || restitution.is_some() {
+            self.block_materials.insert(handle, (friction, restitution));
+        }
+        if let Some(material) = material.filter(|material| material.drag() > 0.0) {
+            self.surface_drag.insert(handle, material.drag());
         }
+        handle
     }
 
-    /// Creates an environment from a world and returns the world along with rigid body handles for the objects in the world (not the player).
+    /// Creates an environment from a world and returns the world along with rigid body handles for the objects in the world (not the players).
     pub fn from_world(world: &World) -> (Environment, Vec<Option<RigidBodyHandle>>) {
-        let mut environment = Environment::new(world.player_position);
+        Environment::from_world_with_config(world, EnvConfig::default())
+    }
+
+    /// Creates an environment from a world with its [`Randomization`] applied, see [`Environment::from_world`].
+    /// Each seed gives a slightly different level, so using a new seed for every episode
+    /// keeps agents from overfitting to the exact level.
+    pub fn from_world_randomized(
+        world: &World,
+        seed: u64,
+    ) -> (Environment, Vec<Option<RigidBodyHandle>>) {
+        let world = world
+            .randomization
+            .apply(world, &mut StdRng::seed_from_u64(seed));
+        Environment::from_world(&world)
+    }
+
+    /// Creates an environment from a world with the given configuration.
+    /// See [`Environment::from_world`].
+    pub fn from_world_with_config(
+        world: &World,
+        config: EnvConfig,
+    ) -> (Environment, Vec<Option<RigidBodyHandle>>) {
+        let mut environment = Environment::new_with_config(world.player_position, config);
+        environment.capabilities = world.capabilities;
+        environment.set_stamina(world.stamina);
+        environment.win_condition = world.win_condition;
+        environment.set_physics_config(world.physics);
+        environment.set_solver_config(world.solver);
+        environment.set_bounds(world.bounds);
+        environment.set_player_shape(world.player_shape);
+        for position in &world.other_players {
+            environment.add_player(*position);
+        }
         let mut rigid_body_handles = vec![];
 
         for object_and_transform in world.objects.iter() {
             let rigid_body_handle = environment.add_object(object_and_transform);
             rigid_body_handles.push(rigid_body_handle);
         }
+        // Objects without a rigid body get a fixed one when they're connected by a joint.
+        let mut joint_rigid_bodies = rigid_body_handles.clone();
+        for joint in &world.joints {
+            let [first, second] = joint.objects;
+            if first == second || first.max(second) >= world.objects.len() {
+                continue;
+            }
+            let rigid_bodies = joint.objects.map(|index| {
+                *joint_rigid_bodies[index]
+                    .get_or_insert_with(|| environment.add_anchor_body(&world.objects[index]))
+            });
+            environment.add_joint(joint, rigid_bodies);
+        }
+        environment.set_spawn_region(world.spawn_region);
+
+        environment
+            .query_pipeline
+            .update(&environment.rigid_body_set, &environment.collider_set);
+
+        if let Some(cell_size) = environment.config.path_distance {
+            environment.distance_field =
+                Some(Arc::new(environment.compute_distance_field(cell_size)));
+        }
 
         (environment, rigid_body_handles)
     }
 
     /// Minimum distance from the center of the player to the goals.
-    pub fn distance_to_goals(&self) -> Option<f32> {
-        let player_translation = self.rigid_body_set[self.player_handle].translation();
-        let player_translation = Vec2::new(player_translation.x, player_translation.y);
+    /// Returns [`Error::NoGoals`] if the environment doesn't have any goals.
+    pub fn distance_to_goals(&self) -> Result<f32> {
+        self.player_distance_to_goals(0).ok_or(Error::NoGoals)
+    }
+
+    /// Minimum distance from the center of each player to the goals, see [`Environment::distance_to_goals`].
+    pub fn player_distances_to_goals(&self) -> Result<Vec<f32>> {
+        (0..self.players.len())
+            .map(|player| self.player_distance_to_goals(player).ok_or(Error::NoGoals))
+            .collect()
+    }
+
+    fn player_distance_to_goals(&self, player: usize) -> Option<f32> {
+        let player_translation = self.player_physics_translation(player);
+        self.required_goals()
+            .map(|(_, goal)| goal.distance(player_translation) / BEVY_TO_PHYSICS_SCALE)
+            .reduce(f32::min)
+    }
 
+    // The goals which win the environment, optional goals only give their reward.
+    fn required_goals(&self) -> impl Iterator<Item = (usize, &ZoneDimensions)> + '_ {
         self.goals
             .iter()
-            .map(|goal| {
-                let goal_translation = Vec2::new(goal.x, goal.y);
-                let x_axis = (Quat::from_rotation_z(goal.rotation) * Vec3::X).truncate();
-                let y_axis = (Quat::from_rotation_z(goal.rotation) * Vec3::Y).truncate();
-
-                let distance_x = ((player_translation - goal_translation).dot(x_axis).abs()
-                    - goal.width / 2.0)
-                    .max(0.0);
-                let distance_y = ((player_translation - goal_translation).dot(y_axis).abs()
-                    - goal.height / 2.0)
-                    .max(0.0);
-                (distance_x.powi(2) + distance_y.powi(2)).sqrt() / BEVY_TO_PHYSICS_SCALE
-            })
-            .reduce(f32::min)
+            .enumerate()
+            .filter(|(index, _)| !self.goal_properties[*index].optional)
     }
 
-    pub fn won(&self) -> bool {
-        self.won
+    /// Distance from the center of the player to the goal with the given index.
+    /// Goals are indexed in the order they were added to the environment.
+    pub fn distance_to_goal(&self, goal: usize) -> Result<f32> {
+        let player_translation = self.player_physics_translation(0);
+        self.goals
+            .get(goal)
+            .map(|goal| goal.distance(player_translation) / BEVY_TO_PHYSICS_SCALE)
+            .ok_or(Error::GoalOutOfRange {
+                goal,
+                goal_count: self.goals.len(),
+            })
     }
 
-    pub fn rigid_body_set(&self) -> &RigidBodySet {
-        &self.rigid_body_set
+    /// Distance from the center of the player to the goals, walking around the fixed blocks,
+    /// using the environment's [`DistanceField`] (see [`EnvConfig::path_distance`]).
+    /// Falls back to [`Environment::distance_to_goals`] if the environment doesn't have a distance field.
+    /// Returns infinity if the goals can't be reached.
+    pub fn path_distance_to_goals(&self) -> Result<f32> {
+        let distance = self.distance_to_goals()?;
+        match &self.distance_field {
+            Some(distance_field) if distance > 0.0 => Ok(distance_field
+                .distance(self.player_position())
+                .max(distance)),
+            _ => Ok(distance),
+        }
     }
 
-    pub fn collider_set(&self) -> &ColliderSet {
-        &self.collider_set
+    pub fn distance_field(&self) -> Option<&Arc<DistanceField>> {
+        self.distance_field.as_ref()
     }
 
-    pub fn player_handle(&self) -> RigidBodyHandle {
-        self.player_handle
+    /// Sets the distance field used by [`Environment::path_distance_to_goals`].
+    /// A field computed for an environment can be shared by other environments created from the same world.
+    pub fn set_distance_field(&mut self, distance_field: Option<Arc<DistanceField>>) {
+        self.distance_field = distance_field;
     }
 
-    /// Move the environment forward by a single time step, with the player playing the given move.
-    /// If the distance to goals is (approximately) 0.0, the environment is set to be won.
-    pub fn step(&mut self, player_move: Move) {
-        let player_translation = self.rigid_body_set[self.player_handle].translation();
-        let player_lower_center = vector![
-            player_translation.x,
-            player_translation.y - PLAYER_DEPTH * BEVY_TO_PHYSICS_SCALE / 2.0
+    /// Computes a [`DistanceField`] with square cells of the given size (in world units) covering the objects.
+    /// A cell is blocked when a fixed block is within half a cell of its center.
+    /// The grid is limited to about a million cells, so larger cells are used for large worlds.
+    pub fn compute_distance_field(&self, cell_size: f32) -> DistanceField {
+        const MAX_CELLS: f32 = 1_000_000.0;
+        const PADDING_CELLS: f32 = 4.0;
+
+        // The extent of the colliders, goals and players, in physics units.
+        let mut min = vector![f32::INFINITY, f32::INFINITY];
+        let mut max = vector![f32::NEG_INFINITY, f32::NEG_INFINITY];
+        let mut include = |point: Vector<Real>| {
+            min = min.inf(&point);
+            max = max.sup(&point);
+        };
+        for (_, collider) in self.collider_set.iter() {
+            let aabb = collider.compute_aabb();
+            include(aabb.mins.coords);
+            include(aabb.maxs.coords);
+        }
+        for goal in &self.goals {
+            let half_size = (goal.width + goal.height) / 2.0;
+            include(vector![goal.x - half_size, goal.y - half_size]);
+            include(vector![goal.x + half_size, goal.y + half_size]);
+        }
+        for player in 0..self.players.len() {
+            let translation = self.player_physics_translation(player);
+            include(vector![translation.x, translation.y]);
+        }
+
+        let size = (max - min) / BEVY_TO_PHYSICS_SCALE;
+        let cell_size = cell_size.max(1.0).max((size.x * size.y / MAX_CELLS).sqrt());
+        let origin = [
+            min.x / BEVY_TO_PHYSICS_SCALE - PADDING_CELLS * cell_size,
+            min.y / BEVY_TO_PHYSICS_SCALE - PADDING_CELLS * cell_size,
         ];
+        let columns = (size.x / cell_size + 2.0 * PADDING_CELLS).ceil() as usize;
+        let rows = (size.y / cell_size + 2.0 * PADDING_CELLS).ceil() as usize;
 
-        let mut player_floor_contacts = vec![];
-        let player_collider = self.rigid_body_set[self.player_handle].colliders()[0];
-        for contact_pair in self.narrow_phase.contacts_with(player_collider) {
-            let contact_collider = if contact_pair.collider1 != player_collider {
-                contact_pair.collider1
-            } else {
-                contact_pair.collider2
-            };
-            let rigid_body = self.collider_set[contact_collider].parent();
-            if contact_pair.has_any_active_contact {
-                for manifold in &contact_pair.manifolds {
-                    for solver_contact in &manifold.data.solver_contacts {
-                        let player_floor_contact = (solver_contact.point - player_lower_center)
-                            / (PLAYER_RADIUS * BEVY_TO_PHYSICS_SCALE);
-                        if player_floor_contact.y < -0.707 {
-                            player_floor_contacts.push((solver_contact.point, rigid_body));
-                        }
-                    }
-                }
+        let cell_shape = rapier2d::parry::shape::Ball::new(cell_size / 2.0 * BEVY_TO_PHYSICS_SCALE);
+        let filter = QueryFilter::default().exclude_sensors().exclude_dynamic();
+        let mut blocked = vec![false; columns * rows];
+        let mut goals = vec![false; columns * rows];
+        for row in 0..rows {
+            for column in 0..columns {
+                let center = vector![
+                    origin[0] + (column as f32 + 0.5) * cell_size,
+                    origin[1] + (row as f32 + 0.5) * cell_size
+                ] * BEVY_TO_PHYSICS_SCALE;
+                let index = row * columns + column;
+                blocked[index] = self
+                    .query_pipeline
+                    .intersection_with_shape(
+                        &self.rigid_body_set,
+                        &self.collider_set,
+                        &Isometry::translation(center.x, center.y),
+                        &cell_shape,
+                        filter,
+                    )
+                    .is_some();
+                goals[index] = self.required_goals().any(|(_, goal)| {
+                    goal.distance(Vec2::new(center.x, center.y)) <= cell_shape.radius
+                });
             }
         }
 
-        let on_ground = !player_floor_contacts.is_empty();
+        DistanceField::compute(origin, cell_size, columns, rows, &blocked, &goals)
+    }
 
+    pub fn goal_count(&self) -> usize {
+        self.goals.len()
+    }
+
+    /// Index of the goal closest to the player, or None if the environment doesn't have any goals.
+    /// Optional goals are ignored.
+    pub fn nearest_goal(&self) -> Option<usize> {
+        self.player_nearest_goal(0)
+    }
+
+    fn player_nearest_goal(&self, player: usize) -> Option<usize> {
+        let player_translation = self.player_physics_translation(player);
+        self.required_goals()
+            .map(|(index, goal)| (index, goal.distance(player_translation)))
+            .min_by(|(_, distance1), (_, distance2)| distance1.total_cmp(distance2))
+            .map(|(index, _)| index)
+    }
+
+    /// Sum of the rewards of the goals reached since the start of the episode, see [`WorldObject::Goal`].
+    pub fn goal_reward(&self) -> f32 {
+        self.goal_reward
+    }
+
+    /// Indices of the goals reached since the start of the episode, including optional goals.
+    pub fn reached_goals(&self) -> Vec<usize> {
+        (0..self.goals.len())
+            .filter(|goal| self.reached_goals[*goal])
+            .collect()
+    }
+
+    pub fn target_goal(&self) -> Option<usize> {
+        self.target_goal
+    }
+
+    /// Sets the goal used by goal-conditioned observations.
+    /// If no target goal is set, the nearest goal is used.
+    pub fn set_target_goal(&mut self, goal: Option<usize>) -> Result<()> {
+        if let Some(goal) = goal {
+            if goal >= self.goals.len() {
+                return Err(Error::GoalOutOfRange {
+                    goal,
+                    goal_count: self.goals.len(),
+                });
+            }
+        }
+        self.target_goal = goal;
+        Ok(())
+    }
+
+    pub fn capabilities(&self) -> Capabilities {
+        self.capabilities
+    }
+
+    /// Sets the optional moves the player can use.
+    /// Environments created with [`Environment::from_world`] use the world's capabilities.
+    pub fn set_capabilities(&mut self, capabilities: Capabilities) {
+        self.capabilities = capabilities;
+    }
+
+    /// Whether the environment has ladders, which can be climbed down with [`Move::down`]
+    /// even without the down capability.
+    pub fn has_ladders(&self) -> bool {
+        !self.ladders.is_empty()
+    }
+
+    pub fn stamina(&self) -> Option<Stamina> {
+        self.stamina
+    }
+
+    /// Sets the energy meter of the players, refilling their energy.
+    /// Environments created with [`Environment::from_world`] use the world's stamina.
+    pub fn set_stamina(&mut self, stamina: Option<Stamina>) {
+        self.stamina = stamina;
+        for state in &mut self.players {
+            state.energy = stamina.map_or(0.0, |stamina| stamina.max_energy);
+        }
+    }
+
+    /// Energy of the first player, or None if the environment doesn't have [`Stamina`].
+    pub fn energy(&self) -> Option<f32> {
+        self.player_energy(0)
+    }
+
+    /// Energy of each player, see [`Environment::energy`].
+    pub fn player_energy(&self, player: usize) -> Option<f32> {
+        self.stamina.map(|_| self.players[player].energy)
+    }
+
+    pub fn win_condition(&self) -> WinCondition {
+        self.win_condition
+    }
+
+    /// Sets what the players have to do to win, taking effect from the next step.
+    /// Environments created with [`Environment::from_world`] use the world's win condition.
+    pub fn set_win_condition(&mut self, win_condition: WinCondition) {
+        self.win_condition = win_condition;
+    }
+
+    /// Number of coins which haven't been collected yet.
+    pub fn coins_remaining(&self) -> usize {
+        self.coins
+            .iter()
+            .filter(|coin| self.rigid_body_set[**coin].is_enabled())
+            .count()
+    }
+
+    pub fn physics_config(&self) -> PhysicsConfig {
+        self.physics
+    }
+
+    /// Sets the physics settings.
+    /// Environments created with [`Environment::from_world`] use the world's physics settings.
+    pub fn set_physics_config(&mut self, physics: PhysicsConfig) {
+        self.physics = physics;
+        for (handle, collider) in self.collider_set.iter_mut() {
+            if !collider.is_sensor() {
+                let (friction, restitution) = self
+                    .block_materials
+                    .get(&handle)
+                    .copied()
+                    .unwrap_or_default();
+                collider.set_friction(friction.unwrap_or(physics.friction));
+                collider.set_restitution(restitution.unwrap_or(physics.restitution));
+            }
+        }
+    }
+
+    pub fn solver_config(&self) -> SolverConfig {
+        SolverConfig {
+            dt: self.integration_parameters.dt,
+            velocity_iterations: self.integration_parameters.max_velocity_iterations,
+            stabilization_iterations: self.integration_parameters.max_stabilization_iterations,
+        }
+    }
+
+    /// Sets the settings of the physics solver, taking effect from the next step.
+    /// Environments created with [`Environment::from_world`] use the world's solver settings.
+    pub fn set_solver_config(&mut self, solver: SolverConfig) {
+        self.integration_parameters = solver.integration_parameters();
+    }
+
+    pub fn player_shape(&self) -> PlayerShape {
+        self.player_shape
+    }
+
+    /// Replaces the player's collider by one with the given shape.
+    /// Environments created with [`Environment::from_world`] use the world's player shape.
+    pub fn set_player_shape(&mut self, player_shape: PlayerShape) {
+        self.player_shape = player_shape;
+        for player in 0..self.players.len() {
+            let player_handle = self.players[player].handle;
+            let old_collider = self.rigid_body_set[player_handle].colliders()[0];
+            self.collider_set.remove(
+                old_collider,
+                &mut self.island_manager,
+                &mut self.rigid_body_set,
+                true,
+            );
+            let player_collider = self.build_collider(player_shape.collider());
+            self.collider_set.insert_with_parent(
+                player_collider,
+                player_handle,
+                &mut self.rigid_body_set,
+            );
+        }
+    }
+
+    pub fn bounds(&self) -> Option<Bounds> {
+        self.bounds
+    }
+
+    pub fn spawn_region(&self) -> Option<Bounds> {
+        self.spawn_region
+    }
+
+    /// Sets the rectangle the first player starts in, moving the player to a random position in it.
+    /// Every [`Environment::reset`] picks a new position, using [`EnvConfig::spawn_seed`].
+    /// The region should be free of blocks, as the player is moved without checking for overlaps.
+    pub fn set_spawn_region(&mut self, spawn_region: Option<Bounds>) {
+        self.spawn_region = spawn_region;
+        self.respawn();
+    }
+
+    // Moves the first player to a random position in the spawn region, if there is one.
+    fn respawn(&mut self) {
+        let Some(spawn_region) = self.spawn_region else {
+            return;
+        };
+        let position = [0, 1].map(|axis| {
+            if spawn_region.min[axis] < spawn_region.max[axis] {
+                self.spawn_rng
+                    .gen_range(spawn_region.min[axis]..spawn_region.max[axis])
+            } else {
+                spawn_region.min[axis]
+            }
+        });
+        let rigid_body = &mut self.rigid_body_set[self.players[0].handle];
+        rigid_body.set_translation(
+            vector![
+                position[0] * BEVY_TO_PHYSICS_SCALE,
+                position[1] * BEVY_TO_PHYSICS_SCALE
+            ],
+            true,
+        );
+        rigid_body.set_linvel(vector![0.0, 0.0], true);
+        self.query_pipeline
+            .update(&self.rigid_body_set, &self.collider_set);
+    }
+
+    /// Sets the rectangle the players should stay in, leaving it ends the episode
+    /// unless the player reached a [`WorldObject::Checkpoint`].
+    /// See [`Environment::out_of_bounds`] and [`Environment::on_left_bounds`].
+    /// Environments created with [`Environment::from_world`] use the world's bounds.
+    pub fn set_bounds(&mut self, bounds: Option<Bounds>) {
+        self.bounds = bounds;
+    }
+
+    /// Calls `callback` after every step for each contact a player started or stopped during the step,
+    /// with the environment after the step.
+    pub fn on_contact(
+        &mut self,
+        callback: impl FnMut(&ContactEvent, &Environment) + Send + Sync + 'static,
+    ) {
+        self.callbacks.contact.push(Box::new(callback));
+    }
+
+    /// Calls `callback` after the step in which a player reached a goal and won the environment.
+    pub fn on_goal_reached(
+        &mut self,
+        callback: impl FnMut(&GoalEvent, &Environment) + Send + Sync + 'static,
+    ) {
+        self.callbacks.goal_reached.push(Box::new(callback));
+    }
+
+    /// Calls `callback` after the steps in which a player left the environment's [`Bounds`].
+    /// The callback is called again if the player comes back and leaves again.
+    pub fn on_left_bounds(
+        &mut self,
+        callback: impl FnMut(&BoundsEvent, &Environment) + Send + Sync + 'static,
+    ) {
+        self.callbacks.left_bounds.push(Box::new(callback));
+    }
+
+    /// Removes the callbacks registered with [`Environment::on_contact`], [`Environment::on_goal_reached`]
+    /// and [`Environment::on_left_bounds`].
+    pub fn clear_callbacks(&mut self) {
+        self.callbacks = EventCallbacks::default();
+    }
+
+    /// Starts recording the first player's position, velocity and applied move after every step,
+    /// discarding the previous recording. Resetting the environment restarts the recording.
+    pub fn record(&mut self) {
+        self.recording = Some(Trajectory::new(self.player_position()));
+    }
+
+    /// The trajectory recorded since [`Environment::record`] was called, or None if the environment isn't recording.
+    pub fn trajectory(&self) -> Option<&Trajectory> {
+        self.recording.as_ref()
+    }
+
+    /// Stops recording and returns the recorded trajectory.
+    pub fn stop_recording(&mut self) -> Option<Trajectory> {
+        self.recording.take()
+    }
+
+    pub fn config(&self) -> &EnvConfig {
+        &self.config
+    }
+
+    /// Sets the reward function used by [`Environment::step_full`].
+    /// The default is [`DenseReward::default`], the decrease in the distance to goals.
+    pub fn set_reward_fn(&mut self, reward_fn: impl RewardFn + 'static) {
+        self.reward_fn = Arc::new(reward_fn);
+    }
+
+    pub fn decision_interval(&self) -> usize {
+        self.config.decision_interval
+    }
+
+    /// Sets the number of physics steps simulated by every [`Environment::step`] (at least 1),
+    /// so agents only decide a move every `decision_interval` physics steps.
+    /// The move is applied on every physics step, and the remaining physics steps are skipped once the environment is won.
+    pub fn set_decision_interval(&mut self, decision_interval: usize) {
+        self.config.decision_interval = decision_interval.max(1);
+    }
+
+    pub fn observation_config(&self) -> ObservationConfig {
+        self.config.observation
+    }
+
+    pub fn set_observation_config(&mut self, observation_config: ObservationConfig) {
+        self.config.observation = observation_config;
+    }
+
+    /// Returns the observation of the environment as a flat vector,
+    /// using the environment's [`ObservationConfig`].
+    pub fn state(&self) -> Vec<f32> {
+        self.observe(&self.config.observation)
+    }
+
+    /// Returns the observation of the environment using a custom [`Observation`].
+    pub fn observe(&self, observation: &impl Observation) -> Vec<f32> {
+        let mut state = vec![];
+        observation.observe(self, &mut state);
+        state
+    }
+
+    /// Index of the target goal, or the nearest goal if no target goal is set.
+    fn observed_goal(&self) -> Option<usize> {
+        self.target_goal.or_else(|| self.nearest_goal())
+    }
+
+    /// A lidar-like sensor: distances from the center of the player to the nearest collider along
+    /// `number_of_rays` evenly spaced directions, starting from the +x axis and going counterclockwise.
+    /// Distances are in the same units as [`World`] positions and are capped at `max_distance`.
+    /// The player's own collider is ignored, other players are obstacles.
+    pub fn raycast_observation(&self, number_of_rays: usize, max_distance: f32) -> Vec<f32> {
+        let player_handle = self.players[0].handle;
+        let player_translation = self.rigid_body_set[player_handle].translation();
+        let filter = QueryFilter::default()
+            .exclude_sensors()
+            .exclude_rigid_body(player_handle);
+        (0..number_of_rays)
+            .map(|index| {
+                let angle = 2.0 * std::f32::consts::PI * index as f32 / number_of_rays as f32;
+                let ray = rapier2d::prelude::Ray::new(
+                    point![player_translation.x, player_translation.y],
+                    vector![angle.cos(), angle.sin()],
+                );
+                self.query_pipeline
+                    .cast_ray(
+                        &self.rigid_body_set,
+                        &self.collider_set,
+                        &ray,
+                        max_distance * BEVY_TO_PHYSICS_SCALE,
+                        true,
+                        filter,
+                    )
+                    .map_or(max_distance, |(_, toi)| toi / BEVY_TO_PHYSICS_SCALE)
+            })
+            .collect()
+    }
+
+    /// Whether the environment's [`WinCondition`] was met.
+    pub fn won(&self) -> bool {
+        self.winner.is_some()
+    }
+
+    /// Index of the player who met the win condition, see [`Environment::add_player`].
+    /// If several players reach a goal during the same physics step, the lowest index wins.
+    /// Conditions which aren't met by a single player ([`WinCondition::Survive`] and [`WinCondition::PushBlock`])
+    /// are won by the first player.
+    pub fn winner(&self) -> Option<usize> {
+        self.winner
+    }
+
+    /// Whether the episode has ended, by winning, by a player leaving the bounds or by a player touching a hazard.
+    pub fn done(&self) -> bool {
+        self.won() || self.out_of_bounds() || self.dead()
+    }
+
+    /// Whether a player was killed by a [`WorldObject::Hazard`] since the start of the episode.
+    pub fn dead(&self) -> bool {
+        self.players.iter().any(|player| player.killed_by.is_some())
+    }
+
+    /// Sum of the rewards of the hazards which killed players since the start of the episode,
+    /// including the players moved back to their checkpoint.
+    pub fn hazard_reward(&self) -> f32 {
+        self.players
+            .iter()
+            .map(|player| {
+                player.respawn_hazard_reward
+                    + player.killed_by.map_or(0.0, |hazard| self.hazards[&hazard])
+            })
+            .sum()
+    }
+
+    /// Whether a [`WorldObject::Switch`] or [`WorldObject::PressurePlate`] with the id is active.
+    pub fn switch_active(&self, id: u32) -> bool {
+        self.switches
+            .iter()
+            .any(|switch| switch.id == id && switch.active)
+    }
+
+    /// Number of times players were moved back to their checkpoint since the start of the episode,
+    /// see [`WorldObject::Checkpoint`].
+    pub fn respawns(&self) -> usize {
+        self.players.iter().map(|player| player.respawns).sum()
+    }
+
+    /// Whether a player left the environment's bounds since the start of the episode, see [`Environment::set_bounds`].
+    pub fn out_of_bounds(&self) -> bool {
+        self.players.iter().any(|player| player.left_bounds)
+    }
+
+    /// Number of steps taken since the environment was created or reset.
+    pub fn steps(&self) -> usize {
+        self.steps
+    }
+
+    /// The move applied by the last step, which differs from the given move when [`ActionNoise`] changed it.
+    pub fn last_move(&self) -> Move {
+        self.players[0].last_move
+    }
+
+    pub fn rigid_body_set(&self) -> &RigidBodySet {
+        &self.rigid_body_set
+    }
+
+    pub fn collider_set(&self) -> &ColliderSet {
+        &self.collider_set
+    }
+
+    /// The rigid body handle of the first player.
+    pub fn player_handle(&self) -> RigidBodyHandle {
+        self.players[0].handle
+    }
+
+    pub fn player_count(&self) -> usize {
+        self.players.len()
+    }
+
+    /// The rigid body handles of the players, in the order they were added.
+    pub fn player_handles(&self) -> Vec<RigidBodyHandle> {
+        self.players.iter().map(|player| player.handle).collect()
+    }
+
+    /// The position of the center of the player, in the same units as [`World`] positions.
+    pub fn player_position(&self) -> [f32; 2] {
+        (self.player_physics_translation(0) / BEVY_TO_PHYSICS_SCALE).to_array()
+    }
+
+    /// The positions of the centers of the players, see [`Environment::player_position`].
+    pub fn player_positions(&self) -> Vec<[f32; 2]> {
+        (0..self.players.len())
+            .map(|player| {
+                (self.player_physics_translation(player) / BEVY_TO_PHYSICS_SCALE).to_array()
+            })
+            .collect()
+    }
+
+    /// The velocity of the player, in world units per second.
+    pub fn player_velocity(&self) -> [f32; 2] {
+        let player_velocity =
+            self.rigid_body_set[self.players[0].handle].linvel() / BEVY_TO_PHYSICS_SCALE;
+        [player_velocity.x, player_velocity.y]
+    }
+
+    /// Whether the player is standing on something, so it can move and jump.
+    pub fn on_ground(&self) -> bool {
+        self.player_contacts(0).iter().any(|contact| contact.floor)
+    }
+
+    /// The points where the player touches the ground, in the same units as [`World`] positions.
+    pub fn floor_contact_points(&self) -> Vec<[f32; 2]> {
+        self.player_contacts(0)
+            .iter()
+            .filter(|contact| contact.floor)
+            .map(|contact| {
+                [
+                    contact.point.x / BEVY_TO_PHYSICS_SCALE,
+                    contact.point.y / BEVY_TO_PHYSICS_SCALE,
+                ]
+            })
+            .collect()
+    }
+
+    /// The player's contacts and the velocities of the player and blocks, see [`EnvInfo`].
+    pub fn env_info(&self) -> EnvInfo {
+        let contacts: Vec<_> = self
+            .player_contacts(0)
+            .into_iter()
+            .map(|contact| ContactInfo {
+                point: [
+                    contact.point.x / BEVY_TO_PHYSICS_SCALE,
+                    contact.point.y / BEVY_TO_PHYSICS_SCALE,
+                ],
+                normal: [contact.normal.x, contact.normal.y],
+                rigid_body: contact.rigid_body,
+                floor: contact.floor,
+            })
+            .collect();
+        let block_velocities = self
+            .rigid_body_set
+            .iter()
+            .filter(|(handle, rigid_body)| {
+                rigid_body.is_dynamic()
+                    && !self.players.iter().any(|player| player.handle == *handle)
+            })
+            .map(|(handle, rigid_body)| {
+                let velocity = rigid_body.linvel() / BEVY_TO_PHYSICS_SCALE;
+                (handle, [velocity.x, velocity.y])
+            })
+            .collect();
+        EnvInfo {
+            grounded: contacts.iter().any(|contact| contact.floor),
+            contacts,
+            player_velocity: self.player_velocity(),
+            block_velocities,
+        }
+    }
+
+    fn player_physics_translation(&self, player: usize) -> Vec2 {
+        let player_translation = self.rigid_body_set[self.players[player].handle].translation();
+        Vec2::new(player_translation.x, player_translation.y)
+    }
+
+    /// Density of the water at the point (in physics units), or 0.0 if the point isn't in water.
+    /// Overlapping water zones don't add up, the densest one is used.
+    fn water_density(&self, point: Vec2) -> f32 {
+        self.water
+            .iter()
+            .filter(|(zone, _)| zone.distance(point) == 0.0)
+            .map(|(_, density)| *density)
+            .fold(0.0, f32::max)
+    }
+
+    // Applies buoyancy and drag, as impulses over the next time step,
+    // to the dynamic bodies whose center of mass is in water.
+    fn apply_water_forces(&mut self) {
+        if self.water.is_empty() {
+            return;
+        }
+
+        let dt = self.integration_parameters.dt;
+        let mut impulses = std::mem::take(&mut self.scratch.impulses);
+        for (handle, rigid_body) in self.rigid_body_set.iter() {
+            if !rigid_body.is_dynamic() {
+                continue;
+            }
+            let center = rigid_body.center_of_mass();
+            let density = self.water_density(Vec2::new(center.x, center.y));
+            if density == 0.0 {
+                continue;
+            }
+            let volume: f32 = rigid_body
+                .colliders()
+                .iter()
+                .map(|collider| self.collider_set[*collider].volume())
+                .sum();
+            let buoyancy = vector![0.0, density * volume * self.physics.gravity];
+            let drag =
+                -rigid_body.linvel() * rigid_body.mass() * (WATER_DRAG * density).min(1.0 / dt);
+            impulses.push((handle, (buoyancy + drag) * dt));
+        }
+
+        for (handle, impulse) in impulses.drain(..) {
+            self.rigid_body_set[handle].apply_impulse(impulse, true);
+        }
+        self.scratch.impulses = impulses;
+    }
+
+    // Slows down the dynamic bodies touching a surface with drag, as impulses over the next time step.
+    fn apply_surface_drag(&mut self) {
+        if self.surface_drag.is_empty() {
+            return;
+        }
+
+        let dt = self.integration_parameters.dt;
+        let mut impulses = std::mem::take(&mut self.scratch.impulses);
+        for (surface, drag) in &self.surface_drag {
+            for contact_pair in self.narrow_phase.contacts_with(*surface) {
+                if !contact_pair.has_any_active_contact {
+                    continue;
+                }
+                let other = if contact_pair.collider1 == *surface {
+                    contact_pair.collider2
+                } else {
+                    contact_pair.collider1
+                };
+                let Some(handle) = self.collider_set[other].parent() else {
+                    continue;
+                };
+                let rigid_body = &self.rigid_body_set[handle];
+                if !rigid_body.is_dynamic() {
+                    continue;
+                }
+                let drag = -rigid_body.linvel() * rigid_body.mass() * drag.min(1.0 / dt);
+                impulses.push((handle, drag * dt));
+            }
+        }
+
+        for (handle, impulse) in impulses.drain(..) {
+            self.rigid_body_set[handle].apply_impulse(impulse, true);
+        }
+        self.scratch.impulses = impulses;
+    }
+
+    // Moves the player to the pair of the teleporter it entered during the last physics step.
+    fn teleport_player(&mut self, player: usize) {
+        if self.teleporters.is_empty() {
+            return;
+        }
+
+        let player_handle = self.players[player].handle;
+        let player_collider = self.rigid_body_set[player_handle].colliders()[0];
+        let intersects = |collider| {
+            self.narrow_phase
+                .intersection_pair(player_collider, collider)
+                .unwrap_or(false)
+        };
+
+        let mut teleporter_arrival = self.players[player].teleporter_arrival;
+        if let Some(arrival) = teleporter_arrival {
+            if !intersects(arrival) {
+                teleporter_arrival = None;
+            }
+        }
+
+        let Some((source, destination)) = self
+            .teleporters
+            .iter()
+            .filter(|teleporter| Some(teleporter.collider) != teleporter_arrival)
+            .find(|teleporter| intersects(teleporter.collider))
+            .and_then(|source| {
+                let destination = self
+                    .teleporters
+                    .iter()
+                    .find(|teleporter| Some(teleporter.id) == source.pair)?;
+                Some((source.clone(), destination.collider))
+            })
+        else {
+            self.players[player].teleporter_arrival = teleporter_arrival;
+            return;
+        };
+
+        let translation = *self.collider_set[destination].translation();
+        let rigid_body = &mut self.rigid_body_set[player_handle];
+        rigid_body.set_translation(translation, true);
+        if !source.keep_velocity {
+            rigid_body.set_linvel(vector![0.0, 0.0], true);
+        }
+        self.players[player].teleporter_arrival = Some(destination);
+    }
+
+    // The climbing speed of the fastest ladder the player overlaps, if any.
+    fn player_ladder_speed(&self, player: usize) -> Option<f32> {
+        if self.ladders.is_empty() {
+            return None;
+        }
+        let player_collider = self.rigid_body_set[self.players[player].handle].colliders()[0];
+        self.ladders
+            .iter()
+            .filter(|(collider, _)| {
+                self.narrow_phase
+                    .intersection_pair(player_collider, **collider)
+                    .unwrap_or(false)
+            })
+            .map(|(_, speed)| *speed)
+            .reduce(f32::max)
+    }
+
+    // Applies the moves enabled by the environment's capabilities.
+    // `merged` is the number of physics steps simulated at once, see Environment::physics_step.
+    fn apply_optional_moves(
+        &mut self,
+        player: usize,
+        player_move: Move,
+        on_ground: bool,
+        merged: usize,
+    ) {
+        let state = &mut self.players[player];
+        if player_move.left != player_move.right {
+            state.facing = if player_move.right { 1.0 } else { -1.0 };
+        }
+        state.dash_cooldown = state.dash_cooldown.saturating_sub(merged);
+        let jump_pressed = player_move.up && !state.jump_held;
+        state.jump_held = player_move.up;
         if on_ground {
+            state.air_jumps_used = 0;
+        }
+        if let Some(stamina) = self.stamina {
+            if on_ground && player_move == Move::default() {
+                state.energy =
+                    (state.energy + stamina.regeneration * merged as f32).min(stamina.max_energy);
+            }
+        }
+
+        let rigid_body = &mut self.rigid_body_set[state.handle];
+        if jump_pressed
+            && !on_ground
+            && state.air_jumps_used < self.capabilities.air_jumps
+            && state.use_jump_energy(self.stamina)
+        {
+            // Jumps from a standstill, so falling doesn't weaken the jump.
+            let linvel = *rigid_body.linvel();
+            rigid_body.set_linvel(vector![linvel.x, 0.0], true);
+            rigid_body.apply_impulse(vector![0.0, self.physics.jump_impulse], true);
+            state.air_jumps_used += 1;
+        }
+        if self.capabilities.down && player_move.down && !on_ground {
+            rigid_body.apply_impulse(vector![0.0, -FAST_FALL_IMPULSE * merged as f32], true);
+        }
+        if self.capabilities.dash && player_move.dash && state.dash_cooldown == 0 {
+            rigid_body.apply_impulse(vector![state.facing * DASH_IMPULSE, 0.0], true);
+            state.dash_cooldown = DASH_COOLDOWN_STEPS;
+        }
+        self.update_grab(player, self.capabilities.grab && player_move.grab);
+    }
+
+    // Attaches the nearest dynamic block within reach to the player with a fixed joint while grab is pressed,
+    // and removes the joint once it is released.
+    fn update_grab(&mut self, player: usize, grab: bool) {
+        let player_handle = self.players[player].handle;
+        match (grab, self.players[player].held) {
+            (true, None) => {
+                let Some(block) = self.grabbable_block(player) else {
+                    return;
+                };
+                let player_translation = *self.rigid_body_set[player_handle].translation();
+                let block_position = self.rigid_body_set[block].position();
+                // The player can't rotate, so the block keeps its current position relative to the player.
+                let joint = FixedJointBuilder::new()
+                    .local_frame1(Isometry::new(
+                        block_position.translation.vector - player_translation,
+                        block_position.rotation.angle(),
+                    ))
+                    .contacts_enabled(false);
+                let joint_handle = self
+                    .impulse_joint_set
+                    .insert(player_handle, block, joint, true);
+                self.players[player].held = Some((block, joint_handle));
+            }
+            (false, Some((_, joint_handle))) => {
+                self.impulse_joint_set.remove(joint_handle, true);
+                self.players[player].held = None;
+            }
+            _ => {}
+        }
+    }
+
+    // The dynamic block closest to the player among the ones within GRAB_DISTANCE of it.
+    fn grabbable_block(&self, player: usize) -> Option<RigidBodyHandle> {
+        let player_handle = self.players[player].handle;
+        let player_translation = *self.rigid_body_set[player_handle].translation();
+        let reach = Capsule::new_y(
+            0.5 * self.player_shape.depth * BEVY_TO_PHYSICS_SCALE,
+            (self.player_shape.radius + GRAB_DISTANCE) * BEVY_TO_PHYSICS_SCALE,
+        );
+        let filter = QueryFilter::only_dynamic()
+            .exclude_sensors()
+            .exclude_rigid_body(player_handle);
+        let mut nearest: Option<(RigidBodyHandle, f32)> = None;
+        self.query_pipeline.intersections_with_shape(
+            &self.rigid_body_set,
+            &self.collider_set,
+            &Isometry::translation(player_translation.x, player_translation.y),
+            &reach,
+            filter,
+            |collider| {
+                let Some(rigid_body) = self.collider_set[collider].parent() else {
+                    return true;
+                };
+                if self.players.iter().any(|state| state.handle == rigid_body) {
+                    return true;
+                }
+                let distance =
+                    (self.rigid_body_set[rigid_body].translation() - player_translation).norm();
+                if nearest.map_or(true, |(_, nearest_distance)| distance < nearest_distance) {
+                    nearest = Some((rigid_body, distance));
+                }
+                true
+            },
+        );
+        nearest.map(|(rigid_body, _)| rigid_body)
+    }
+
+    /// Whether the first player overlaps each [`WorldObject::SensorZone`], in the order of the world's objects.
+    pub fn sensor_zone_overlaps(&self) -> Vec<bool> {
+        let player_collider = self.rigid_body_set[self.players[0].handle].colliders()[0];
+        self.sensor_zones
+            .iter()
+            .map(|zone| {
+                self.narrow_phase
+                    .intersection_pair(player_collider, *zone)
+                    .unwrap_or(false)
+            })
+            .collect()
+    }
+
+    /// Positions of the centers of the [`WorldObject::Enemy`] and [`WorldObject::Saw`] objects, in the order of the world's objects.
+    /// In the same units as [`World`] positions.
+    pub fn enemy_positions(&self) -> Vec<[f32; 2]> {
+        self.enemies
+            .iter()
+            .map(|enemy| {
+                let translation = self.rigid_body_set[*enemy].translation();
+                [
+                    translation.x / BEVY_TO_PHYSICS_SCALE,
+                    translation.y / BEVY_TO_PHYSICS_SCALE,
+                ]
+            })
+            .collect()
+    }
+
+    /// The block held by the first player, see [`Capabilities::grab`].
+    pub fn held_block(&self) -> Option<RigidBodyHandle> {
+        self.players[0].held.map(|(block, _)| block)
+    }
+
+    // Applies the attractors' accelerations, as impulses over the next time step,
+    // to the dynamic bodies within their radius.
+    fn apply_attractor_forces(&mut self) {
+        if self.attractors.is_empty() {
+            return;
+        }
+
+        let dt = self.integration_parameters.dt;
+        // Avoids huge accelerations close to the center.
+        let min_distance = self.player_shape.radius * BEVY_TO_PHYSICS_SCALE;
+        for (_, rigid_body) in self.rigid_body_set.iter_mut() {
+            if !rigid_body.is_dynamic() {
+                continue;
+            }
+            let center = *rigid_body.center_of_mass();
+            let mut acceleration = vector![0.0, 0.0];
+            for attractor in &self.attractors {
+                let offset = vector![attractor.x - center.x, attractor.y - center.y];
+                let distance = offset.norm();
+                if distance > attractor.radius || distance == 0.0 {
+                    continue;
+                }
+                acceleration +=
+                    offset / distance * attractor.strength / distance.max(min_distance).powi(2);
+            }
+            if acceleration != vector![0.0, 0.0] {
+                let impulse = acceleration * rigid_body.mass() * dt;
+                rigid_body.apply_impulse(impulse, true);
+            }
+        }
+    }
+
+    /// Applies an impulse to a rigid body, taking effect during the next step.
+    /// The impulse uses the same length units as [`World`] positions.
+    pub fn apply_external_impulse(&mut self, body: RigidBodyHandle, impulse: [f32; 2]) {
+        if let Some(rigid_body) = self.rigid_body_set.get_mut(body) {
+            rigid_body.apply_impulse(
+                vector![
+                    impulse[0] * BEVY_TO_PHYSICS_SCALE,
+                    impulse[1] * BEVY_TO_PHYSICS_SCALE
+                ],
+                true,
+            );
+        }
+    }
+
+    // Sets the poses of the animated blocks at the end of the next time step.
+    // Stops the anchors of the taut ropes from moving apart, pulling overstretched ropes back to their length.
+    fn apply_rope_constraints(&mut self) {
+        let dt = self.integration_parameters.dt;
+        for rope in &self.ropes {
+            let [body1, body2] = rope.rigid_bodies.map(|handle| &self.rigid_body_set[handle]);
+            let point1 = body1.position() * rope.anchors[0];
+            let point2 = body2.position() * rope.anchors[1];
+            let offset = point2 - point1;
+            let distance = offset.norm();
+            if distance <= rope.length || distance < 1e-6 {
+                continue;
+            }
+            let direction = offset / distance;
+            let separating_speed = (body2.velocity_at_point(&point2)
+                - body1.velocity_at_point(&point1))
+            .dot(&direction)
+                + ROPE_CORRECTION * (distance - rope.length) / dt;
+            let inverse_mass = |body: &RigidBody| {
+                if body.is_dynamic() {
+                    1.0 / body.mass()
+                } else {
+                    0.0
+                }
+            };
+            let total_inverse_mass = inverse_mass(body1) + inverse_mass(body2);
+            if separating_speed <= 0.0 || total_inverse_mass == 0.0 {
+                continue;
+            }
+            let impulse = direction * separating_speed / total_inverse_mass;
+            self.rigid_body_set[rope.rigid_bodies[0]].apply_impulse_at_point(impulse, point1, true);
+            self.rigid_body_set[rope.rigid_bodies[1]]
+                .apply_impulse_at_point(-impulse, point2, true);
+        }
+    }
+
+    fn animate_blocks(&mut self) {
+        let dt = self.integration_parameters.dt;
+        for index in 0..self.animated_blocks.len() {
+            let time = match self.animated_blocks[index].switch {
+                Some(switch) => {
+                    if self.switch_active(switch) {
+                        self.animated_blocks[index].time += dt;
+                    }
+                    self.animated_blocks[index].time
+                }
+                None => self.time + dt,
+            };
+            let block = &self.animated_blocks[index];
+            let (offset, rotation) = block.animation.sample(time);
+            let translation =
+                block.position + vector![offset[0], offset[1]] * BEVY_TO_PHYSICS_SCALE;
+            self.rigid_body_set[block.rigid_body]
+                .set_next_kinematic_position(Isometry::new(translation, block.rotation + rotation));
+        }
+    }
+
+    // Center of the lower semicircle of the player's capsule.
+    fn player_lower_center(&self, player: usize) -> Vector<Real> {
+        let player_translation = self.rigid_body_set[self.players[player].handle].translation();
+        vector![
+            player_translation.x,
+            player_translation.y - self.player_shape.depth * BEVY_TO_PHYSICS_SCALE / 2.0
+        ]
+    }
+
+    // Active contact points between the player and other colliders.
+    fn player_contacts(&self, player: usize) -> Vec<PlayerContact> {
+        let mut player_contacts = vec![];
+        self.for_each_player_contact(player, |contact| player_contacts.push(contact));
+        player_contacts
+    }
+
+    fn player_contact_count(&self, player: usize) -> usize {
+        let mut count = 0;
+        self.for_each_player_contact(player, |_| count += 1);
+        count
+    }
+
+    // Calls `visit` with each active contact point between the player and other colliders,
+    // without collecting them.
+    fn for_each_player_contact(&self, player: usize, mut visit: impl FnMut(PlayerContact)) {
+        let player_lower_center = self.player_lower_center(player);
+        let player_collider = self.rigid_body_set[self.players[player].handle].colliders()[0];
+        for contact_pair in self.narrow_phase.contacts_with(player_collider) {
+            let contact_collider = if contact_pair.collider1 != player_collider {
+                contact_pair.collider1
+            } else {
+                contact_pair.collider2
+            };
+            let rigid_body = self.collider_set[contact_collider].parent();
+            if contact_pair.has_any_active_contact {
+                for manifold in &contact_pair.manifolds {
+                    // The manifold's normal points from the first collider to the second one.
+                    let normal = if contact_pair.collider1 == player_collider {
+                        -manifold.data.normal
+                    } else {
+                        manifold.data.normal
+                    };
+                    for solver_contact in &manifold.data.solver_contacts {
+                        let player_floor_contact = (solver_contact.point - player_lower_center)
+                            / (self.player_shape.radius * BEVY_TO_PHYSICS_SCALE);
+                        visit(PlayerContact {
+                            point: solver_contact.point,
+                            normal,
+                            rigid_body,
+                            floor: player_floor_contact.y < -0.707,
+                        });
+                    }
+                }
+            }
+        }
+    }
+
+    /// Restores the environment to the state it was in before the first step,
+    /// without rebuilding it from the world.
+    /// Objects added after the first step are removed and their handles become invalid.
+    pub fn reset(&mut self) {
+        if let Some(initial_state) = self.initial_state.take() {
+            self.restore(&initial_state);
+            self.initial_state = Some(initial_state);
+        }
+        // The events of an unfinished step refer to objects which may have been removed.
+        self.pending_events.clear();
+        self.respawn();
+        if self.recording.is_some() {
+            self.record();
+        }
+    }
+
+    /// Captures the simulation state (rigid bodies, colliders, contacts, the objects' state and whether the environment is won),
+    /// so the simulation can later be branched from this point with [`Environment::restore`].
+    pub fn snapshot(&self) -> EnvSnapshot {
+        EnvSnapshot {
+            island_manager: self.island_manager.clone(),
+            broad_phase: self.broad_phase.clone(),
+            narrow_phase: self.narrow_phase.clone(),
+            impulse_joint_set: self.impulse_joint_set.clone(),
+            multibody_joint_set: self.multibody_joint_set.clone(),
+            ccd_solver: self.ccd_solver.clone(),
+            rigid_body_set: self.rigid_body_set.clone(),
+            collider_set: self.collider_set.clone(),
+            query_pipeline: self.query_pipeline.clone(),
+            goals: self.goals.clone(),
+            goal_properties: self.goal_properties.clone(),
+            reached_goals: self.reached_goals.clone(),
+            goal_reward: self.goal_reward,
+            water: self.water.clone(),
+            conveyors: self.conveyors.clone(),
+            hazards: self.hazards.clone(),
+            ladders: self.ladders.clone(),
+            attractors: self.attractors.clone(),
+            teleporters: self.teleporters.clone(),
+            checkpoints: self.checkpoints.clone(),
+            switches: self.switches.clone(),
+            doors: self.doors.clone(),
+            animated_blocks: self.animated_blocks.clone(),
+            coins: self.coins.clone(),
+            block_materials: self.block_materials.clone(),
+            surface_drag: self.surface_drag.clone(),
+            ropes: self.ropes.clone(),
+            enemies: self.enemies.clone(),
+            timed_blocks: self.timed_blocks.clone(),
+            sensor_zones: self.sensor_zones.clone(),
+            time: self.time,
+            players: self.players.clone(),
+            rng: self.rng.clone(),
+            target_goal: self.target_goal,
+            winner: self.winner,
+            steps: self.steps,
+            physics_steps: self.physics_steps,
+        }
+    }
+
+    /// Restores the simulation state captured by [`Environment::snapshot`].
+    /// The snapshot should come from this environment (or a clone of it), as rigid body handles are restored as-is.
+    pub fn restore(&mut self, snapshot: &EnvSnapshot) {
+        self.island_manager.clone_from(&snapshot.island_manager);
+        self.broad_phase.clone_from(&snapshot.broad_phase);
+        self.narrow_phase.clone_from(&snapshot.narrow_phase);
+        self.impulse_joint_set
+            .clone_from(&snapshot.impulse_joint_set);
+        self.multibody_joint_set
+            .clone_from(&snapshot.multibody_joint_set);
+        self.ccd_solver.clone_from(&snapshot.ccd_solver);
+        self.rigid_body_set.clone_from(&snapshot.rigid_body_set);
+        self.collider_set.clone_from(&snapshot.collider_set);
+        self.query_pipeline.clone_from(&snapshot.query_pipeline);
+        self.goals.clone_from(&snapshot.goals);
+        self.goal_properties.clone_from(&snapshot.goal_properties);
+        self.reached_goals.clone_from(&snapshot.reached_goals);
+        self.goal_reward = snapshot.goal_reward;
+        self.water.clone_from(&snapshot.water);
+        self.conveyors.clone_from(&snapshot.conveyors);
+        self.hazards.clone_from(&snapshot.hazards);
+        self.ladders.clone_from(&snapshot.ladders);
+        self.attractors.clone_from(&snapshot.attractors);
+        self.teleporters.clone_from(&snapshot.teleporters);
+        self.checkpoints.clone_from(&snapshot.checkpoints);
+        self.switches.clone_from(&snapshot.switches);
+        self.doors.clone_from(&snapshot.doors);
+        self.animated_blocks.clone_from(&snapshot.animated_blocks);
+        self.coins.clone_from(&snapshot.coins);
+        self.block_materials.clone_from(&snapshot.block_materials);
+        self.surface_drag.clone_from(&snapshot.surface_drag);
+        self.ropes.clone_from(&snapshot.ropes);
+        self.enemies.clone_from(&snapshot.enemies);
+        self.timed_blocks.clone_from(&snapshot.timed_blocks);
+        self.sensor_zones.clone_from(&snapshot.sensor_zones);
+        self.time = snapshot.time;
+        self.players.clone_from(&snapshot.players);
+        self.rng.clone_from(&snapshot.rng);
+        self.target_goal = snapshot.target_goal;
+        self.winner = snapshot.winner;
+        self.steps = snapshot.steps;
+        self.physics_steps = snapshot.physics_steps;
+    }
+
+    /// Like [`Environment::step`], but returns the transition: the new observation,
+    /// the reward computed by the environment's [`RewardFn`], whether the episode is done, and extra information.
+    pub fn step_full(&mut self, player_move: Move) -> StepResult {
+        let previous_distance = self.path_distance_to_goals().ok();
+        let previously_won = self.won();
+        let previous_goal_reward = self.goal_reward;
+        let previous_hazard_reward = self.hazard_reward();
+        self.step(player_move);
+        let distance = self.path_distance_to_goals().ok();
+
+        let transition = Transition {
+            player_move,
+            previous_distance_to_goals: previous_distance,
+            distance_to_goals: distance,
+            just_won: self.won() && !previously_won,
+            done: self.done(),
+            goal_reward: self.goal_reward - previous_goal_reward,
+            hazard_reward: self.hazard_reward() - previous_hazard_reward,
+            wall_contact: self.player_contacts(0).iter().any(|contact| !contact.floor),
+        };
+        let reward = self.reward_fn.reward(&transition, self);
+
+        StepResult {
+            observation: self.state(),
+            reward,
+            done: self.done(),
+            info: StepInfo {
+                steps: self.steps,
+                won: self.won(),
+                out_of_bounds: self.out_of_bounds(),
+                dead: self.dead(),
+                distance_to_goals: distance,
+                goal_reward: self.goal_reward,
+                reached_goals: self.reached_goals(),
+            },
+            env_info: self.env_info(),
+        }
+    }
+
+    /// Move the environment forward by a single time step, with the player playing the given move.
+    /// A time step is [`Environment::decision_interval`] physics steps.
+    /// If the distance to goals is (approximately) 0.0, the environment is set to be won.
+    /// Players other than the first one stand still, see [`Environment::step_multi`].
+    pub fn step(&mut self, player_move: Move) {
+        self.step_players(&[player_move]);
+    }
+
+    /// Like [`Environment::step`], with one move per player in the order they were added.
+    /// The environment is won by the first player reaching a goal, see [`Environment::winner`].
+    /// Returns [`Error::PlayerCount`] if there isn't exactly one move per player.
+    pub fn step_multi(&mut self, moves: &[Move]) -> Result<()> {
+        if moves.len() != self.players.len() {
+            return Err(Error::PlayerCount {
+                expected: self.players.len(),
+                actual: moves.len(),
+            });
+        }
+        self.step_players(moves);
+        Ok(())
+    }
+
+    // Steps the environment, the players without a move stand still.
+    fn step_players(&mut self, moves: &[Move]) {
+        if self.initial_state.is_none() {
+            self.initial_state = Some(Box::new(self.snapshot()));
+        }
+
+        let mut player_moves = std::mem::take(&mut self.scratch.moves);
+        player_moves.clear();
+        for player in 0..self.players.len() {
+            let player_move = moves.get(player).copied().unwrap_or_default();
+            let player_move = self.apply_action_noise(player, player_move);
+            self.players[player].last_move = player_move;
+            player_moves.push(player_move);
+        }
+
+        for index in 0..self.config.disturbances.len() {
+            let disturbance = self.config.disturbances[index];
+            if disturbance.step == self.steps {
+                self.apply_external_impulse(self.players[0].handle, disturbance.impulse);
+            }
+        }
+
+        let mut remaining = self.config.decision_interval.max(1);
+        loop {
+            let merged = match self.config.adaptive_step {
+                Some(adaptive_step) if self.can_merge_steps(&adaptive_step) => {
+                    adaptive_step.coarse_factor.clamp(1, remaining)
+                }
+                _ => 1,
+            };
+            self.physics_step(&player_moves, merged);
+            remaining -= merged;
+            if remaining == 0 || self.done() {
+                break;
+            }
+        }
+        self.scratch.moves = player_moves;
+
+        self.steps += 1;
+        if let WinCondition::Survive { steps } = self.win_condition {
+            if self.winner.is_none() && !self.out_of_bounds() && self.steps >= steps {
+                self.winner = Some(0);
+            }
+        }
+        if self.recording.is_some() {
+            let step = TrajectoryStep {
+                position: self.player_position(),
+                velocity: self.player_velocity(),
+                player_move: self.players[0].last_move,
+            };
+            let won = self.won();
+            if let Some(trajectory) = &mut self.recording {
+                trajectory.steps.push(step);
+                trajectory.won |= won;
+            }
+        }
+        self.dispatch_events();
+    }
+
+    // Calls the callbacks with the events raised during the step.
+    fn dispatch_events(&mut self) {
+        if self.callbacks.is_empty() {
+            self.pending_events.clear();
+            return;
+        }
+
+        let mut callbacks = std::mem::take(&mut self.callbacks);
+        let mut events = std::mem::take(&mut self.pending_events);
+        for event in events.drain(..) {
+            callbacks.dispatch(&event, self);
+        }
+        self.callbacks = callbacks;
+        self.pending_events = events;
+    }
+
+    // Raises a contact event for each player involved in the collision.
+    fn push_collision_event(&mut self, event: CollisionEvent) {
+        for (collider, other) in [
+            (event.collider1(), event.collider2()),
+            (event.collider2(), event.collider1()),
+        ] {
+            let player = self.players.iter().position(|player| {
+                self.rigid_body_set[player.handle].colliders().first() == Some(&collider)
+            });
+            if let Some(player) = player {
+                self.pending_events.push(EnvEvent::Contact(ContactEvent {
+                    player,
+                    collider: other,
+                    rigid_body: self
+                        .collider_set
+                        .get(other)
+                        .and_then(|collider| collider.parent()),
+                    started: event.started(),
+                    sensor: event.sensor(),
+                }));
+            }
+        }
+    }
+
+    // Raises an event for each player which left the bounds during the last physics step.
+    fn check_bounds(&mut self) {
+        let Some(bounds) = self.bounds else {
+            return;
+        };
+        for player in 0..self.players.len() {
+            let position =
+                (self.player_physics_translation(player) / BEVY_TO_PHYSICS_SCALE).to_array();
+            let in_bounds = bounds.contains(position);
+            if self.players[player].in_bounds && !in_bounds {
+                self.pending_events
+                    .push(EnvEvent::LeftBounds(BoundsEvent { player, position }));
+                if self.respawn_at_checkpoint(player) {
+                    continue;
+                }
+                self.players[player].left_bounds = true;
+            }
+            self.players[player].in_bounds = in_bounds;
+        }
+    }
+
+    // Kills the players touching a hazard during the last physics step.
+    fn check_hazards(&mut self) {
+        if self.hazards.is_empty() {
+            return;
+        }
+        for player in 0..self.players.len() {
+            if self.players[player].killed_by.is_some() {
+                continue;
+            }
+            let player_collider = self.rigid_body_set[self.players[player].handle].colliders()[0];
+            let Some(hazard) = self
+                .narrow_phase
+                .contacts_with(player_collider)
+                .filter(|contact_pair| contact_pair.has_any_active_contact)
+                .map(|contact_pair| {
+                    if contact_pair.collider1 == player_collider {
+                        contact_pair.collider2
+                    } else {
+                        contact_pair.collider1
+                    }
+                })
+                .find(|collider| self.hazards.contains_key(collider))
+            else {
+                continue;
+            };
+            if self.respawn_at_checkpoint(player) {
+                self.players[player].respawn_hazard_reward += self.hazards[&hazard];
+            } else {
+                self.players[player].killed_by = Some(hazard);
+            }
+        }
+    }
+
+    // Updates the switches pressed during the last physics step, and removes the doors of the active switches.
+    fn update_switches(&mut self) {
+        if self.switches.is_empty() {
+            return;
+        }
+        for index in 0..self.switches.len() {
+            let collider = self.switches[index].collider;
+            let pressed = if self.switches[index].plate {
+                self.plate_pressed(collider)
+            } else {
+                self.narrow_phase
+                    .intersections_with(collider)
+                    .filter(|(_, _, intersecting)| *intersecting)
+                    .any(|(collider1, collider2, _)| {
+                        let other = if collider1 == collider {
+                            collider2
+                        } else {
+                            collider1
+                        };
+                        self.is_dynamic_collider(other)
+                    })
+            };
+            let switch = &mut self.switches[index];
+            if switch.toggle {
+                if pressed && !switch.pressed {
+                    switch.active = !switch.active;
+                }
+            } else {
+                switch.active = pressed;
+            }
+            switch.pressed = pressed;
+        }
+        for index in 0..self.doors.len() {
+            let (rigid_body, switch) = self.doors[index];
+            let open = self.switch_active(switch);
+            self.rigid_body_set[rigid_body].set_enabled(!open);
+        }
+    }
+
+    // Whether a dynamic body rests on top of the pressure plate, touching it from above along its rotated y axis.
+    fn plate_pressed(&self, plate: ColliderHandle) -> bool {
+        let up = self.collider_set[plate].rotation() * vector![0.0, 1.0];
+        self.narrow_phase
+            .contacts_with(plate)
+            .filter(|contact_pair| contact_pair.has_any_active_contact)
+            .any(|contact_pair| {
+                // Contact normals point from the first collider to the second one.
+                let (other, sign) = if contact_pair.collider1 == plate {
+                    (contact_pair.collider2, 1.0)
+                } else {
+                    (contact_pair.collider1, -1.0)
+                };
+                self.is_dynamic_collider(other)
+                    && contact_pair
+                        .manifolds
+                        .iter()
+                        .any(|manifold| sign * manifold.data.normal.dot(&up) > 0.5)
+            })
+    }
+
+    fn is_dynamic_collider(&self, collider: ColliderHandle) -> bool {
+        self.collider_set[collider]
+            .parent()
+            .map_or(false, |parent| self.rigid_body_set[parent].is_dynamic())
+    }
+
+    // Makes the timed blocks solid or intangible for the current physics step.
+    fn update_timed_blocks(&mut self) {
+        for (rigid_body, period, phase) in &self.timed_blocks {
+            let solid = ((self.physics_steps + phase) / (*period).max(1)) % 2 == 0;
+            self.rigid_body_set[*rigid_body].set_enabled(solid);
+        }
+    }
+
+    // Makes the checkpoints touched by a player during the last physics step its respawn position.
+    fn reach_checkpoints(&mut self) {
+        if self.checkpoints.is_empty() {
+            return;
+        }
+        for player in 0..self.players.len() {
+            let player_collider = self.rigid_body_set[self.players[player].handle].colliders()[0];
+            if let Some(checkpoint) = self.checkpoints.iter().copied().find(|checkpoint| {
+                self.narrow_phase
+                    .intersection_pair(player_collider, *checkpoint)
+                    .unwrap_or(false)
+            }) {
+                self.players[player].checkpoint = Some(checkpoint);
+            }
+        }
+    }
+
+    // Moves the player to the center of its checkpoint and stops it.
+    // Returns false if the player hasn't reached a checkpoint.
+    fn respawn_at_checkpoint(&mut self, player: usize) -> bool {
+        let Some(checkpoint) = self.players[player].checkpoint else {
+            return false;
+        };
+        let translation = *self.collider_set[checkpoint].translation();
+        let rigid_body = &mut self.rigid_body_set[self.players[player].handle];
+        rigid_body.set_translation(translation, true);
+        rigid_body.set_linvel(vector![0.0, 0.0], true);
+        self.players[player].respawns += 1;
+        self.players[player].in_bounds = true;
+        self.query_pipeline
+            .update(&self.rigid_body_set, &self.collider_set);
+        true
+    }
+
+    // The move actually applied for the given move, see ActionNoise.
+    fn apply_action_noise(&mut self, player: usize, player_move: Move) -> Move {
+        let action_noise = match self.config.action_noise {
+            Some(action_noise) => action_noise,
+            None => return player_move,
+        };
+        if self.rng.gen::<f32>() < action_noise.repeat_probability {
+            self.players[player].last_move
+        } else if self.rng.gen::<f32>() < action_noise.flip_probability {
+            // Only the buttons which do something are flipped, so the noise doesn't turn into no-ops.
+            let buttons: Vec<u32> = (0..Move::COUNT.trailing_zeros())
+                .filter(|button| match button {
+                    3 => self.capabilities.down || self.has_ladders(),
+                    4 => self.capabilities.dash,
+                    5 => self.capabilities.grab,
+                    _ => true,
+                })
+                .collect();
+            let button = buttons[self.rng.gen_range(0..buttons.len())];
+            Move::from_index(player_move.to_index() ^ (1 << button))
+        } else {
+            player_move
+        }
+    }
+
+    // Whether every player is far enough from goals and touches few enough things
+    // for physics steps to be merged.
+    fn can_merge_steps(&self, adaptive_step: &AdaptiveStep) -> bool {
+        (0..self.players.len()).all(|player| {
+            self.player_distance_to_goals(player)
+                .map_or(true, |distance| distance > adaptive_step.fine_distance)
+                && self.player_contact_count(player) <= adaptive_step.fine_contacts
+        })
+    }
+
+    // Applies the moves and simulates `merged` physics steps as a single step, with a longer time step.
+    // Continuous impulses (moving, swimming, fast falling) are scaled accordingly, while jumps and dashes aren't.
+    fn physics_step(&mut self, player_moves: &[Move], merged: usize) {
+        let base_dt = self.integration_parameters.dt;
+        self.integration_parameters.dt = base_dt * merged as f32;
+
+        for (player, player_move) in player_moves.iter().enumerate() {
+            self.apply_player_move(player, *player_move, merged);
+        }
+        self.apply_water_forces();
+        self.apply_surface_drag();
+        self.apply_attractor_forces();
+        self.animate_blocks();
+        self.update_timed_blocks();
+        self.apply_rope_constraints();
+
+        let events: &dyn EventHandler = if self.callbacks.contact.is_empty() {
+            &()
+        } else {
+            &self.scratch.collisions
+        };
+        self.physics_pipeline.step(
+            &vector![0.0, -self.physics.gravity],
+            &self.integration_parameters,
+            &mut self.island_manager,
+            &mut self.broad_phase,
+            &mut self.narrow_phase,
+            &mut self.rigid_body_set,
+            &mut self.collider_set,
+            &mut self.impulse_joint_set,
+            &mut self.multibody_joint_set,
+            &mut self.ccd_solver,
+            None,
+            &ConveyorHooks {
+                conveyors: &self.conveyors,
+            },
+            events,
+        );
+        let mut collisions = std::mem::take(self.scratch.collisions.events.get_mut().unwrap());
+        for event in collisions.drain(..) {
+            self.push_collision_event(event);
+        }
+        *self.scratch.collisions.events.get_mut().unwrap() = collisions;
+        self.time += self.integration_parameters.dt;
+        self.physics_steps += merged;
+        self.integration_parameters.dt = base_dt;
+        for player in 0..self.players.len() {
+            self.teleport_player(player);
+        }
+        self.query_pipeline
+            .update(&self.rigid_body_set, &self.collider_set);
+        self.reach_checkpoints();
+        self.update_switches();
+        self.check_bounds();
+        self.check_hazards();
+        self.reach_goals();
+        let last_coin_collector = self.collect_coins();
+
+        if self.winner.is_none() {
+            self.winner = match self.win_condition {
+                WinCondition::ReachGoal => (0..self.players.len()).find(|player| {
+                    self.player_distance_to_goals(*player)
+                        .map_or(false, |distance| distance < 1e-7)
+                }),
+                WinCondition::CollectCoins => {
+                    last_coin_collector.filter(|_| self.coins_remaining() == 0)
+                }
+                WinCondition::PushBlock => self.block_in_goal().then_some(0),
+                // Checked once per step, see Environment::step_players.
+                WinCondition::Survive { .. } => None,
+            };
+            if let (WinCondition::ReachGoal, Some(player)) = (self.win_condition, self.winner) {
+                if let Some(goal) = self.player_nearest_goal(player) {
+                    self.pending_events
+                        .push(EnvEvent::GoalReached(GoalEvent { player, goal }));
+                }
+            }
+        }
+    }
+
+    // Collects the coins the players touched during the last physics step.
+    // Returns the last player who collected a coin.
+    fn collect_coins(&mut self) -> Option<usize> {
+        let mut collector = None;
+        for player in 0..self.players.len() {
+            let player_collider = self.rigid_body_set[self.players[player].handle].colliders()[0];
+            for index in 0..self.coins.len() {
+                let coin = &self.rigid_body_set[self.coins[index]];
+                if coin.is_enabled()
+                    && self
+                        .narrow_phase
+                        .intersection_pair(player_collider, coin.colliders()[0])
+                        .unwrap_or(false)
+                {
+                    self.rigid_body_set[self.coins[index]].set_enabled(false);
+                    collector = Some(player);
+                }
+            }
+        }
+        collector
+    }
+
+    // Whether the center of a non-fixed block is in a goal.
+    fn block_in_goal(&self) -> bool {
+        self.rigid_body_set.iter().any(|(handle, rigid_body)| {
+            let translation = rigid_body.translation();
+            rigid_body.is_dynamic()
+                && !self.players.iter().any(|player| player.handle == handle)
+                && self
+                    .required_goals()
+                    .any(|(_, goal)| goal.distance(Vec2::new(translation.x, translation.y)) == 0.0)
+        })
+    }
+
+    // Marks the goals touched by a player during the last physics step as reached, adding up their rewards.
+    fn reach_goals(&mut self) {
+        for goal in 0..self.goals.len() {
+            if self.reached_goals[goal] {
+                continue;
+            }
+            let reached = (0..self.players.len()).any(|player| {
+                self.goals[goal].distance(self.player_physics_translation(player))
+                    / BEVY_TO_PHYSICS_SCALE
+                    < 1e-7
+            });
+            if reached {
+                self.reached_goals[goal] = true;
+                self.goal_reward += self.goal_properties[goal].reward;
+            }
+        }
+    }
+
+    // Applies the impulses of a player's move, see Environment::physics_step.
+    fn apply_player_move(&mut self, player: usize, player_move: Move, merged: usize) {
+        let scale = merged as f32;
+        let player_handle = self.players[player].handle;
+
+        let player_lower_center = self.player_lower_center(player);
+        let mut player_floor_contacts = std::mem::take(&mut self.scratch.floor_contacts);
+        player_floor_contacts.clear();
+        self.for_each_player_contact(player, |contact| {
+            if contact.floor {
+                player_floor_contacts.push((contact.point, contact.rigid_body));
+            }
+        });
+
+        let on_ground = !player_floor_contacts.is_empty();
+        let PhysicsConfig {
+            player_impulse: move_impulse,
+            jump_impulse,
+            ..
+        } = self.physics;
+        let move_impulse = move_impulse * scale;
+
+        if let Some(speed) = self.player_ladder_speed(player) {
+            let direction = vector![
+                player_move.right as i32 as f32 - player_move.left as i32 as f32,
+                player_move.up as i32 as f32 - player_move.down as i32 as f32
+            ];
+            // Gravity is cancelled by adding the velocity it removes during the step.
+            let gravity = vector![0.0, self.physics.gravity * self.integration_parameters.dt];
+            self.rigid_body_set[player_handle].set_linvel(speed * direction + gravity, true);
+        } else if on_ground {
             let mut player_impulse = vector![0.0, 0.0];
 
             if player_move.left {
@@ -262,7 +3117,7 @@ impl Environment {
 
                 let mut normal = *point - player_lower_center;
                 normal /= (normal.x.powi(2) + normal.y.powi(2)).sqrt();
-                let impulse = vector![0.003 * normal.y, -0.003 * normal.x]; // Rotate normal
+                let impulse = vector![move_impulse * normal.y, -move_impulse * normal.x]; // Rotate normal
 
                 if let Some(rigid_body) = rigid_body {
                     self.rigid_body_set[*rigid_body].apply_impulse_at_point(-impulse, *point, true);
@@ -286,7 +3141,7 @@ impl Environment {
 
                 let mut normal = *point - player_lower_center;
                 normal /= (normal.x.powi(2) + normal.y.powi(2)).sqrt();
-                let impulse = vector![-0.003 * normal.y, 0.003 * normal.x]; // Rotate normal
+                let impulse = vector![-move_impulse * normal.y, move_impulse * normal.x]; // Rotate normal
 
                 if let Some(rigid_body) = rigid_body {
                     self.rigid_body_set[*rigid_body].apply_impulse_at_point(-impulse, *point, true);
@@ -294,11 +3149,11 @@ impl Environment {
                 player_impulse += impulse;
             }
 
-            if player_move.up {
+            if player_move.up && self.players[player].use_jump_energy(self.stamina) {
                 for (point, rigid_body) in &player_floor_contacts {
                     let mut normal = *point - player_lower_center;
                     normal /= (normal.x.powi(2) + normal.y.powi(2)).sqrt();
-                    let impulse = vector![-0.1 * normal.x, -0.1 * normal.y]
+                    let impulse = vector![-jump_impulse * normal.x, -jump_impulse * normal.y]
                         / player_floor_contacts.len() as f32;
 
                     if let Some(rigid_body) = rigid_body {
@@ -309,38 +3164,378 @@ impl Environment {
                 }
             }
 
-            self.rigid_body_set[self.player_handle].apply_impulse(player_impulse, true);
+            self.rigid_body_set[player_handle].apply_impulse(player_impulse, true);
+        } else if self.water_density(self.player_physics_translation(player)) > 0.0 {
+            let swim_direction = vector![
+                player_move.right as i32 as f32 - player_move.left as i32 as f32,
+                player_move.up as i32 as f32
+            ];
+            self.rigid_body_set[player_handle]
+                .apply_impulse(SWIM_IMPULSE * scale * swim_direction, true);
+        } else if self.capabilities.air_control > 0.0 {
+            let direction = player_move.right as i32 as f32 - player_move.left as i32 as f32;
+            self.rigid_body_set[player_handle].apply_impulse(
+                vector![
+                    self.capabilities.air_control * move_impulse * direction,
+                    0.0
+                ],
+                true,
+            );
         }
+        self.scratch.floor_contacts = player_floor_contacts;
 
-        self.physics_pipeline.step(
-            &vector![0.0, -2.0],
-            &self.integration_parameters,
-            &mut self.island_manager,
-            &mut self.broad_phase,
-            &mut self.narrow_phase,
-            &mut self.rigid_body_set,
-            &mut self.collider_set,
-            &mut self.impulse_joint_set,
-            &mut self.multibody_joint_set,
-            &mut self.ccd_solver,
-            None,
-            &(),
-            &(),
-        );
-        self.query_pipeline
-            .update(&self.rigid_body_set, &self.collider_set);
+        self.apply_optional_moves(player, player_move, on_ground, merged);
+    }
+}
 
-        if !self.won {
-            if let Some(distance) = self.distance_to_goals() {
-                if distance < 1e-7 {
-                    self.won = true;
-                }
-            }
+/// Forks the simulation, for example to evaluate several moves from the same state.
+/// The callbacks can't be cloned, so the clone starts without any, see [`Environment::on_contact`].
+impl Clone for Environment {
+    fn clone(&self) -> Self {
+        Environment {
+            integration_parameters: self.integration_parameters,
+            // The pipeline only holds buffers reused between steps.
+            physics_pipeline: PhysicsPipeline::new(),
+            island_manager: self.island_manager.clone(),
+            broad_phase: self.broad_phase.clone(),
+            narrow_phase: self.narrow_phase.clone(),
+            impulse_joint_set: self.impulse_joint_set.clone(),
+            multibody_joint_set: self.multibody_joint_set.clone(),
+            ccd_solver: self.ccd_solver.clone(),
+            rigid_body_set: self.rigid_body_set.clone(),
+            collider_set: self.collider_set.clone(),
+            query_pipeline: self.query_pipeline.clone(),
+            players: self.players.clone(),
+            goals: self.goals.clone(),
+            goal_properties: self.goal_properties.clone(),
+            reached_goals: self.reached_goals.clone(),
+            goal_reward: self.goal_reward,
+            water: self.water.clone(),
+            conveyors: self.conveyors.clone(),
+            hazards: self.hazards.clone(),
+            ladders: self.ladders.clone(),
+            attractors: self.attractors.clone(),
+            teleporters: self.teleporters.clone(),
+            checkpoints: self.checkpoints.clone(),
+            switches: self.switches.clone(),
+            doors: self.doors.clone(),
+            animated_blocks: self.animated_blocks.clone(),
+            coins: self.coins.clone(),
+            block_materials: self.block_materials.clone(),
+            surface_drag: self.surface_drag.clone(),
+            ropes: self.ropes.clone(),
+            enemies: self.enemies.clone(),
+            timed_blocks: self.timed_blocks.clone(),
+            sensor_zones: self.sensor_zones.clone(),
+            win_condition: self.win_condition,
+            time: self.time,
+            capabilities: self.capabilities,
+            stamina: self.stamina,
+            physics: self.physics,
+            player_shape: self.player_shape,
+            rng: self.rng.clone(),
+            target_goal: self.target_goal,
+            bounds: self.bounds,
+            spawn_region: self.spawn_region,
+            spawn_rng: self.spawn_rng.clone(),
+            callbacks: EventCallbacks::default(),
+            pending_events: vec![],
+            recording: self.recording.clone(),
+            distance_field: self.distance_field.clone(),
+            scratch: StepScratch::default(),
+            config: self.config.clone(),
+            reward_fn: self.reward_fn.clone(),
+            winner: self.winner,
+            steps: self.steps,
+            physics_steps: self.physics_steps,
+            initial_state: self.initial_state.clone(),
+        }
+    }
+}
+
+/// A transition returned by [`Environment::step_full`].
+#[derive(Debug, Clone)]
+pub struct StepResult {
+    /// The observation after the step, see [`Environment::state`].
+    pub observation: Vec<f32>,
+    pub reward: f32,
+    pub done: bool,
+    pub info: StepInfo,
+    pub env_info: EnvInfo,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct StepInfo {
+    /// Number of steps taken, including this one.
+    pub steps: usize,
+    pub won: bool,
+    /// Whether a player left the environment's bounds, see [`Environment::out_of_bounds`].
+    pub out_of_bounds: bool,
+    /// Whether a player was killed by a hazard, see [`Environment::dead`].
+    pub dead: bool,
+    /// None if the environment doesn't have any goals.
+    /// See [`Environment::path_distance_to_goals`].
+    pub distance_to_goals: Option<f32>,
+    /// See [`Environment::goal_reward`].
+    pub goal_reward: f32,
+    /// See [`Environment::reached_goals`].
+    pub reached_goals: Vec<usize>,
+}
+
+/// The physical state of the environment after a step, returned by [`Environment::env_info`].
+/// Positions are in the same units as [`World`] positions, and velocities in world units per second.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct EnvInfo {
+    /// The active contact points of the player.
+    pub contacts: Vec<ContactInfo>,
+    /// Whether the player is standing on something, see [`Environment::on_ground`].
+    pub grounded: bool,
+    pub player_velocity: [f32; 2],
+    /// The velocities of the non-fixed blocks, along with their rigid body handles.
+    pub block_velocities: Vec<(RigidBodyHandle, [f32; 2])>,
+}
+
+/// A point where the player touches another collider, see [`EnvInfo`].
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ContactInfo {
+    pub point: [f32; 2],
+    /// Unit normal of the contact, pointing towards the player.
+    pub normal: [f32; 2],
+    /// The rigid body the player touches, None for fixed blocks.
+    pub rigid_body: Option<RigidBodyHandle>,
+    /// Whether the player can stand on the contact.
+    pub floor: bool,
+}
+
+/// A copy of the simulation state of an environment, created by [`Environment::snapshot`].
+#[derive(Clone)]
+pub struct EnvSnapshot {
+    island_manager: IslandManager,
+    broad_phase: BroadPhase,
+    narrow_phase: NarrowPhase,
+    impulse_joint_set: ImpulseJointSet,
+    multibody_joint_set: MultibodyJointSet,
+    ccd_solver: CCDSolver,
+    rigid_body_set: RigidBodySet,
+    collider_set: ColliderSet,
+    query_pipeline: QueryPipeline,
+    goals: Vec<ZoneDimensions>,
+    goal_properties: Vec<GoalProperties>,
+    reached_goals: Vec<bool>,
+    goal_reward: f32,
+    water: Vec<(ZoneDimensions, f32)>,
+    conveyors: HashMap<ColliderHandle, f32>,
+    // The reward of each hazard, including the enemies.
+    hazards: HashMap<ColliderHandle, f32>,
+    // Ladder sensor colliders along with their climbing speeds, in physics units.
+    ladders: HashMap<ColliderHandle, f32>,
+    attractors: Vec<Attractor>,
+    teleporters: Vec<Teleporter>,
+    // Sensor colliders.
+    checkpoints: Vec<ColliderHandle>,
+    switches: Vec<Switch>,
+    // Fixed rigid bodies along with the id of their switch, disabled while the switch is active.
+    doors: Vec<(RigidBodyHandle, u32)>,
+    animated_blocks: Vec<AnimatedBlock>,
+    coins: Vec<RigidBodyHandle>,
+    block_materials: HashMap<ColliderHandle, (Option<f32>, Option<f32>)>,
+    surface_drag: HashMap<ColliderHandle, f32>,
+    ropes: Vec<Rope>,
+    enemies: Vec<RigidBodyHandle>,
+    // Fixed rigid bodies along with their period and phase, disabled while intangible.
+    timed_blocks: Vec<(RigidBodyHandle, usize, usize)>,
+    sensor_zones: Vec<ColliderHandle>,
+    time: f32,
+    players: Vec<PlayerState>,
+    rng: StdRng,
+    target_goal: Option<usize>,
+    winner: Option<usize>,
+    steps: usize,
+    physics_steps: usize,
+}
+
+fn fixed_block_collider(object_and_transform: &ObjectAndTransform) -> ColliderBuilder {
+    ColliderBuilder::cuboid(
+        0.5 * object_and_transform.scale[0].abs() * BEVY_TO_PHYSICS_SCALE,
+        0.5 * object_and_transform.scale[1].abs() * BEVY_TO_PHYSICS_SCALE,
+    )
+    .translation(vector![
+        object_and_transform.position[0] * BEVY_TO_PHYSICS_SCALE,
+        object_and_transform.position[1] * BEVY_TO_PHYSICS_SCALE
+    ])
+    .rotation(object_and_transform.rotation)
+}
+
+// Sets the tangent velocity of contacts with conveyors, so the solver moves touching bodies along the conveyor.
+struct ConveyorHooks<'a> {
+    conveyors: &'a HashMap<ColliderHandle, f32>,
+}
+
+impl PhysicsHooks for ConveyorHooks<'_> {
+    fn modify_solver_contacts(&self, context: &mut ContactModificationContext) {
+        // The tangent velocity is the velocity of the second collider relative to the first.
+        let (conveyor, sign) = if self.conveyors.contains_key(&context.collider1) {
+            (context.collider1, 1.0)
+        } else if self.conveyors.contains_key(&context.collider2) {
+            (context.collider2, -1.0)
+        } else {
+            return;
+        };
+        let velocity = context.colliders[conveyor].rotation()
+            * vector![1.0, 0.0]
+            * self.conveyors[&conveyor]
+            * sign;
+        for solver_contact in context.solver_contacts.iter_mut() {
+            solver_contact.tangent_velocity = velocity;
+        }
+    }
+}
+
+#[derive(Clone)]
+struct PlayerState {
+    handle: RigidBodyHandle,
+    // The horizontal direction the player last moved in (1.0 for right, -1.0 for left), used for dashing.
+    facing: f32,
+    // Steps until the player can dash again.
+    dash_cooldown: usize,
+    // The teleporter the player was last moved to, until the player leaves it.
+    teleporter_arrival: Option<ColliderHandle>,
+    // The move applied by the last step, after action noise.
+    last_move: Move,
+    // Whether the player was in the environment's bounds after the last physics step.
+    in_bounds: bool,
+    // Whether the player left the environment's bounds since the start of the episode.
+    left_bounds: bool,
+    // Whether up was pressed during the last step, air jumps need it to be pressed again.
+    jump_held: bool,
+    // Jumps made in the air since the player last touched the ground.
+    air_jumps_used: usize,
+    // Only used if the environment has stamina.
+    energy: f32,
+    // The hazard which killed the player since the start of the episode.
+    killed_by: Option<ColliderHandle>,
+    // The last checkpoint the player touched.
+    checkpoint: Option<ColliderHandle>,
+    // Times the player was moved back to its checkpoint, and the rewards of the hazards which caused it.
+    respawns: usize,
+    respawn_hazard_reward: f32,
+    // The grabbed block and the joint attaching it to the player.
+    held: Option<(RigidBodyHandle, ImpulseJointHandle)>,
+}
+
+impl PlayerState {
+    fn new(handle: RigidBodyHandle) -> PlayerState {
+        PlayerState {
+            handle,
+            facing: 1.0,
+            dash_cooldown: 0,
+            teleporter_arrival: None,
+            last_move: Move::default(),
+            in_bounds: true,
+            left_bounds: false,
+            jump_held: false,
+            air_jumps_used: 0,
+            energy: 0.0,
+            held: None,
+            killed_by: None,
+            checkpoint: None,
+            respawns: 0,
+            respawn_hazard_reward: 0.0,
+        }
+    }
+
+    // Uses the energy of a jump, returns whether the player had enough energy to jump.
+    fn use_jump_energy(&mut self, stamina: Option<Stamina>) -> bool {
+        let Some(stamina) = stamina else {
+            return true;
+        };
+        if self.energy < stamina.jump_cost {
+            return false;
         }
+        self.energy -= stamina.jump_cost;
+        true
     }
 }
 
-pub struct GoalDimensions {
+// Buffers reused between steps, so stepping doesn't allocate once they have grown large enough.
+#[derive(Default)]
+struct StepScratch {
+    moves: Vec<Move>,
+    floor_contacts: Vec<(Point<Real>, Option<RigidBodyHandle>)>,
+    impulses: Vec<(RigidBodyHandle, Vector<Real>)>,
+    collisions: CollisionCollector,
+}
+
+struct PlayerContact {
+    point: Point<Real>,
+    // Unit normal pointing towards the player.
+    normal: Vector<Real>,
+    rigid_body: Option<RigidBodyHandle>,
+    // Whether the contact is below the player, so the player can move and jump using it.
+    floor: bool,
+}
+
+#[derive(Clone, Copy)]
+struct GoalProperties {
+    reward: f32,
+    optional: bool,
+}
+
+#[derive(Clone)]
+struct Teleporter {
+    // A sensor collider.
+    collider: ColliderHandle,
+    id: u32,
+    pair: Option<u32>,
+    keep_velocity: bool,
+}
+
+#[derive(Clone)]
+struct AnimatedBlock {
+    rigid_body: RigidBodyHandle,
+    // The block's pose in the world, in physics units.
+    position: Vector<Real>,
+    rotation: f32,
+    animation: Animation,
+    // Blocks with a switch only move while it's active, their animation uses `time` instead of the environment's time.
+    switch: Option<u32>,
+    time: f32,
+}
+
+// In physics units.
+#[derive(Clone)]
+struct Rope {
+    rigid_bodies: [RigidBodyHandle; 2],
+    // In the frames of the rigid bodies.
+    anchors: [Point<Real>; 2],
+    length: f32,
+}
+
+#[derive(Clone)]
+struct Switch {
+    // A sensor collider, or a solid one for pressure plates.
+    collider: ColliderHandle,
+    id: u32,
+    toggle: bool,
+    // Pressure plates are pressed by the bodies resting on top of them instead of the bodies overlapping them.
+    plate: bool,
+    // Whether the switch was pressed after the last physics step.
+    pressed: bool,
+    active: bool,
+}
+
+// In physics units.
+#[derive(Clone)]
+struct Attractor {
+    x: f32,
+    y: f32,
+    strength: f32,
+    radius: f32,
+}
+
+// A rotated rectangle, used for goals and water.
+#[derive(Clone)]
+pub struct ZoneDimensions {
     x: f32,
     y: f32,
     width: f32,
@@ -348,9 +3543,338 @@ pub struct GoalDimensions {
     rotation: f32,
 }
 
-#[derive(Default, Clone, Copy, Debug)]
+impl ZoneDimensions {
+    fn from_object_and_transform(object_and_transform: &ObjectAndTransform) -> ZoneDimensions {
+        ZoneDimensions {
+            x: object_and_transform.position[0] * BEVY_TO_PHYSICS_SCALE,
+            y: object_and_transform.position[1] * BEVY_TO_PHYSICS_SCALE,
+            width: object_and_transform.scale[0].abs() * BEVY_TO_PHYSICS_SCALE,
+            height: object_and_transform.scale[1].abs() * BEVY_TO_PHYSICS_SCALE,
+            rotation: object_and_transform.rotation,
+        }
+    }
+
+    /// Distance from a point to the rectangle, in physics units.
+    fn distance(&self, point: Vec2) -> f32 {
+        let goal_translation = Vec2::new(self.x, self.y);
+        let x_axis = (Quat::from_rotation_z(self.rotation) * Vec3::X).truncate();
+        let y_axis = (Quat::from_rotation_z(self.rotation) * Vec3::Y).truncate();
+
+        let distance_x = ((point - goal_translation).dot(x_axis).abs() - self.width / 2.0).max(0.0);
+        let distance_y =
+            ((point - goal_translation).dot(y_axis).abs() - self.height / 2.0).max(0.0);
+        (distance_x.powi(2) + distance_y.powi(2)).sqrt()
+    }
+}
+
+/// Configuration of an [`Environment`] which isn't stored in the [`World`].
+///
+/// Training and visualization should use the same configuration,
+/// otherwise visualized agents can behave differently from how they were scored.
+#[derive(Clone, Debug, PartialEq)]
+pub struct EnvConfig {
+    pub observation: ObservationConfig,
+    /// Impulses applied to the player at given steps, see [`Disturbance`].
+    pub disturbances: Vec<Disturbance>,
+    /// Number of physics steps simulated by every [`Environment::step`], with the same move.
+    /// See [`Environment::set_decision_interval`].
+    pub decision_interval: usize,
+    /// Merges physics steps away from goals to simulate faster, see [`AdaptiveStep`].
+    /// None simulates every physics step, which should be used to verify results.
+    pub adaptive_step: Option<AdaptiveStep>,
+    /// Randomly changes the moves passed to [`Environment::step`], see [`ActionNoise`].
+    pub action_noise: Option<ActionNoise>,
+    /// Cell size of the [`DistanceField`] computed by [`Environment::from_world_with_config`], in world units.
+    /// When set, rewards and scores use [`Environment::path_distance_to_goals`] instead of the straight line distance.
+    pub path_distance: Option<f32>,
+    /// Seed of the random start positions in the world's spawn region, see [`Environment::set_spawn_region`].
+    pub spawn_seed: u64,
+}
+
+/// Randomly replaces the moves passed to [`Environment::step`], like sticky actions in Atari environments,
+/// so policies don't overfit to deterministic physics.
+/// The applied move is available with [`Environment::last_move`].
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct ActionNoise {
+    /// Probability that a step repeats the previously applied move instead of the given one.
+    pub repeat_probability: f32,
+    /// Probability that a step which doesn't repeat the previous move presses or releases a random button.
+    /// Only left, right, up and the buttons enabled by the [`Capabilities`] (or down with ladders) are flipped.
+    pub flip_probability: f32,
+    /// Seed of the random number generator. Resetting the environment also resets the generator.
+    pub seed: u64,
+}
+
+impl Default for ActionNoise {
+    fn default() -> Self {
+        ActionNoise {
+            repeat_probability: 0.25,
+            flip_probability: 0.0,
+            seed: 0,
+        }
+    }
+}
+
+/// Merges up to `coarse_factor` physics steps of an [`Environment::step`] into a single longer physics step
+/// while the player is far from goals and touches few things, trading accuracy for speed.
+/// Only the physics steps of a single step are merged, so the decision interval should be a multiple of `coarse_factor`.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct AdaptiveStep {
+    pub coarse_factor: usize,
+    /// Physics steps aren't merged within this distance to goals, in world units.
+    pub fine_distance: f32,
+    /// Physics steps aren't merged while the player has more contact points than this.
+    pub fine_contacts: usize,
+}
+
+impl Default for AdaptiveStep {
+    fn default() -> Self {
+        AdaptiveStep {
+            coarse_factor: 2,
+            fine_distance: 200.0,
+            fine_contacts: 2,
+        }
+    }
+}
+
+impl Default for EnvConfig {
+    fn default() -> Self {
+        EnvConfig {
+            observation: ObservationConfig::default(),
+            disturbances: vec![],
+            decision_interval: 1,
+            adaptive_step: None,
+            action_noise: None,
+            path_distance: None,
+            spawn_seed: 0,
+        }
+    }
+}
+
+/// An impulse applied to the player at the start of a step,
+/// used to train and evaluate robustness to pushes.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, Default, PartialEq)]
+pub struct Disturbance {
+    /// Applied when [`Environment::steps`] equals this, before the player's move.
+    pub step: usize,
+    /// See [`Environment::apply_external_impulse`].
+    pub impulse: [f32; 2],
+}
+
+/// Describes what an agent observes of the environment.
+///
+/// [`ObservationConfig`] covers the common cases, custom observations can be
+/// used with [`Environment::observe`]. Closures taking the environment and the
+/// state vector also implement this trait.
+pub trait Observation {
+    /// Appends the observed values to `state`.
+    fn observe(&self, environment: &Environment, state: &mut Vec<f32>);
+}
+
+impl<F: Fn(&Environment, &mut Vec<f32>)> Observation for F {
+    fn observe(&self, environment: &Environment, state: &mut Vec<f32>) {
+        self(environment, state)
+    }
+}
+
+/// Configures the values returned by [`Environment::state`].
+/// The values are added in the order of the fields.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq)]
+pub struct ObservationConfig {
+    /// The player position (2 values).
+    pub player_position: bool,
+    /// The player velocity (2 values).
+    pub player_velocity: bool,
+    /// The vector from the player to the target goal's center (2 values).
+    /// The nearest goal is used if no target goal is set.
+    pub goal_vector: bool,
+    /// A one-hot encoding of the target goal (one value per goal).
+    /// Only included when the environment has multiple goals.
+    pub goal_id: bool,
+    /// Number of evenly spaced directions around the player in which the
+    /// distance to the nearest obstacle is measured (one value per direction).
+    /// See [`Environment::raycast_observation`].
+    pub obstacle_rays: usize,
+    /// Maximum measured obstacle distance.
+    pub obstacle_max_distance: f32,
+    /// The player's energy as a fraction of the maximum energy (1 value), 1.0 without [`Stamina`].
+    #[serde(default)]
+    pub energy: bool,
+    /// Whether the player holds a block, and the vector from the player to the held block (3 values).
+    /// See [`Environment::held_block`].
+    #[serde(default)]
+    pub held_block: bool,
+    /// The vector from the player to each enemy and saw (2 values per enemy or saw).
+    /// See [`Environment::enemy_positions`].
+    #[serde(default)]
+    pub enemies: bool,
+    /// Whether the player overlaps each sensor zone, 1.0 if it does and 0.0 otherwise (1 value per zone).
+    /// See [`Environment::sensor_zone_overlaps`].
+    #[serde(default)]
+    pub sensor_zones: bool,
+}
+
+impl Default for ObservationConfig {
+    fn default() -> Self {
+        ObservationConfig {
+            player_position: true,
+            player_velocity: false,
+            goal_vector: true,
+            goal_id: false,
+            obstacle_rays: 0,
+            obstacle_max_distance: 500.0,
+            energy: false,
+            held_block: false,
+            enemies: false,
+            sensor_zones: false,
+        }
+    }
+}
+
+impl Observation for ObservationConfig {
+    fn observe(&self, environment: &Environment, state: &mut Vec<f32>) {
+        let player_translation = environment.player_physics_translation(0) / BEVY_TO_PHYSICS_SCALE;
+
+        if self.player_position {
+            state.extend([player_translation.x, player_translation.y]);
+        }
+
+        if self.player_velocity {
+            state.extend(environment.player_velocity());
+        }
+
+        let goal = environment.observed_goal();
+
+        if self.goal_vector {
+            let goal_vector = goal.map_or(Vec2::ZERO, |goal| {
+                let goal = &environment.goals[goal];
+                Vec2::new(goal.x, goal.y) / BEVY_TO_PHYSICS_SCALE - player_translation
+            });
+            state.extend([goal_vector.x, goal_vector.y]);
+        }
+
+        if self.goal_id && environment.goals.len() > 1 {
+            state.extend((0..environment.goals.len()).map(|index| {
+                if Some(index) == goal {
+                    1.0
+                } else {
+                    0.0
+                }
+            }));
+        }
+
+        if self.obstacle_rays > 0 {
+            state.extend(
+                environment.raycast_observation(self.obstacle_rays, self.obstacle_max_distance),
+            );
+        }
+
+        if self.energy {
+            state.push(environment.stamina.map_or(1.0, |stamina| {
+                environment.players[0].energy / stamina.max_energy
+            }));
+        }
+
+        if self.held_block {
+            match environment.held_block() {
+                Some(block) => {
+                    let translation = environment.rigid_body_set[block].translation();
+                    let offset = Vec2::new(translation.x, translation.y) / BEVY_TO_PHYSICS_SCALE
+                        - player_translation;
+                    state.extend([1.0, offset.x, offset.y]);
+                }
+                None => state.extend([0.0, 0.0, 0.0]),
+            }
+        }
+
+        if self.enemies {
+            for [x, y] in environment.enemy_positions() {
+                state.extend([x - player_translation.x, y - player_translation.y]);
+            }
+        }
+
+        if self.sensor_zones {
+            state.extend(
+                environment
+                    .sensor_zone_overlaps()
+                    .into_iter()
+                    .map(|overlaps| if overlaps { 1.0 } else { 0.0 }),
+            );
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Default, Clone, Copy, Debug, PartialEq, Eq)]
 pub struct Move {
     pub left: bool,
     pub right: bool,
     pub up: bool,
+    /// Only used if enabled in the world's [`Capabilities`].
+    #[serde(default)]
+    pub down: bool,
+    /// Only used if enabled in the world's [`Capabilities`].
+    #[serde(default)]
+    pub dash: bool,
+    /// Only used if enabled in the world's [`Capabilities`].
+    #[serde(default)]
+    pub grab: bool,
+}
+
+impl Move {
+    /// The number of distinct moves.
+    pub const COUNT: usize = 64;
+
+    /// Every move, ordered by [`Move::to_index`].
+    /// The first 8 moves only use left, right and up.
+    pub const ALL: [Move; Move::COUNT] = {
+        let mut moves = [Move {
+            left: false,
+            right: false,
+            up: false,
+            down: false,
+            dash: false,
+            grab: false,
+        }; Move::COUNT];
+        let mut index = 0;
+        while index < Move::COUNT {
+            moves[index] = Move::from_index(index);
+            index += 1;
+        }
+        moves
+    };
+
+    /// The moves which only use left, right and up.
+    /// These are the moves available in a world without any [`Capabilities`].
+    pub const BASIC: [Move; 8] = {
+        let mut moves = [Move::ALL[0]; 8];
+        let mut index = 0;
+        while index < 8 {
+            moves[index] = Move::ALL[index];
+            index += 1;
+        }
+        moves
+    };
+
+    /// The move with the given index, where bit 0 is left, bit 1 is right, bit 2 is up,
+    /// bit 3 is down, bit 4 is dash and bit 5 is grab. Higher bits are ignored.
+    pub const fn from_index(index: usize) -> Move {
+        Move {
+            left: index & 1 != 0,
+            right: index & 2 != 0,
+            up: index & 4 != 0,
+            down: index & 8 != 0,
+            dash: index & 16 != 0,
+            grab: index & 32 != 0,
+        }
+    }
+
+    /// Inverse of [`Move::from_index`]. Always less than [`Move::COUNT`].
+    pub const fn to_index(self) -> usize {
+        self.left as usize
+            | (self.right as usize) << 1
+            | (self.up as usize) << 2
+            | (self.down as usize) << 3
+            | (self.dash as usize) << 4
+            | (self.grab as usize) << 5
+    }
 }
@@ -0,0 +1,174 @@
+use std::fmt;
+
+use crate::common::{ObjectAndTransform, World};
+
+// Positions, sizes and rotations closer than this are the same,
+// as the editor's transforms don't exactly round trip the rotations.
+const TRANSFORM_EPSILON: f32 = 1e-3;
+
+/// A difference between two worlds, found by [`World::diff`].
+#[derive(Clone, Debug)]
+pub enum WorldChange {
+    /// An object which is only in the new world.
+    Added(ObjectAndTransform),
+    /// An object which is only in the old world.
+    Removed(ObjectAndTransform),
+    /// An object with the same properties and color in both worlds, but a different position, size or rotation.
+    Moved {
+        from: ObjectAndTransform,
+        to: ObjectAndTransform,
+    },
+    /// An object with the same transform in both worlds, but different properties or color.
+    Changed {
+        from: ObjectAndTransform,
+        to: ObjectAndTransform,
+    },
+    /// The first player starts at a different position.
+    PlayerMoved { from: [f32; 2], to: [f32; 2] },
+}
+
+impl fmt::Display for WorldChange {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let position = |object: &ObjectAndTransform| {
+            format!("({:.1}, {:.1})", object.position[0], object.position[1])
+        };
+        match self {
+            WorldChange::Added(object) => {
+                write!(f, "Added {} at {}.", object.object.name(), position(object))
+            }
+            WorldChange::Removed(object) => {
+                write!(
+                    f,
+                    "Removed {} at {}.",
+                    object.object.name(),
+                    position(object)
+                )
+            }
+            WorldChange::Moved { from, to } if same_position(from, to) => write!(
+                f,
+                "Resized or rotated {} at {}.",
+                to.object.name(),
+                position(to)
+            ),
+            WorldChange::Moved { from, to } => write!(
+                f,
+                "Moved {} from {} to {}.",
+                to.object.name(),
+                position(from),
+                position(to)
+            ),
+            WorldChange::Changed { from, to } if from.object.name() == to.object.name() => {
+                write!(f, "Changed {} at {}.", to.object.name(), position(to))
+            }
+            WorldChange::Changed { from, to } => write!(
+                f,
+                "Replaced {} with {} at {}.",
+                from.object.name(),
+                to.object.name(),
+                position(to)
+            ),
+            WorldChange::PlayerMoved { from, to } => write!(
+                f,
+                "Moved the player from ({:.1}, {:.1}) to ({:.1}, {:.1}).",
+                from[0], from[1], to[0], to[1]
+            ),
+        }
+    }
+}
+
+fn same_position(a: &ObjectAndTransform, b: &ObjectAndTransform) -> bool {
+    (a.position[0] - b.position[0]).abs() < TRANSFORM_EPSILON
+        && (a.position[1] - b.position[1]).abs() < TRANSFORM_EPSILON
+}
+
+// The depth isn't compared, as it only changes the order the objects are drawn in.
+fn same_transform(a: &ObjectAndTransform, b: &ObjectAndTransform) -> bool {
+    same_position(a, b)
+        && (a.scale[0] - b.scale[0]).abs() < TRANSFORM_EPSILON
+        && (a.scale[1] - b.scale[1]).abs() < TRANSFORM_EPSILON
+        && (a.rotation - b.rotation).abs() < TRANSFORM_EPSILON
+}
+
+fn same_object(a: &ObjectAndTransform, b: &ObjectAndTransform) -> bool {
+    a.object == b.object && a.color == b.color
+}
+
+// Removes the old object nearest to `object` among those matching it from the unmatched objects.
+fn take_nearest<'a>(
+    old_objects: &mut [Option<&'a ObjectAndTransform>],
+    object: &ObjectAndTransform,
+    matches: fn(&ObjectAndTransform, &ObjectAndTransform) -> bool,
+) -> Option<&'a ObjectAndTransform> {
+    let distance = |other: &ObjectAndTransform| {
+        (other.position[0] - object.position[0]).hypot(other.position[1] - object.position[1])
+    };
+    let (index, _) = old_objects
+        .iter()
+        .enumerate()
+        .filter_map(|(index, old)| {
+            old.filter(|old| matches(old, object))
+                .map(|old| (index, old))
+        })
+        .min_by(|(_, a), (_, b)| distance(a).total_cmp(&distance(b)))?;
+    old_objects[index].take()
+}
+
+impl World {
+    /// The changes to the objects and the player's position from this world to `new`, for reviewing edits to a level.
+    /// The order of the objects doesn't matter. Objects are matched to the nearest unmatched object with the same
+    /// properties in the other world, and objects whose properties changed to the object at the same place.
+    pub fn diff(&self, new: &World) -> Vec<WorldChange> {
+        let mut old_objects: Vec<_> = self.objects.iter().map(Some).collect();
+        let mut new_objects: Vec<_> = new.objects.iter().map(Some).collect();
+        let mut changes = vec![];
+        if self.player_position != new.player_position {
+            changes.push(WorldChange::PlayerMoved {
+                from: self.player_position,
+                to: new.player_position,
+            });
+        }
+
+        // Unchanged objects are matched first, then moved objects, then objects whose properties changed.
+        let passes: [fn(&ObjectAndTransform, &ObjectAndTransform) -> bool; 3] = [
+            |a, b| same_object(a, b) && same_transform(a, b),
+            same_object,
+            same_transform,
+        ];
+        for matches in passes {
+            for slot in &mut new_objects {
+                let Some(object) = *slot else {
+                    continue;
+                };
+                let Some(old) = take_nearest(&mut old_objects, object, matches) else {
+                    continue;
+                };
+                *slot = None;
+                if !same_object(old, object) {
+                    changes.push(WorldChange::Changed {
+                        from: old.clone(),
+                        to: object.clone(),
+                    });
+                } else if !same_transform(old, object) {
+                    changes.push(WorldChange::Moved {
+                        from: old.clone(),
+                        to: object.clone(),
+                    });
+                }
+            }
+        }
+
+        changes.extend(
+            old_objects
+                .into_iter()
+                .flatten()
+                .map(|old| WorldChange::Removed(old.clone())),
+        );
+        changes.extend(
+            new_objects
+                .into_iter()
+                .flatten()
+                .map(|object| WorldChange::Added(object.clone())),
+        );
+        changes
+    }
+}
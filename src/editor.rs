@@ -1,31 +1,302 @@
-use crate::common::{
-    AppState, ObjectAndTransform, World, WorldObject, PLAYER_DEPTH, PLAYER_RADIUS,
+use crate::{
+    animation::{Animation, LoopMode},
+    calibration::CalibrationTarget,
+    common::{
+        player_color, AppState, ObjectAndTransform, PlayerShape, Stamina, WinCondition, World,
+        WorldObject,
+    },
+    diff::WorldChange,
+    error::Result,
+    events::Bounds,
+    joint::{JointKind, WorldJoint},
+    material::SurfaceMaterial,
+    migration::Migration,
+    overlay::OverlayPainter,
+    polygon::{lines_mesh, stroke_outline},
+    prefab::Prefab,
+    validation::ValidationIssue,
 };
 
-use bevy::{input::mouse::MouseWheel, prelude::*, sprite::MaterialMesh2dBundle};
+use bevy::{
+    app::AppExit,
+    input::mouse::MouseWheel,
+    prelude::*,
+    render::texture::{CompressedImageFormats, ImageType},
+    sprite::{MaterialMesh2dBundle, Mesh2dHandle},
+    window::{PrimaryWindow, WindowCloseRequested},
+};
 use bevy_egui::{
-    egui::{self, DragValue},
+    egui::{self, Color32, DragValue},
     EguiContexts,
 };
-use std::{f32::consts::PI, fs};
+use rand::Rng;
+use std::{
+    collections::HashMap,
+    env,
+    f32::consts::PI,
+    fs,
+    path::{Path, PathBuf},
+    process,
+    time::SystemTime,
+};
 
 const ANCHOR_RADIUS: f32 = 5.0;
 const RING_OUTER_RADIUS: f32 = 100.0;
 const RING_INNER_RADIUS: f32 = 90.0;
+const TEMPLATE_BLOCK_THICKNESS: f32 = 20.0;
+const DEFAULT_SURVIVE_STEPS: usize = 600;
+// Space between the objects and new bounds.
+const BOUNDS_MARGIN: f32 = 500.0;
+const BOUNDS_DASH_LENGTH: f32 = 20.0;
+const SELECTION_COLOR: Color32 = Color32::from_rgb(0, 120, 255);
+// Step of the rotation when snapping, in degrees.
+const ROTATION_SNAP: f32 = 15.0;
+// Long edges use longer dashes, so there are at most this many dashes per edge.
+const MAX_BOUNDS_DASHES: f32 = 200.0;
+const BOUNDS_OUTLINE_THICKNESS: f32 = 4.0;
+// In front of the objects.
+const BOUNDS_OUTLINE_Z: f32 = 900.0;
+// Half the size of a new spawn region, around the player.
+const SPAWN_REGION_HALF_SIZE: f32 = 100.0;
+// Fraction of the screen left around the objects by "Fit view".
+const FIT_VIEW_MARGIN: f32 = 0.1;
+const MINIMAP_SIZE: Vec2 = Vec2::new(200.0, 150.0);
+// Seconds between autosaves of the edited world.
+const AUTOSAVE_INTERVAL: f32 = 30.0;
+// Seconds between checks of whether the world file was changed by another program.
+const WATCH_INTERVAL: f32 = 1.0;
+const MAX_RECENT_WORLDS: usize = 10;
+// Start of the names of the autosaves, which are followed by the editor's process id and the tab's index.
+const AUTOSAVE_PREFIX: &str = "physics_reinforcement_learning_environment_autosave_";
+// Autosaves not updated for this many intervals are left by editors which exited, where it can't be checked directly.
+const STALE_AUTOSAVE_INTERVALS: f32 = 3.0;
+// Size of new objects placed by clicking instead of dragging a rectangle.
+const NEW_OBJECT_SIZE: f32 = 50.0;
+// Drags shorter than this, in pixels, are clicks when placing a new object.
+const MIN_PLACEMENT_DRAG: f32 = 5.0;
+const TERRAIN_BRUSH_WIDTH: f32 = 20.0;
+// Distance between the points of a brush stroke, in pixels.
+const TERRAIN_BRUSH_SPACING: f32 = 10.0;
+// Dragged objects snap to the edges and centers of other objects closer than this, in pixels.
+const SNAP_DISTANCE: f32 = 8.0;
+const GUIDE_COLOR: Color32 = Color32::from_rgb(255, 0, 200);
+// Behind the background decorations.
+const REFERENCE_IMAGE_Z: f32 = -0.08;
+// In front of the reference image, behind the background decorations.
+const GRID_Z: f32 = -0.07;
+// Minimum distance between the lines of the background grid, in pixels.
+const GRID_MIN_SPACING: f32 = 25.0;
+const GRID_COLOR: Color = Color::rgb(0.88, 0.88, 0.88);
+const GRID_AXES_COLOR: Color = Color::rgb(0.55, 0.55, 0.55);
 
 pub fn add_editor_systems(app: &mut App) {
     app.init_resource::<EditorUiState>()
+        .init_resource::<TemplateSettings>()
+        .init_resource::<PrefabLibrary>()
+        .init_resource::<EditorFiles>()
+        .init_resource::<ReferenceImage>()
+        .init_resource::<EditorJoints>()
+        .init_resource::<EditorTabs>()
+        .init_resource::<EditorRevision>()
         .add_system(setup_editor.in_schedule(OnEnter(AppState::Editor)))
-        .add_system(editor_ui_system.in_set(OnUpdate(AppState::Editor)))
-        .add_system(cleanup_editor.in_schedule(OnExit(AppState::Editor)));
+        .add_systems(
+            (
+                update_revision.before(editor_ui_system),
+                editor_ui_system,
+                update_indicators,
+                update_bounds_outline,
+                draw_overlay,
+                autosave_world,
+                watch_world_file,
+                update_reference_image,
+                update_grid,
+            )
+                .in_set(OnUpdate(AppState::Editor)),
+        )
+        .add_system(cleanup_editor.in_schedule(OnExit(AppState::Editor)))
+        .add_system(confirm_close)
+        .add_system(remove_autosave.in_base_set(CoreSet::Last));
 }
 
-#[derive(Component, Clone)]
+#[derive(Component, Clone, PartialEq)]
 pub enum EditorObject {
-    Player,
+    Player(PlayerShape),
+    // A player other than the first one, see World::other_players.
+    OtherPlayer(PlayerShape),
     WorldObject(WorldObject),
 }
 
+// Shows a property of an object in the editor.
+#[derive(Component, Clone, Copy)]
+enum Indicator {
+    // The direction of a conveyor.
+    ConveyorArrow,
+    // The radius of an attractor.
+    AttractorRing,
+}
+
+impl Indicator {
+    fn color(self) -> Color {
+        match self {
+            Indicator::ConveyorArrow => Color::WHITE,
+            Indicator::AttractorRing => Color::PURPLE,
+        }
+    }
+}
+
+// A joint of the world, connecting the entities of the objects instead of their indices
+// so the joints stay valid when objects are added or deleted.
+#[derive(Clone, PartialEq)]
+struct EditorJoint {
+    entities: [Entity; 2],
+    anchors: [[f32; 2]; 2],
+    kind: JointKind,
+}
+
+#[derive(Resource, Default)]
+struct EditorJoints(Vec<EditorJoint>);
+
+impl EditorJoints {
+    // `entities` are the entities of the world's objects, in the same order.
+    fn from_world(world: &World, entities: &[Entity]) -> EditorJoints {
+        EditorJoints(
+            world
+                .joints
+                .iter()
+                .filter_map(|joint| {
+                    Some(EditorJoint {
+                        entities: [
+                            *entities.get(joint.objects[0])?,
+                            *entities.get(joint.objects[1])?,
+                        ],
+                        anchors: joint.anchors,
+                        kind: joint.kind,
+                    })
+                })
+                .collect(),
+        )
+    }
+
+    // The joints of the world, given the index of each object's entity in the world's objects.
+    // Joints of deleted objects are skipped.
+    fn to_world(&self, indices: &HashMap<Entity, usize>) -> Vec<WorldJoint> {
+        self.0
+            .iter()
+            .filter_map(|joint| {
+                Some(WorldJoint {
+                    objects: [
+                        *indices.get(&joint.entities[0])?,
+                        *indices.get(&joint.entities[1])?,
+                    ],
+                    anchors: joint.anchors,
+                    kind: joint.kind,
+                })
+            })
+            .collect()
+    }
+}
+
+// A dash of the outline of the world's bounds or spawn region.
+#[derive(Component)]
+struct BoundsOutline;
+
+#[derive(Component)]
+struct ReferenceImageSprite;
+
+// The lines of the background grid, or its x and y axes.
+#[derive(Component)]
+struct GridLines;
+
+// A color the object is shown with instead of its default color, see ObjectAndTransform::color.
+#[derive(Component, Clone, Copy, PartialEq)]
+struct CustomColor([f32; 4]);
+
+// The color the object is shown with.
+fn object_color(object: &WorldObject, custom_color: Option<&CustomColor>) -> Color {
+    match custom_color {
+        Some(CustomColor([red, green, blue, alpha])) => Color::rgba(*red, *green, *blue, *alpha),
+        None => object.color(),
+    }
+}
+
+// The object as it's saved in the world.
+fn object_and_transform(
+    object: &WorldObject,
+    transform: &Transform,
+    custom_color: Option<&CustomColor>,
+) -> ObjectAndTransform {
+    ObjectAndTransform {
+        object: object.clone(),
+        position: transform.translation.to_array(),
+        scale: transform.scale.truncate().to_array(),
+        rotation: transform.rotation.to_euler(EulerRot::XYZ).2,
+        color: custom_color.map(|custom_color| custom_color.0),
+    }
+}
+
+// A file dialog for worlds, which are saved in the format given by the file's extension, see World::load.
+fn world_file_dialog() -> rfd::FileDialog {
+    rfd::FileDialog::new()
+        .add_filter("World", &["json", "ron", "bin"])
+        .add_filter("JSON", &["json"])
+        .add_filter("RON", &["ron"])
+        .add_filter("Binary", &["bin"])
+}
+
+// An id which none of the teleporters in the editor have.
+fn unused_teleporter_id(objects: &Query<(Entity, &mut EditorObject, &mut Transform)>) -> u32 {
+    objects
+        .iter()
+        .filter_map(|(_, object, _)| match object {
+            EditorObject::WorldObject(WorldObject::Teleporter { id, .. }) => Some(id + 1),
+            _ => None,
+        })
+        .max()
+        .unwrap_or(0)
+}
+
+// Gives copied teleporters new ids starting from `next_id`, so they don't teleport to the teleporters they're copied from.
+// They stay paired with the teleporters copied with them, and are unpaired from the others.
+fn reassign_teleporter_ids(copies: &mut [ObjectAndTransform], next_id: &mut u32) {
+    let mut ids = HashMap::new();
+    for copy in copies.iter() {
+        if let WorldObject::Teleporter { id, .. } = copy.object {
+            ids.entry(id).or_insert_with(|| {
+                *next_id += 1;
+                *next_id - 1
+            });
+        }
+    }
+    for copy in copies {
+        if let WorldObject::Teleporter { id, pair, .. } = &mut copy.object {
+            *id = ids[id];
+            *pair = pair.and_then(|pair| ids.get(&pair).copied());
+        }
+    }
+}
+
+// Creates the entity of an object of a world or prefab, with its custom color.
+fn create_world_object(
+    object_and_transform: &ObjectAndTransform,
+    commands: &mut Commands,
+    meshes: &mut ResMut<Assets<Mesh>>,
+    materials: &mut ResMut<Assets<ColorMaterial>>,
+) -> Entity {
+    let entity = EditorObject::WorldObject(object_and_transform.object.clone()).create_entity(
+        object_and_transform.transform(),
+        commands,
+        meshes,
+        materials,
+    );
+    if let Some(color) = object_and_transform.color {
+        commands.entity(entity).insert((
+            CustomColor(color),
+            materials.add(ColorMaterial::from(object_and_transform.color())),
+        ));
+    }
+    entity
+}
+
 #[derive(Component)]
 enum TransformEditor {
     Anchor,
@@ -85,15 +356,49 @@ fn create_ring(
 }
 
 impl EditorObject {
+    fn name(&self) -> &'static str {
+        match self {
+            EditorObject::Player(_) => "Player",
+            EditorObject::OtherPlayer(_) => "Other player",
+            EditorObject::WorldObject(object) => object.name(),
+        }
+    }
+
+    fn color(&self) -> Color {
+        match self {
+            EditorObject::Player(_) => player_color(0),
+            EditorObject::OtherPlayer(_) => player_color(1),
+            EditorObject::WorldObject(object) => object.color(),
+        }
+    }
+
+    // The smallest axis aligned rectangle containing the object, as (min, max).
+    fn extent(&self, transform: &Transform) -> (Vec2, Vec2) {
+        match self {
+            EditorObject::Player(shape) | EditorObject::OtherPlayer(shape) => {
+                let translation = transform.translation.truncate();
+                let half_size = Vec2::new(shape.radius, shape.half_height());
+                (translation - half_size, translation + half_size)
+            }
+            EditorObject::WorldObject(_) => [[-0.5, -0.5], [0.5, -0.5], [0.5, 0.5], [-0.5, 0.5]]
+                .into_iter()
+                .map(|[x, y]| transform.transform_point(Vec3::new(x, y, 0.0)).truncate())
+                .fold(
+                    (Vec2::splat(f32::INFINITY), Vec2::splat(f32::NEG_INFINITY)),
+                    |(min, max), corner| (min.min(corner), max.max(corner)),
+                ),
+        }
+    }
+
     fn can_drag(&self, transform: &Transform, pointer_position: Vec2) -> bool {
         match self {
-            EditorObject::Player => {
+            EditorObject::Player(shape) | EditorObject::OtherPlayer(shape) => {
                 let translation = transform.translation.truncate();
-                let center_offset = Vec2::new(0.0, PLAYER_DEPTH / 2.0);
-                ((pointer_position - translation).x.abs() < PLAYER_RADIUS
-                    && (pointer_position - translation).y.abs() < PLAYER_DEPTH / 2.0)
-                    || (pointer_position - translation - center_offset).length() < PLAYER_RADIUS
-                    || (pointer_position - translation + center_offset).length() < PLAYER_RADIUS
+                let center_offset = Vec2::new(0.0, shape.depth / 2.0);
+                ((pointer_position - translation).x.abs() < shape.radius
+                    && (pointer_position - translation).y.abs() < shape.depth / 2.0)
+                    || (pointer_position - translation - center_offset).length() < shape.radius
+                    || (pointer_position - translation + center_offset).length() < shape.radius
             }
             EditorObject::WorldObject(_) => {
                 let translation = transform.translation.truncate();
@@ -115,53 +420,502 @@ impl EditorObject {
         materials: &mut ResMut<Assets<ColorMaterial>>,
     ) -> Entity {
         match self {
-            EditorObject::WorldObject(WorldObject::Block { fixed }) => {
-                let color = if fixed {
-                    Color::BLACK
-                } else {
-                    Color::DARK_GRAY
+            EditorObject::WorldObject(ref object) => {
+                let color = object.color();
+                let mesh = object.mesh();
+                let indicator = match object {
+                    WorldObject::Conveyor { .. } => Some((
+                        Indicator::ConveyorArrow,
+                        shape::RegularPolygon::new(0.5, 3).into(),
+                    )),
+                    WorldObject::Attractor { .. } => Some((
+                        Indicator::AttractorRing,
+                        shape::Torus {
+                            radius: 1.0,
+                            ring_radius: 0.01,
+                            subdivisions_segments: 50,
+                            subdivisions_sides: 10,
+                        }
+                        .into(),
+                    )),
+                    _ => None,
                 };
+                let mut entity = commands.spawn(self);
+                entity.insert(MaterialMesh2dBundle {
+                    mesh: meshes.add(mesh).into(),
+                    material: materials.add(ColorMaterial::from(color)),
+                    transform,
+                    ..default()
+                });
+                if let Some((indicator, indicator_mesh)) = indicator {
+                    // The indicator's transform is kept up to date by update_indicators.
+                    entity.with_children(|parent| {
+                        parent
+                            .spawn(MaterialMesh2dBundle {
+                                mesh: meshes.add(indicator_mesh).into(),
+                                material: materials.add(ColorMaterial::from(indicator.color())),
+                                transform: Transform::from_xyz(0.0, 0.0, 0.1),
+                                ..default()
+                            })
+                            .insert(indicator);
+                    });
+                }
+                entity.id()
+            }
+            EditorObject::Player(shape) | EditorObject::OtherPlayer(shape) => {
+                let color = player_color(usize::from(matches!(self, EditorObject::OtherPlayer(_))));
                 commands
                     .spawn(self)
                     .insert(MaterialMesh2dBundle {
-                        mesh: meshes.add(Mesh::from(shape::Quad::new(Vec2::ONE))).into(),
+                        mesh: meshes.add(shape.mesh()).into(),
                         material: materials.add(ColorMaterial::from(color)),
                         transform,
                         ..default()
                     })
                     .id()
             }
-            EditorObject::Player => commands
-                .spawn(self)
-                .insert(MaterialMesh2dBundle {
-                    mesh: meshes
-                        .add(Mesh::from(shape::Capsule {
-                            radius: PLAYER_RADIUS,
-                            rings: 20,
-                            depth: PLAYER_DEPTH,
-                            latitudes: 20,
-                            longitudes: 20,
-                            uv_profile: shape::CapsuleUvProfile::Uniform,
-                        }))
-                        .into(),
-                    material: materials.add(ColorMaterial::from(Color::GRAY)),
-                    transform,
-                    ..default()
-                })
-                .id(),
-            EditorObject::WorldObject(WorldObject::Goal) => commands
-                .spawn(self)
-                .insert(MaterialMesh2dBundle {
-                    mesh: meshes.add(Mesh::from(shape::Quad::new(Vec2::ONE))).into(),
-                    material: materials.add(ColorMaterial::from(Color::rgba(0.0, 1.0, 0.0, 0.5))),
-                    transform,
-                    ..default()
-                })
-                .id(),
         }
     }
 }
 
+#[derive(Clone, Copy)]
+enum Template {
+    Floor,
+    Staircase,
+    Gap,
+}
+
+// Parameters of the quick-insert templates.
+#[derive(Resource)]
+struct TemplateSettings {
+    floor_length: f32,
+    step_count: usize,
+    step_width: f32,
+    step_height: f32,
+    gap_width: f32,
+    landing_length: f32,
+}
+
+impl Default for TemplateSettings {
+    fn default() -> Self {
+        TemplateSettings {
+            floor_length: 500.0,
+            step_count: 5,
+            step_width: 60.0,
+            step_height: 30.0,
+            gap_width: 150.0,
+            landing_length: 200.0,
+        }
+    }
+}
+
+impl TemplateSettings {
+    // Returns the (center, size) of the blocks in the template, relative to the template's center.
+    fn blocks(&self, template: Template) -> Vec<(Vec2, Vec2)> {
+        match template {
+            Template::Floor => vec![(
+                Vec2::ZERO,
+                Vec2::new(self.floor_length, TEMPLATE_BLOCK_THICKNESS),
+            )],
+            Template::Staircase => {
+                let total_width = self.step_count as f32 * self.step_width;
+                let total_height = self.step_count as f32 * self.step_height;
+                (0..self.step_count)
+                    .map(|step| {
+                        let height = (step + 1) as f32 * self.step_height;
+                        (
+                            Vec2::new(
+                                (step as f32 + 0.5) * self.step_width - total_width / 2.0,
+                                (height - total_height) / 2.0,
+                            ),
+                            Vec2::new(self.step_width, height),
+                        )
+                    })
+                    .collect()
+            }
+            Template::Gap => {
+                let offset = (self.gap_width + self.landing_length) / 2.0;
+                let size = Vec2::new(self.landing_length, TEMPLATE_BLOCK_THICKNESS);
+                vec![
+                    (Vec2::new(-offset, 0.0), size),
+                    (Vec2::new(offset, 0.0), size),
+                ]
+            }
+        }
+    }
+
+    fn ui(&mut self, ui: &mut egui::Ui) -> Option<Template> {
+        let mut template = None;
+        egui::Grid::new("Template grid")
+            .spacing([25.0, 5.0])
+            .show(ui, |ui| {
+                ui.label("Length:");
+                ui.add(DragValue::new(&mut self.floor_length).clamp_range(1.0..=f32::MAX));
+                if ui.button("Insert floor").clicked() {
+                    template = Some(Template::Floor);
+                }
+                ui.end_row();
+
+                ui.label("Steps:");
+                ui.horizontal(|ui| {
+                    ui.add(DragValue::new(&mut self.step_count).clamp_range(1..=100));
+                    ui.label("Width:");
+                    ui.add(DragValue::new(&mut self.step_width).clamp_range(1.0..=f32::MAX));
+                    ui.label("Height:");
+                    ui.add(DragValue::new(&mut self.step_height).clamp_range(1.0..=f32::MAX));
+                });
+                if ui.button("Insert staircase").clicked() {
+                    template = Some(Template::Staircase);
+                }
+                ui.end_row();
+
+                ui.label("Gap width:");
+                ui.horizontal(|ui| {
+                    ui.add(DragValue::new(&mut self.gap_width).clamp_range(0.0..=f32::MAX));
+                    ui.label("Landing:");
+                    ui.add(DragValue::new(&mut self.landing_length).clamp_range(1.0..=f32::MAX));
+                });
+                if ui.button("Insert gap").clicked() {
+                    template = Some(Template::Gap);
+                }
+                ui.end_row();
+            });
+        template
+    }
+}
+
+// The prefabs which can be inserted into worlds. They're kept while the app is running and can be exported to files.
+#[derive(Default, Resource)]
+struct PrefabLibrary {
+    prefabs: Vec<Prefab>,
+    // The name of the next prefab saved from a group selection.
+    new_prefab_name: String,
+    // The objects copied with Ctrl+C, which Ctrl+V pastes into any tab.
+    clipboard: Option<Prefab>,
+}
+
+// A world open in the editor, with the editor's state kept with it while another tab is active.
+struct EditorTab {
+    world: World,
+    camera_translation: Vec2,
+    camera_scale: f32,
+    save_path: Option<PathBuf>,
+    save_path_modified: Option<SystemTime>,
+    saved_revision: SavedRevision,
+    unsaved_changes: bool,
+}
+
+impl EditorTab {
+    fn new(world: World) -> EditorTab {
+        EditorTab {
+            world,
+            camera_translation: Vec2::ZERO,
+            camera_scale: 1.0,
+            save_path: None,
+            save_path_modified: None,
+            // Like a newly opened world, the world in the first frame is the saved world.
+            saved_revision: SavedRevision::Pending,
+            unsaved_changes: false,
+        }
+    }
+
+    // The tab of the active world, taking the file state from the editor.
+    fn active(world: World, camera: &Transform, ui_state: &mut EditorUiState) -> EditorTab {
+        EditorTab {
+            world,
+            camera_translation: camera.translation.truncate(),
+            camera_scale: camera.scale.x,
+            save_path: ui_state.save_path.take(),
+            save_path_modified: ui_state.save_path_modified,
+            saved_revision: ui_state.saved_revision,
+            unsaved_changes: ui_state.unsaved_changes,
+        }
+    }
+
+    // Opens the tab's world in the editor, replacing the active world.
+    fn open(
+        self,
+        world: &mut ResMut<World>,
+        commands: &mut Commands,
+        objects: &Query<(Entity, &mut EditorObject, &mut Transform)>,
+        transform_editors: &Query<
+            (Entity, &mut Transform, &TransformEditor),
+            (Without<EditorObject>, Without<Camera>),
+        >,
+        camera: &mut Transform,
+        ui_state: &mut ResMut<EditorUiState>,
+        joints: &mut ResMut<EditorJoints>,
+        meshes: &mut ResMut<Assets<Mesh>>,
+        materials: &mut ResMut<Assets<ColorMaterial>>,
+    ) {
+        **world = self.world;
+        load_world(
+            world,
+            commands,
+            objects,
+            transform_editors,
+            camera,
+            ui_state,
+            joints,
+            meshes,
+            materials,
+        );
+        camera.translation.x = self.camera_translation.x;
+        camera.translation.y = self.camera_translation.y;
+        camera.scale.x = self.camera_scale;
+        camera.scale.y = self.camera_scale;
+        ui_state.save_path = self.save_path;
+        ui_state.save_path_modified = self.save_path_modified;
+        // The world's entities are spawned again, so its revision changes.
+        ui_state.saved_revision = SavedRevision::respawned(self.unsaved_changes);
+        ui_state.unsaved_changes = self.unsaved_changes;
+    }
+
+    fn name(save_path: Option<&PathBuf>, unsaved_changes: bool) -> String {
+        let name = save_path.and_then(|path| path.file_name()).map_or_else(
+            || "Untitled".to_string(),
+            |name| name.to_string_lossy().into_owned(),
+        );
+        if unsaved_changes {
+            format!("{name} *")
+        } else {
+            name
+        }
+    }
+}
+
+// The worlds open in the editor. The active tab's world is the World resource and the editor's objects,
+// so its place in `tabs` is None.
+#[derive(Resource)]
+struct EditorTabs {
+    tabs: Vec<Option<EditorTab>>,
+    active: usize,
+}
+
+impl Default for EditorTabs {
+    fn default() -> Self {
+        EditorTabs {
+            tabs: vec![None],
+            active: 0,
+        }
+    }
+}
+
+impl EditorTabs {
+    fn unsaved_changes_in_other_tabs(&self) -> bool {
+        self.tabs.iter().flatten().any(|tab| tab.unsaved_changes)
+    }
+}
+
+// The autosaves of the open worlds, kept in the temporary directory, and the recently opened or saved world files,
+// kept in the config directory.
+#[derive(Resource)]
+struct EditorFiles {
+    autosave_timer: Timer,
+    watch_timer: Timer,
+    // The autosaves and worlds of the tabs of editors which didn't exit normally, until they're recovered
+    // or discarded.
+    recovered: Vec<(PathBuf, World)>,
+    // The most recent first.
+    recent: Vec<PathBuf>,
+}
+
+impl Default for EditorFiles {
+    fn default() -> Self {
+        EditorFiles {
+            autosave_timer: Timer::from_seconds(AUTOSAVE_INTERVAL, TimerMode::Repeating),
+            watch_timer: Timer::from_seconds(WATCH_INTERVAL, TimerMode::Repeating),
+            // The autosaves are removed when the app exits normally.
+            recovered: leftover_autosaves(),
+            recent: fs::read_to_string(recent_worlds_path())
+                .ok()
+                .and_then(|contents| serde_json::from_str(&contents).ok())
+                .unwrap_or_default(),
+        }
+    }
+}
+
+impl EditorFiles {
+    fn add_recent(&mut self, path: PathBuf) {
+        self.recent.retain(|recent| *recent != path);
+        self.recent.insert(0, path);
+        self.recent.truncate(MAX_RECENT_WORLDS);
+        let result = serde_json::to_string(&self.recent)
+            .map_err(|error| error.to_string())
+            .and_then(|contents| {
+                let path = recent_worlds_path();
+                if let Some(directory) = path.parent() {
+                    fs::create_dir_all(directory).map_err(|error| error.to_string())?;
+                }
+                fs::write(path, contents).map_err(|error| error.to_string())
+            });
+        if let Err(error) = result {
+            println!("Couldn't save the recent worlds: {error}.");
+        }
+    }
+
+    // Removes the autosaves of the recovered worlds. Recovered worlds are autosaved again with this editor's tabs.
+    fn take_recovered(&mut self) -> Vec<World> {
+        std::mem::take(&mut self.recovered)
+            .into_iter()
+            .map(|(path, world)| {
+                let _ = fs::remove_file(path);
+                world
+            })
+            .collect()
+    }
+}
+
+// A semi-transparent image drawn behind the objects, to trace levels from sketches. It isn't saved in the world.
+#[derive(Resource)]
+struct ReferenceImage {
+    // The file to load the image from, set when importing it.
+    path: Option<PathBuf>,
+    image: Option<Handle<Image>>,
+    // Position of the image's center, in world units.
+    offset: Vec2,
+    // World units per pixel.
+    scale: f32,
+    opacity: f32,
+}
+
+impl Default for ReferenceImage {
+    fn default() -> Self {
+        ReferenceImage {
+            path: None,
+            image: None,
+            offset: Vec2::ZERO,
+            scale: 1.0,
+            opacity: 0.5,
+        }
+    }
+}
+
+impl ReferenceImage {
+    fn ui(&mut self, ui: &mut egui::Ui) {
+        ui.horizontal(|ui| {
+            if ui.button("Import PNG").clicked() {
+                self.path = rfd::FileDialog::new()
+                    .add_filter("PNG", &["png"])
+                    .pick_file();
+            }
+            if self.image.is_some() && ui.button("Remove").clicked() {
+                self.image = None;
+            }
+        });
+        if self.image.is_none() {
+            return;
+        }
+        egui::Grid::new("Reference image grid")
+            .spacing([25.0, 5.0])
+            .show(ui, |ui| {
+                ui.label("Offset:");
+                ui.horizontal(|ui| {
+                    ui.add(DragValue::new(&mut self.offset.x));
+                    ui.add(DragValue::new(&mut self.offset.y));
+                });
+                ui.end_row();
+
+                ui.label("Scale:").on_hover_text("World units per pixel.");
+                ui.add(
+                    DragValue::new(&mut self.scale)
+                        .speed(0.01)
+                        .clamp_range(0.001..=f32::MAX),
+                );
+                ui.end_row();
+
+                ui.label("Opacity:");
+                ui.add(egui::Slider::new(&mut self.opacity, 0.0..=1.0));
+                ui.end_row();
+            });
+    }
+}
+
+fn modified_time(path: &Path) -> Option<SystemTime> {
+    fs::metadata(path)
+        .and_then(|metadata| metadata.modified())
+        .ok()
+}
+
+// The autosave of the world in the tab at the index. The process id keeps the autosaves of editors running
+// at the same time apart.
+fn autosave_path(index: usize) -> PathBuf {
+    env::temp_dir().join(format!("{AUTOSAVE_PREFIX}{}_{index}.json", process::id()))
+}
+
+// The autosaves left by editors which didn't exit normally, ordered by editor and tab.
+fn leftover_autosaves() -> Vec<(PathBuf, World)> {
+    let Ok(entries) = fs::read_dir(env::temp_dir()) else {
+        return Vec::new();
+    };
+    let mut autosaves: Vec<_> = entries
+        .flatten()
+        .filter_map(|entry| {
+            let path = entry.path();
+            let name = path.file_name()?.to_str()?;
+            let (pid, index) = name
+                .strip_prefix(AUTOSAVE_PREFIX)?
+                .strip_suffix(".json")?
+                .split_once('_')?;
+            let pid: u32 = pid.parse().ok()?;
+            let index: usize = index.parse().ok()?;
+            if pid == process::id() || editor_running(pid, modified_time(&path)) {
+                return None;
+            }
+            Some(((pid, index), path))
+        })
+        .collect();
+    autosaves.sort();
+    autosaves
+        .into_iter()
+        .filter_map(|(_, path)| {
+            let world = World::load(&path).ok()?;
+            Some((path, world))
+        })
+        .collect()
+}
+
+// Whether the editor with the process id, which last autosaved at the time, is still running. Without /proc,
+// outside Linux, editors whose autosaves weren't updated for a few intervals are assumed to have exited.
+fn editor_running(pid: u32, autosaved: Option<SystemTime>) -> bool {
+    let processes = Path::new("/proc");
+    if processes.is_dir() {
+        return processes.join(pid.to_string()).exists();
+    }
+    autosaved
+        .and_then(|time| time.elapsed().ok())
+        .map_or(false, |elapsed| {
+            elapsed.as_secs_f32() < STALE_AUTOSAVE_INTERVALS * AUTOSAVE_INTERVAL
+        })
+}
+
+// Removes the autosaves of the tabs from the index on.
+fn remove_autosaves(from: usize) {
+    for path in (from..).map(autosave_path).take_while(|path| path.exists()) {
+        let _ = fs::remove_file(path);
+    }
+}
+
+// The directory for the editor's settings, which unlike the temporary directory isn't cleared by the OS.
+fn config_dir() -> PathBuf {
+    let home = || env::var_os("HOME").map(PathBuf::from);
+    let base = if cfg!(windows) {
+        env::var_os("APPDATA").map(PathBuf::from)
+    } else if cfg!(target_os = "macos") {
+        home().map(|home| home.join("Library").join("Application Support"))
+    } else {
+        env::var_os("XDG_CONFIG_HOME")
+            .map(PathBuf::from)
+            .or_else(|| home().map(|home| home.join(".config")))
+    };
+    base.unwrap_or_else(env::temp_dir)
+        .join("physics_reinforcement_learning_environment")
+}
+
+fn recent_worlds_path() -> PathBuf {
+    config_dir().join("recent.json")
+}
+
 struct DragState {
     initial_pointer_offset: Vec2,
     initial_camera_translation: Vec2,
@@ -258,6 +1012,12 @@ struct SelectedState {
     entity: Entity,
     transform_editors: TransformEditors,
     prev_z_index: f32,
+    // The keyframe shown in the animation timeline.
+    selected_keyframe: usize,
+    // Whether clicking adds vertices to the selected polygon instead of selecting objects.
+    placing_vertices: bool,
+    // The kind of joint created when clicking another object, instead of selecting it.
+    connecting: Option<JointKind>,
 }
 
 impl SelectedState {
@@ -357,6 +1117,17 @@ impl SelectedState {
         }
     }
 
+    // Whether the object is dragged without resizing or rotating it.
+    fn is_moving(&self) -> bool {
+        matches!(
+            self.transform_editors,
+            TransformEditors::Rect {
+                dragging: RectDrag::None(_),
+                ..
+            } | TransformEditors::None { .. }
+        )
+    }
+
     fn drag(
         &mut self,
         objects: &mut Query<(Entity, &mut EditorObject, &mut Transform)>,
@@ -366,6 +1137,8 @@ impl SelectedState {
         >,
         initial_pointer_position: Vec2,
         pointer_position: Vec2,
+        snap_rotation: bool,
+        lock_aspect_ratio: bool,
     ) {
         match &self.transform_editors {
             TransformEditors::Rect { dragging, .. } => {
@@ -393,8 +1166,12 @@ impl SelectedState {
                         } else {
                             0.0
                         };
-                        rect_transform.rotation =
-                            Quat::from_rotation_z(initial_rotation + rotation_change);
+                        let mut rotation = initial_rotation + rotation_change;
+                        if snap_rotation {
+                            let step = ROTATION_SNAP.to_radians();
+                            rotation = (rotation / step).round() * step;
+                        }
+                        rect_transform.rotation = Quat::from_rotation_z(rotation);
                     }
                     RectDrag::Left(initial_translation) => {
                         let new_position =
@@ -449,6 +1226,20 @@ impl SelectedState {
                             (top_anchor_position - bottom_anchor_position).dot(y_axis);
                     }
                 }
+                // Dragging a side anchor scales the other axis proportionally.
+                if lock_aspect_ratio && size.x != 0.0 && size.y != 0.0 {
+                    match dragging {
+                        RectDrag::Left(_) | RectDrag::Right(_) => {
+                            rect_transform.scale.y =
+                                size.y * (rect_transform.scale.x / size.x).abs();
+                        }
+                        RectDrag::Top(_) | RectDrag::Bottom(_) => {
+                            rect_transform.scale.x =
+                                size.x * (rect_transform.scale.y / size.y).abs();
+                        }
+                        RectDrag::None(_) | RectDrag::Rotation(_) => {}
+                    }
+                }
 
                 self.transform_editors
                     .update_transform(&rect_transform, transform_editors);
@@ -466,51 +1257,437 @@ impl SelectedState {
     }
 }
 
-#[derive(Default, Resource)]
-struct EditorUiState {
-    drag: Option<DragState>,
-    selected: Option<SelectedState>,
+// Several objects selected with shift-click or box selection, which are moved, rotated and deleted together.
+struct GroupSelection {
+    entities: Vec<Entity>,
+    // The translations of the objects when the current drag started.
+    initial_translations: Vec<Vec2>,
+    // Degrees the group was rotated by since it was selected.
+    rotation: f32,
 }
 
-impl EditorUiState {
-    fn clear_selection(
-        &mut self,
+impl GroupSelection {
+    fn new(entities: Vec<Entity>) -> Option<GroupSelection> {
+        (!entities.is_empty()).then_some(GroupSelection {
+            entities,
+            initial_translations: vec![],
+            rotation: 0.0,
+        })
+    }
+
+    fn drag_start(&mut self, objects: &Query<(Entity, &mut EditorObject, &mut Transform)>) {
+        self.initial_translations = self
+            .entities
+            .iter()
+            .map(|entity| objects.get(*entity).unwrap().2.translation.truncate())
+            .collect();
+    }
+
+    fn drag(
+        &self,
         objects: &mut Query<(Entity, &mut EditorObject, &mut Transform)>,
-        commands: &mut Commands,
+        initial_pointer_position: Vec2,
+        pointer_position: Vec2,
     ) {
-        if let Some(selected_state) = self.selected.take() {
-            selected_state.clear_selection(objects, commands);
+        for (entity, initial_translation) in self.entities.iter().zip(&self.initial_translations) {
+            let new_position = *initial_translation + (pointer_position - initial_pointer_position);
+            let (_, _, mut transform) = objects.get_mut(*entity).unwrap();
+            transform.translation.x = new_position.x;
+            transform.translation.y = new_position.y;
         }
     }
 
-    fn create_and_select(
-        &mut self,
-        world_object: WorldObject,
-        position: Vec2,
-        camera_scale: f32,
+    // Mirrors the objects about the center of the group.
+    fn flip(
+        &self,
+        axis: FlipAxis,
         objects: &mut Query<(Entity, &mut EditorObject, &mut Transform)>,
-        commands: &mut Commands,
+        current_meshes: &mut Query<&mut Mesh2dHandle>,
         meshes: &mut ResMut<Assets<Mesh>>,
-        materials: &mut ResMut<Assets<ColorMaterial>>,
     ) {
-        self.clear_selection(objects, commands);
-
-        let selection_z_index = objects
+        let index = axis.index();
+        let center = self
+            .entities
             .iter()
-            .map(|(_, _, transform)| transform.translation.z)
-            .reduce(f32::max)
-            .unwrap()
-            + 1.0; // We can unwrap as player will always be there.
+            .map(|entity| objects.get(*entity).unwrap().2.translation.truncate())
+            .sum::<Vec2>()
+            / self.entities.len() as f32;
+        for entity in &self.entities {
+            let (_, mut object, mut transform) = objects.get_mut(*entity).unwrap();
+            transform.translation[index] = 2.0 * center[index] - transform.translation[index];
+            if let EditorObject::WorldObject(world_object) = &mut *object {
+                flip_world_object(world_object, &mut transform, axis);
+                if let WorldObject::Polygon { .. } = world_object {
+                    if let Ok(mut mesh) = current_meshes.get_mut(*entity) {
+                        *mesh = meshes.add(world_object.mesh()).into();
+                    }
+                }
+            }
+        }
+    }
+
+    // Moves and rotates each object by random amounts within the ranges, players only move as they aren't rotated.
+    fn jitter(
+        &self,
+        jitter: JitterSettings,
+        objects: &mut Query<(Entity, &mut EditorObject, &mut Transform)>,
+        rng: &mut impl Rng,
+    ) {
+        for entity in &self.entities {
+            let (_, object, mut transform) = objects.get_mut(*entity).unwrap();
+            transform.translation.x += rng.gen_range(-jitter.offset..=jitter.offset);
+            transform.translation.y += rng.gen_range(-jitter.offset..=jitter.offset);
+            if let EditorObject::WorldObject(_) = *object {
+                let angle = rng.gen_range(-jitter.rotation..=jitter.rotation) * PI / 180.0;
+                transform.rotation = Quat::from_rotation_z(angle) * transform.rotation;
+            }
+        }
+    }
+
+    // Rotates the objects around the center of the group, players only move as they aren't rotated.
+    fn rotate(&self, angle: f32, objects: &mut Query<(Entity, &mut EditorObject, &mut Transform)>) {
+        let center = self
+            .entities
+            .iter()
+            .map(|entity| objects.get(*entity).unwrap().2.translation.truncate())
+            .sum::<Vec2>()
+            / self.entities.len() as f32;
+        let rotation = Quat::from_rotation_z(angle);
+        for entity in &self.entities {
+            let (_, object, mut transform) = objects.get_mut(*entity).unwrap();
+            let offset = rotation * (transform.translation.truncate() - center).extend(0.0);
+            transform.translation.x = center.x + offset.x;
+            transform.translation.y = center.y + offset.y;
+            if let EditorObject::WorldObject(_) = *object {
+                transform.rotation = rotation * transform.rotation;
+            }
+        }
+    }
+}
+
+// Ranges of the random changes "Jitter" applies to the group selection, to rough up regular layouts.
+#[derive(Clone, Copy)]
+struct JitterSettings {
+    // Largest offset along each axis, in world units.
+    offset: f32,
+    // Largest rotation, in degrees.
+    rotation: f32,
+}
+
+impl Default for JitterSettings {
+    fn default() -> Self {
+        JitterSettings {
+            offset: 10.0,
+            rotation: 5.0,
+        }
+    }
+}
+
+// The copies "Repeat" makes of the selected object, for rows of objects like stairs or pillars.
+#[derive(Clone, Copy)]
+struct RepeatSettings {
+    count: usize,
+    // The offset of each copy from the previous one, in world units.
+    offset: Vec2,
+}
+
+impl Default for RepeatSettings {
+    fn default() -> Self {
+        RepeatSettings {
+            count: 10,
+            offset: Vec2::new(120.0, 0.0),
+        }
+    }
+}
+
+// A new object being placed by dragging a rectangle on the canvas.
+struct NewObjectPlacement {
+    object: WorldObject,
+    // The corner the drag started at and the corner under the pointer.
+    rect: Option<(Vec2, Vec2)>,
+}
+
+// Painting terrain, each stroke becomes a polygon.
+struct TerrainBrush {
+    width: f32,
+    // The points of the stroke being painted.
+    stroke: Vec<Vec2>,
+}
+
+// Measuring distances and angles by dragging a line on the canvas.
+#[derive(Default)]
+struct MeasureTool {
+    // The start and end of the last line dragged.
+    line: Option<(Vec2, Vec2)>,
+}
+
+// An object of the edited world, which stays the same when the editor's entities are respawned.
+#[derive(Clone, Copy)]
+enum WorldIndex {
+    Player,
+    // The index in World::other_players.
+    OtherPlayer(usize),
+    // The index in World::objects.
+    Object(usize),
+}
+
+// The editor's view when starting a play-test, restored when returning to the editor.
+struct TestView {
+    camera_translation: Vec2,
+    camera_scale: f32,
+    selection: Vec<WorldIndex>,
+}
+
+// Actions leaving the edited world, which ask to save the unsaved changes first.
+#[derive(Clone, PartialEq)]
+enum PendingAction {
+    Play,
+    // Opens the file, or picks the file to open if None.
+    Open(Option<PathBuf>),
+    NewWorld,
+    Recover,
+    CloseTab,
+    Quit,
+}
+
+// The revision of the world when it was last opened or saved, to find unsaved changes.
+#[derive(Clone, Copy, Default, PartialEq)]
+enum SavedRevision {
+    // Set to the world's revision in the frame after it's opened or saved, once its entities are spawned.
+    #[default]
+    Pending,
+    Saved(u64),
+    // The world has changes which aren't saved anywhere, like a recovered or imported world.
+    Unsaved,
+}
+
+impl SavedRevision {
+    // The saved revision of a world whose entities are spawned again, like when returning from play-testing.
+    fn respawned(unsaved_changes: bool) -> SavedRevision {
+        if unsaved_changes {
+            SavedRevision::Unsaved
+        } else {
+            SavedRevision::Pending
+        }
+    }
+}
+
+#[derive(Default, Resource)]
+struct EditorUiState {
+    drag: Option<DragState>,
+    selected: Option<SelectedState>,
+    group: Option<GroupSelection>,
+    // The corner the box selection started at and the corner under the pointer.
+    selection_box: Option<(Vec2, Vec2)>,
+    placement: Option<NewObjectPlacement>,
+    brush: Option<TerrainBrush>,
+    measure: Option<MeasureTool>,
+    // The world position under the pointer in the last frame, for the coordinate readout.
+    pointer_position: Option<Vec2>,
+    // Lines showing which edges or centers the dragged objects snapped to, as (start, end).
+    guides: Vec<(Vec2, Vec2)>,
+    // Whether dragging the rotation ring always snaps, instead of only while holding shift.
+    snap_rotation: bool,
+    // Whether dragging a side anchor always keeps the proportions, instead of only while holding shift.
+    lock_aspect_ratio: bool,
+    jitter: JitterSettings,
+    repeat: RepeatSettings,
+    // The file the world was last opened from or saved to, used by Ctrl+S.
+    save_path: Option<PathBuf>,
+    // When the file was last modified, to find changes made by other programs.
+    save_path_modified: Option<SystemTime>,
+    // Whether the file was changed by another program, until the world is reloaded or the change is ignored.
+    file_changed: bool,
+    // The migrations applied to the opened world as it was saved in an older format, until they're dismissed.
+    migrations: Vec<Migration>,
+    // The issues found the last time the world was validated, None if it wasn't validated since it was opened.
+    validation: Option<Vec<ValidationIssue>>,
+    // The file the world was last compared with by "Compare with file…", and the changes from it to the world.
+    comparison: Option<(PathBuf, Vec<WorldChange>)>,
+    saved_revision: SavedRevision,
+    unsaved_changes: bool,
+    // An action which would discard the unsaved changes, until the user saves them or confirms discarding them.
+    pending_action: Option<PendingAction>,
+    // Why saving from the unsaved changes prompt failed, shown until the prompt is closed.
+    save_error: Option<String>,
+    // Whether the editor is left to play-test the world with "Test from here".
+    testing: bool,
+    // The view to restore when returning from the play-test.
+    test_view: Option<TestView>,
+    // The objects to select in the first frame after returning from the play-test, once they're spawned.
+    restored_selection: Vec<Entity>,
+    calibration_target: CalibrationTarget,
+}
+
+impl EditorUiState {
+    // Whether the action can run now, otherwise it's pending until the unsaved changes are saved or discarded.
+    fn confirm(&mut self, action: PendingAction) -> bool {
+        if self.unsaved_changes {
+            self.pending_action = Some(action);
+            false
+        } else {
+            true
+        }
+    }
+
+    // The selected object, or the objects of the group selection.
+    fn selected_entities(&self) -> Vec<Entity> {
+        self.selected
+            .as_ref()
+            .map(|selected_state| selected_state.entity)
+            .into_iter()
+            .chain(self.group.iter().flat_map(|group| group.entities.clone()))
+            .collect()
+    }
+
+    fn clear_selection(
+        &mut self,
+        objects: &mut Query<(Entity, &mut EditorObject, &mut Transform)>,
+        commands: &mut Commands,
+    ) {
+        if let Some(selected_state) = self.selected.take() {
+            selected_state.clear_selection(objects, commands);
+        }
+        self.group = None;
+    }
+
+    // Adds the object under the pointer to the group selection, or removes it if it's already in the group.
+    // Starts a box selection if there is no object under the pointer.
+    fn shift_click(
+        &mut self,
+        pointer_position: Vec2,
+        objects: &mut Query<(Entity, &mut EditorObject, &mut Transform)>,
+        commands: &mut Commands,
+    ) {
+        let Some(entity) = object_at(pointer_position, objects) else {
+            self.selection_box = Some((pointer_position, pointer_position));
+            return;
+        };
+        let mut entities = self.take_selected_entities(objects, commands);
+        if let Some(index) = entities.iter().position(|selected| *selected == entity) {
+            entities.remove(index);
+        } else {
+            entities.push(entity);
+        }
+        self.group = GroupSelection::new(entities);
+    }
+
+    // Adds the objects whose center is in the selection box to the group selection.
+    fn box_select(
+        &mut self,
+        objects: &mut Query<(Entity, &mut EditorObject, &mut Transform)>,
+        commands: &mut Commands,
+    ) {
+        let Some((start, end)) = self.selection_box.take() else {
+            return;
+        };
+        let (min, max) = (start.min(end), start.max(end));
+        let inside: Vec<_> = objects
+            .iter()
+            .filter(|(_, _, transform)| {
+                let translation = transform.translation.truncate();
+                translation.cmpge(min).all() && translation.cmple(max).all()
+            })
+            .map(|(entity, _, _)| entity)
+            .collect();
+        let mut entities = self.take_selected_entities(objects, commands);
+        for entity in inside {
+            if !entities.contains(&entity) {
+                entities.push(entity);
+            }
+        }
+        self.group = GroupSelection::new(entities);
+    }
+
+    // Deletes the selected objects, except the first player which can't be deleted.
+    fn delete_selection(
+        &mut self,
+        objects: &mut Query<(Entity, &mut EditorObject, &mut Transform)>,
+        commands: &mut Commands,
+    ) {
+        self.drag = None;
+        for entity in self.take_selected_entities(objects, commands) {
+            if !matches!(objects.get(entity), Ok((_, EditorObject::Player(_), _))) {
+                commands.entity(entity).despawn_recursive();
+            }
+        }
+    }
+
+    // Moves the selected objects by the offset.
+    fn nudge_selection(
+        &self,
+        offset: Vec2,
+        objects: &mut Query<(Entity, &mut EditorObject, &mut Transform)>,
+        transform_editors: &mut Query<
+            (Entity, &mut Transform, &TransformEditor),
+            (Without<EditorObject>, Without<Camera>),
+        >,
+    ) {
+        let entities = self.selected.iter().map(|selected| selected.entity).chain(
+            self.group
+                .iter()
+                .flat_map(|group| group.entities.iter().copied()),
+        );
+        for entity in entities {
+            let (_, _, mut transform) = objects.get_mut(entity).unwrap();
+            transform.translation.x += offset.x;
+            transform.translation.y += offset.y;
+        }
+        if let Some(selected) = &self.selected {
+            let (_, _, transform) = objects.get(selected.entity).unwrap();
+            selected
+                .transform_editors
+                .update_transform(transform, transform_editors);
+        }
+    }
+
+    // Clears the selection and returns the selected objects, so they can be added to a group.
+    fn take_selected_entities(
+        &mut self,
+        objects: &mut Query<(Entity, &mut EditorObject, &mut Transform)>,
+        commands: &mut Commands,
+    ) -> Vec<Entity> {
+        let mut entities = self.group.take().map_or(vec![], |group| group.entities);
+        if let Some(selected_state) = self.selected.take() {
+            entities.push(selected_state.entity);
+            selected_state.clear_selection(objects, commands);
+        }
+        entities
+    }
+
+    fn create_and_select(
+        &mut self,
+        editor_object: EditorObject,
+        position: Vec2,
+        size: Vec2,
+        camera_scale: f32,
+        objects: &mut Query<(Entity, &mut EditorObject, &mut Transform)>,
+        commands: &mut Commands,
+        meshes: &mut ResMut<Assets<Mesh>>,
+        materials: &mut ResMut<Assets<ColorMaterial>>,
+    ) {
+        self.clear_selection(objects, commands);
+
+        let selection_z_index = objects
+            .iter()
+            .map(|(_, _, transform)| transform.translation.z)
+            .reduce(f32::max)
+            .unwrap()
+            + 1.0; // We can unwrap as player will always be there.
 
-        let transform = Transform::from_xyz(position.x, position.y, selection_z_index)
-            .with_scale(Vec3::new(50.0, 50.0, 1.0));
-        let entity = EditorObject::WorldObject(world_object.clone())
+        let mut transform = Transform::from_xyz(position.x, position.y, selection_z_index);
+        // Players are meshed at their size, so they aren't scaled.
+        if let EditorObject::WorldObject(_) = editor_object {
+            transform.scale = size.extend(1.0);
+        }
+        let entity = editor_object
+            .clone()
             .create_entity(transform, commands, meshes, materials);
 
         self.selected = Some(SelectedState {
             entity,
             transform_editors: self.create_transform_editors(
-                &EditorObject::WorldObject(world_object),
+                &editor_object,
                 &transform,
                 camera_scale,
                 selection_z_index,
@@ -519,7 +1696,195 @@ impl EditorUiState {
                 materials,
             ),
             prev_z_index: transform.translation.z,
+            selected_keyframe: 0,
+            placing_vertices: false,
+            connecting: None,
+        });
+    }
+
+    // Creates the object being placed in the dragged rectangle, or with the default size at the click.
+    fn finish_placement(
+        &mut self,
+        camera_scale: f32,
+        objects: &mut Query<(Entity, &mut EditorObject, &mut Transform)>,
+        commands: &mut Commands,
+        meshes: &mut ResMut<Assets<Mesh>>,
+        materials: &mut ResMut<Assets<ColorMaterial>>,
+    ) {
+        let Some((start, end)) = self.placement.as_ref().and_then(|placement| placement.rect)
+        else {
+            return;
+        };
+        let object = self.placement.take().unwrap().object;
+        let (position, size) =
+            if (end - start).abs().max_element() < MIN_PLACEMENT_DRAG * camera_scale {
+                (start, Vec2::splat(NEW_OBJECT_SIZE))
+            } else {
+                ((start + end) / 2.0, (end - start).abs().max(Vec2::ONE))
+            };
+        self.create_and_select(
+            EditorObject::WorldObject(object),
+            position,
+            size,
+            camera_scale,
+            objects,
+            commands,
+            meshes,
+            materials,
+        );
+    }
+
+    // Adds the stroke being painted with the terrain brush to the world as a polygon.
+    fn finish_stroke(
+        &mut self,
+        objects: &mut Query<(Entity, &mut EditorObject, &mut Transform)>,
+        commands: &mut Commands,
+        meshes: &mut ResMut<Assets<Mesh>>,
+        materials: &mut ResMut<Assets<ColorMaterial>>,
+    ) {
+        let Some(brush) = &mut self.brush else {
+            return;
+        };
+        let stroke = std::mem::take(&mut brush.stroke);
+        if stroke.len() < 2 {
+            return;
+        }
+        let points: Vec<_> = stroke.iter().map(|point| point.to_array()).collect();
+        let outline: Vec<_> = stroke_outline(&points, brush.width)
+            .into_iter()
+            .map(Vec2::from_array)
+            .collect();
+        let (min, max) = outline.iter().fold(
+            (Vec2::splat(f32::INFINITY), Vec2::splat(f32::NEG_INFINITY)),
+            |(min, max), point| (min.min(*point), max.max(*point)),
+        );
+        let center = (min + max) / 2.0;
+        let size = (max - min).max(Vec2::ONE);
+
+        let z_index = objects
+            .iter()
+            .map(|(_, _, transform)| transform.translation.z)
+            .reduce(f32::max)
+            .unwrap()
+            + 1.0; // We can unwrap as player will always be there.
+                   // The polygon covers the stroke's bounding box, so it can be dragged anywhere on the stroke's extent.
+        let transform =
+            Transform::from_translation(center.extend(z_index)).with_scale(size.extend(1.0));
+        EditorObject::WorldObject(WorldObject::Polygon {
+            points: outline
+                .iter()
+                .map(|point| ((*point - center) / size).to_array())
+                .collect(),
+        })
+        .create_entity(transform, commands, meshes, materials);
+    }
+
+    fn insert_template(
+        &mut self,
+        blocks: Vec<(Vec2, Vec2)>,
+        position: Vec2,
+        objects: &mut Query<(Entity, &mut EditorObject, &mut Transform)>,
+        commands: &mut Commands,
+        meshes: &mut ResMut<Assets<Mesh>>,
+        materials: &mut ResMut<Assets<ColorMaterial>>,
+    ) {
+        self.clear_selection(objects, commands);
+
+        let z_index = objects
+            .iter()
+            .map(|(_, _, transform)| transform.translation.z)
+            .reduce(f32::max)
+            .unwrap()
+            + 1.0; // We can unwrap as player will always be there.
+
+        for (center, size) in blocks {
+            let translation = position + center;
+            let transform = Transform::from_xyz(translation.x, translation.y, z_index)
+                .with_scale(size.extend(1.0));
+            EditorObject::WorldObject(WorldObject::Block {
+                fixed: true,
+                animation: None,
+                ccd: false,
+                friction: None,
+                restitution: None,
+                density: 1.0,
+                material: None,
+            })
+            .create_entity(transform, commands, meshes, materials);
+        }
+    }
+
+    // Creates the prefab's objects around the position and selects them as a group.
+    fn insert_prefab(
+        &mut self,
+        prefab: &Prefab,
+        position: Vec2,
+        objects: &mut Query<(Entity, &mut EditorObject, &mut Transform)>,
+        commands: &mut Commands,
+        meshes: &mut ResMut<Assets<Mesh>>,
+        materials: &mut ResMut<Assets<ColorMaterial>>,
+    ) {
+        self.clear_selection(objects, commands);
+
+        let z_index = objects
+            .iter()
+            .map(|(_, _, transform)| transform.translation.z)
+            .reduce(f32::max)
+            .unwrap()
+            + 1.0; // We can unwrap as player will always be there.
+
+        let mut copies = prefab.objects_at(position.to_array(), z_index);
+        reassign_teleporter_ids(&mut copies, &mut unused_teleporter_id(objects));
+        let entities = copies
+            .into_iter()
+            .map(|object_and_transform| {
+                create_world_object(&object_and_transform, commands, meshes, materials)
+            })
+            .collect();
+        self.group = GroupSelection::new(entities);
+    }
+
+    // Creates copies of the selected object, each offset from the previous one,
+    // and selects the object and its copies as a group.
+    fn repeat_selection(
+        &mut self,
+        objects: &mut Query<(Entity, &mut EditorObject, &mut Transform)>,
+        custom_colors: &Query<&CustomColor>,
+        commands: &mut Commands,
+        meshes: &mut ResMut<Assets<Mesh>>,
+        materials: &mut ResMut<Assets<ColorMaterial>>,
+    ) {
+        let Some(selected) = &self.selected else {
+            return;
+        };
+        let entity = selected.entity;
+        self.clear_selection(objects, commands);
+
+        let (_, object, transform) = objects.get(entity).unwrap();
+        let EditorObject::WorldObject(world_object) = object else {
+            return;
+        };
+        let object_and_transform =
+            object_and_transform(world_object, transform, custom_colors.get(entity).ok());
+        let z_index = objects
+            .iter()
+            .map(|(_, _, transform)| transform.translation.z)
+            .reduce(f32::max)
+            .unwrap()
+            + 1.0; // We can unwrap as player will always be there.
+
+        let mut next_teleporter_id = unused_teleporter_id(objects);
+        let copies = (1..=self.repeat.count).map(|index| {
+            let [x, y, _] = object_and_transform.position;
+            let offset = self.repeat.offset * index as f32;
+            let mut copy = ObjectAndTransform {
+                position: [x + offset.x, y + offset.y, z_index + (index - 1) as f32],
+                ..object_and_transform.clone()
+            };
+            reassign_teleporter_ids(std::slice::from_mut(&mut copy), &mut next_teleporter_id);
+            create_world_object(&copy, commands, meshes, materials)
         });
+        self.group = GroupSelection::new(std::iter::once(entity).chain(copies).collect());
     }
 
     fn select<'a>(
@@ -554,6 +1919,9 @@ impl EditorUiState {
                 materials,
             ),
             prev_z_index: transform.translation.z,
+            selected_keyframe: 0,
+            placing_vertices: false,
+            connecting: None,
         });
         transform.translation.z = selection_z_index;
         self.selected.as_mut().unwrap()
@@ -619,7 +1987,7 @@ impl EditorUiState {
                     dragging: RectDrag::None(transform.translation.truncate()),
                 }
             }
-            EditorObject::Player => TransformEditors::None {
+            EditorObject::Player(_) | EditorObject::OtherPlayer(_) => TransformEditors::None {
                 initial_translation: transform.translation.truncate(),
             },
         }
@@ -639,6 +2007,20 @@ impl EditorUiState {
         meshes: &mut ResMut<Assets<Mesh>>,
         materials: &mut ResMut<Assets<ColorMaterial>>,
     ) {
+        if let Some(group) = &mut self.group {
+            if object_at(pointer_position, objects)
+                .map_or(false, |entity| group.entities.contains(&entity))
+            {
+                group.drag_start(objects);
+                self.drag = Some(DragState {
+                    initial_pointer_offset: pointer_offset_from_center,
+                    initial_camera_translation: camera_transform.translation.truncate(),
+                });
+                return;
+            }
+            self.group = None;
+        }
+
         // First check selected.
         if let Some(selected_state) = &mut self.selected {
             if selected_state.can_drag(pointer_position, objects, transform_editors) {
@@ -658,23 +2040,7 @@ impl EditorUiState {
             }
         }
 
-        let mut drag_entity = None;
-        let mut max_drag_z_index: Option<f32> = None;
-
-        for (entity, object, transform) in objects.iter() {
-            if let Some(max_drag_z_index) = max_drag_z_index {
-                if transform.translation.z <= max_drag_z_index {
-                    continue;
-                }
-            }
-
-            if object.can_drag(transform, pointer_position) {
-                max_drag_z_index = Some(transform.translation.z);
-                drag_entity = Some(entity);
-            }
-        }
-
-        if let Some(drag_entity) = drag_entity {
+        if let Some(drag_entity) = object_at(pointer_position, objects) {
             let selected_state = self.select(
                 drag_entity,
                 camera_transform.scale.x,
@@ -705,7 +2071,11 @@ impl EditorUiState {
             (Without<EditorObject>, Without<Camera>),
         >,
         camera_transform: &mut Transform,
+        shift_pressed: bool,
+        snap_to_objects: bool,
     ) {
+        let snap_rotation = self.snap_rotation || shift_pressed;
+        let lock_aspect_ratio = self.lock_aspect_ratio || shift_pressed;
         if let Some(DragState {
             initial_pointer_offset,
             initial_camera_translation,
@@ -717,7 +2087,33 @@ impl EditorUiState {
                     transform_editors,
                     initial_camera_translation + initial_pointer_offset,
                     initial_camera_translation + pointer_offset_from_center,
+                    snap_rotation,
+                    lock_aspect_ratio,
+                );
+                if snap_to_objects && selected_state.is_moving() {
+                    self.guides = snap_to_objects(
+                        &[selected_state.entity],
+                        objects,
+                        SNAP_DISTANCE * camera_transform.scale.x,
+                    );
+                    let (_, _, transform) = objects.get(selected_state.entity).unwrap();
+                    selected_state
+                        .transform_editors
+                        .update_transform(transform, transform_editors);
+                }
+            } else if let Some(group) = &self.group {
+                group.drag(
+                    objects,
+                    initial_camera_translation + initial_pointer_offset,
+                    initial_camera_translation + pointer_offset_from_center,
                 );
+                if snap_to_objects {
+                    self.guides = snap_to_objects(
+                        &group.entities,
+                        objects,
+                        SNAP_DISTANCE * camera_transform.scale.x,
+                    );
+                }
             } else {
                 // Camera will dragged in the opposite direction,
                 // this makes it appear as if the world is dragged in the correct direction.
@@ -731,19 +2127,308 @@ impl EditorUiState {
 
     fn drag_end(&mut self) {
         self.drag = None;
+        self.guides.clear();
     }
 }
 
-fn setup_editor(
-    mut commands: Commands,
-    world: Res<World>,
-    mut camera: Query<&mut Transform, With<Camera>>,
-    mut ui_state: ResMut<EditorUiState>,
-    mut meshes: ResMut<Assets<Mesh>>,
-    mut materials: ResMut<Assets<ColorMaterial>>,
-) {
-    EditorObject::Player.create_entity(
-        Transform::from_translation(Vec3::new(
+// Moves the dragged objects so the left, center or right, and the bottom, center or top of their extent
+// line up with the nearest ones of another object closer than `max_distance`.
+// Returns guide lines through the aligned objects.
+fn snap_to_objects(
+    dragged: &[Entity],
+    objects: &mut Query<(Entity, &mut EditorObject, &mut Transform)>,
+    max_distance: f32,
+) -> Vec<(Vec2, Vec2)> {
+    // The minimum, center and maximum along each axis.
+    let lines = |(min, max): (Vec2, Vec2)| [min, (min + max) / 2.0, max];
+    let Some(extent) = dragged
+        .iter()
+        .filter_map(|entity| objects.get(*entity).ok())
+        .map(|(_, object, transform)| object.extent(transform))
+        .reduce(|(min, max), (other_min, other_max)| (min.min(other_min), max.max(other_max)))
+    else {
+        return vec![];
+    };
+    let others: Vec<_> = objects
+        .iter()
+        .filter(|(entity, _, _)| !dragged.contains(entity))
+        .map(|(_, object, transform)| object.extent(transform))
+        .collect();
+
+    let mut offset = Vec2::ZERO;
+    for axis in 0..2 {
+        let nearest = others
+            .iter()
+            .flat_map(|other| lines(*other))
+            .flat_map(|other| lines(extent).map(move |line| other[axis] - line[axis]))
+            .filter(|difference| difference.abs() < max_distance)
+            .min_by(|a, b| a.abs().total_cmp(&b.abs()));
+        if let Some(difference) = nearest {
+            offset[axis] = difference;
+        }
+    }
+    if offset == Vec2::ZERO {
+        return vec![];
+    }
+    for entity in dragged {
+        if let Ok((_, _, mut transform)) = objects.get_mut(*entity) {
+            transform.translation += offset.extend(0.0);
+        }
+    }
+
+    let extent = (extent.0 + offset, extent.1 + offset);
+    let mut guides = vec![];
+    for other in &others {
+        // Only the axes which snapped have guides.
+        for axis in (0..2).filter(|axis| offset[*axis] != 0.0) {
+            for line in lines(*other) {
+                if lines(extent)
+                    .iter()
+                    .any(|dragged_line| (dragged_line[axis] - line[axis]).abs() < 1e-3)
+                {
+                    // The guide spans both objects along the other axis.
+                    let other_axis = 1 - axis;
+                    let mut start = line;
+                    let mut end = line;
+                    start[other_axis] = extent.0[other_axis].min(other.0[other_axis]);
+                    end[other_axis] = extent.1[other_axis].max(other.1[other_axis]);
+                    guides.push((start, end));
+                }
+            }
+        }
+    }
+    guides
+}
+
+// Which side of the objects is swapped by a flip.
+#[derive(Clone, Copy)]
+enum FlipAxis {
+    // Swaps left and right.
+    Horizontal,
+    // Swaps top and bottom.
+    Vertical,
+}
+
+impl FlipAxis {
+    const ALL: [FlipAxis; 2] = [FlipAxis::Horizontal, FlipAxis::Vertical];
+
+    fn name(&self) -> &'static str {
+        match self {
+            FlipAxis::Horizontal => "Horizontal",
+            FlipAxis::Vertical => "Vertical",
+        }
+    }
+
+    // The index of the coordinate which is negated.
+    fn index(&self) -> usize {
+        match self {
+            FlipAxis::Horizontal => 0,
+            FlipAxis::Vertical => 1,
+        }
+    }
+}
+
+// Mirrors the object about its position. Mirroring a rotated rectangle negates its rotation,
+// so the shape of polygons, the offsets of waypoints and keyframes, and directions along the object are mirrored too.
+fn flip_world_object(world_object: &mut WorldObject, transform: &mut Transform, axis: FlipAxis) {
+    let index = axis.index();
+    transform.rotation = transform.rotation.inverse();
+    match world_object {
+        WorldObject::Polygon { points } => {
+            for point in points {
+                point[index] *= -1.0;
+            }
+        }
+        WorldObject::MovingPlatform { waypoints, .. } | WorldObject::Saw { waypoints, .. } => {
+            for waypoint in waypoints {
+                waypoint[index] *= -1.0;
+            }
+        }
+        WorldObject::Enemy { patrol, .. } => patrol[index] *= -1.0,
+        WorldObject::Block {
+            animation: Some(animation),
+            ..
+        } => {
+            for keyframe in &mut animation.keyframes {
+                keyframe.offset[index] *= -1.0;
+                keyframe.rotation *= -1.0;
+            }
+        }
+        // The x axis of the conveyor is reversed by a horizontal flip.
+        WorldObject::Conveyor { speed } => {
+            if let FlipAxis::Horizontal = axis {
+                *speed *= -1.0;
+            }
+        }
+        _ => {}
+    }
+}
+
+// A change of the order the world objects are drawn in.
+#[derive(Clone, Copy)]
+enum ZOrderChange {
+    ToBack,
+    Backward,
+    Forward,
+    ToFront,
+}
+
+impl ZOrderChange {
+    fn name(&self) -> &'static str {
+        match self {
+            ZOrderChange::ToBack => "To back",
+            ZOrderChange::Backward => "Backward",
+            ZOrderChange::Forward => "Forward",
+            ZOrderChange::ToFront => "To front",
+        }
+    }
+}
+
+// Moves the selected object in the drawing order. The depths of the world objects are renumbered from 1 in order,
+// so the order is saved in their positions.
+fn change_z_order(
+    change: ZOrderChange,
+    selected: &mut SelectedState,
+    objects: &mut Query<(Entity, &mut EditorObject, &mut Transform)>,
+) {
+    // The selected object is drawn in front while selected, its depth is restored afterwards.
+    let mut depths: Vec<_> = objects
+        .iter()
+        .filter(|(_, object, _)| matches!(object, EditorObject::WorldObject(_)))
+        .map(|(entity, _, transform)| {
+            let depth = if entity == selected.entity {
+                selected.prev_z_index
+            } else {
+                transform.translation.z
+            };
+            (entity, depth)
+        })
+        .collect();
+    depths.sort_by(|(_, depth), (_, other_depth)| depth.total_cmp(other_depth));
+    let mut order: Vec<_> = depths.into_iter().map(|(entity, _)| entity).collect();
+
+    let Some(index) = order.iter().position(|entity| *entity == selected.entity) else {
+        return;
+    };
+    let entity = order.remove(index);
+    let new_index = match change {
+        ZOrderChange::ToBack => 0,
+        ZOrderChange::Backward => index.saturating_sub(1),
+        ZOrderChange::Forward => (index + 1).min(order.len()),
+        ZOrderChange::ToFront => order.len(),
+    };
+    order.insert(new_index, entity);
+
+    for (index, entity) in order.into_iter().enumerate() {
+        let depth = index as f32 + 1.0;
+        if entity == selected.entity {
+            selected.prev_z_index = depth;
+        } else {
+            objects.get_mut(entity).unwrap().2.translation.z = depth;
+        }
+    }
+}
+
+// Saves the world with the objects and joints in the editor, and remembers the file for Ctrl+S and the recent worlds.
+fn save_world(
+    world: &World,
+    path: PathBuf,
+    objects: &Query<(Entity, &mut EditorObject, &mut Transform)>,
+    custom_colors: &Query<&CustomColor>,
+    joints: &EditorJoints,
+    ui_state: &mut EditorUiState,
+    editor_files: &mut EditorFiles,
+) -> Result<()> {
+    let world = editor_world(
+        world,
+        objects,
+        custom_colors,
+        ui_state.selected.as_ref(),
+        joints,
+    );
+    world.save(&path)?;
+    editor_files.add_recent(path.clone());
+    ui_state.save_path_modified = modified_time(&path);
+    ui_state.save_path = Some(path);
+    ui_state.saved_revision = SavedRevision::Pending;
+    Ok(())
+}
+
+// The world being edited, with the settings of `world` and the objects in the editor.
+fn editor_world(
+    world: &World,
+    objects: &Query<(Entity, &mut EditorObject, &mut Transform)>,
+    custom_colors: &Query<&CustomColor>,
+    selected: Option<&SelectedState>,
+    joints: &EditorJoints,
+) -> World {
+    let mut world = World {
+        objects: vec![],
+        other_players: vec![],
+        ..world.clone()
+    };
+    let mut indices = HashMap::new();
+    for (entity, object, transform) in objects.iter() {
+        match object {
+            EditorObject::Player(_) => {
+                world.player_position[0] = transform.translation.x;
+                world.player_position[1] = transform.translation.y;
+            }
+            EditorObject::OtherPlayer(_) => {
+                world
+                    .other_players
+                    .push(transform.translation.truncate().to_array());
+            }
+            EditorObject::WorldObject(object) => {
+                let mut object_and_transform =
+                    object_and_transform(object, transform, custom_colors.get(entity).ok());
+                // The selected object is drawn in front of the others until it's deselected.
+                if let Some(selected) = selected.filter(|selected| selected.entity == entity) {
+                    object_and_transform.position[2] = selected.prev_z_index;
+                }
+                indices.insert(entity, world.objects.len());
+                world.objects.push(object_and_transform);
+            }
+        }
+    }
+    world.joints = joints.to_world(&indices);
+    world
+}
+
+// The topmost object under the pointer.
+fn object_at(
+    pointer_position: Vec2,
+    objects: &Query<(Entity, &mut EditorObject, &mut Transform)>,
+) -> Option<Entity> {
+    let mut drag_entity = None;
+    let mut max_drag_z_index: Option<f32> = None;
+
+    for (entity, object, transform) in objects.iter() {
+        if let Some(max_drag_z_index) = max_drag_z_index {
+            if transform.translation.z <= max_drag_z_index {
+                continue;
+            }
+        }
+
+        if object.can_drag(transform, pointer_position) {
+            max_drag_z_index = Some(transform.translation.z);
+            drag_entity = Some(entity);
+        }
+    }
+    drag_entity
+}
+
+fn setup_editor(
+    mut commands: Commands,
+    world: Res<World>,
+    mut camera: Query<&mut Transform, With<Camera>>,
+    mut ui_state: ResMut<EditorUiState>,
+    mut joints: ResMut<EditorJoints>,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<ColorMaterial>>,
+) {
+    let player = EditorObject::Player(world.player_shape).create_entity(
+        Transform::from_translation(Vec3::new(
             world.player_position[0],
             world.player_position[1],
             0.0,
@@ -752,54 +2437,279 @@ fn setup_editor(
         &mut meshes,
         &mut materials,
     );
+    let other_players: Vec<_> = world
+        .other_players
+        .iter()
+        .map(|position| {
+            EditorObject::OtherPlayer(world.player_shape).create_entity(
+                Transform::from_translation(Vec3::new(position[0], position[1], 0.0)),
+                &mut commands,
+                &mut meshes,
+                &mut materials,
+            )
+        })
+        .collect();
 
-    for object_and_transform in world.objects.iter() {
-        EditorObject::WorldObject(object_and_transform.object.clone()).create_entity(
-            object_and_transform.transform(),
-            &mut commands,
-            &mut meshes,
-            &mut materials,
-        );
-    }
+    let entities: Vec<_> = world
+        .objects
+        .iter()
+        .map(|object_and_transform| {
+            create_world_object(
+                object_and_transform,
+                &mut commands,
+                &mut meshes,
+                &mut materials,
+            )
+        })
+        .collect();
+    *joints = EditorJoints::from_world(&world, &entities);
     let mut camera_transform = camera.iter_mut().next().unwrap();
-    camera_transform.translation.x = 0.0;
-    camera_transform.translation.y = 0.0;
-    *ui_state = EditorUiState::default();
+    let restored_selection = match ui_state.test_view.take() {
+        Some(test_view) => {
+            camera_transform.translation.x = test_view.camera_translation.x;
+            camera_transform.translation.y = test_view.camera_translation.y;
+            camera_transform.scale.x = test_view.camera_scale;
+            camera_transform.scale.y = test_view.camera_scale;
+            test_view
+                .selection
+                .into_iter()
+                .map(|index| match index {
+                    WorldIndex::Player => player,
+                    WorldIndex::OtherPlayer(index) => other_players[index],
+                    WorldIndex::Object(index) => entities[index],
+                })
+                .collect()
+        }
+        None => {
+            camera_transform.translation.x = 0.0;
+            camera_transform.translation.y = 0.0;
+            vec![]
+        }
+    };
+    // The world is the same one as before playing or training.
+    *ui_state = EditorUiState {
+        snap_rotation: ui_state.snap_rotation,
+        lock_aspect_ratio: ui_state.lock_aspect_ratio,
+        jitter: ui_state.jitter,
+        repeat: ui_state.repeat,
+        save_path: ui_state.save_path.take(),
+        save_path_modified: ui_state.save_path_modified,
+        file_changed: ui_state.file_changed,
+        // The world's entities are spawned again, so its revision changes.
+        saved_revision: SavedRevision::respawned(ui_state.unsaved_changes),
+        unsaved_changes: ui_state.unsaved_changes,
+        pending_action: ui_state.pending_action.take(),
+        restored_selection,
+        ..default()
+    };
 }
 
 fn cleanup_editor(
     mut commands: Commands,
     mut world: ResMut<World>,
     mut ui_state: ResMut<EditorUiState>,
+    joints: Res<EditorJoints>,
     mut objects: Query<(Entity, &mut EditorObject, &mut Transform)>,
     mut camera: Query<&mut Transform, (With<Camera>, Without<EditorObject>)>,
+    bounds_outline: Query<Entity, With<BoundsOutline>>,
+    reference_image: Query<Entity, With<ReferenceImageSprite>>,
+    grid: Query<Entity, With<GridLines>>,
+    custom_colors: Query<&CustomColor>,
 ) {
+    let selection = ui_state.selected_entities();
     ui_state.clear_selection(&mut objects, &mut commands);
+    for entity in bounds_outline.iter().chain(&reference_image).chain(&grid) {
+        commands.entity(entity).despawn();
+    }
 
     world.objects.clear();
+    world.other_players.clear();
+    let mut indices = HashMap::new();
+    let mut selection_indices = vec![];
     for (entity, object, transform) in objects.iter() {
-        match object {
-            EditorObject::Player => {
+        let index = match object {
+            EditorObject::Player(_) => {
                 world.player_position[0] = transform.translation.x;
                 world.player_position[1] = transform.translation.y;
+                WorldIndex::Player
+            }
+            EditorObject::OtherPlayer(_) => {
+                world
+                    .other_players
+                    .push(transform.translation.truncate().to_array());
+                WorldIndex::OtherPlayer(world.other_players.len() - 1)
             }
             EditorObject::WorldObject(object) => {
-                world.objects.push(ObjectAndTransform {
-                    object: object.clone(),
-                    position: transform.translation.to_array(),
-                    scale: transform.scale.truncate().to_array(),
-                    rotation: transform.rotation.to_euler(EulerRot::XYZ).2,
-                });
+                indices.insert(entity, world.objects.len());
+                world.objects.push(object_and_transform(
+                    object,
+                    transform,
+                    custom_colors.get(entity).ok(),
+                ));
+                WorldIndex::Object(world.objects.len() - 1)
             }
+        };
+        if selection.contains(&entity) {
+            selection_indices.push(index);
         }
-        commands.entity(entity).despawn();
+        commands.entity(entity).despawn_recursive();
     }
+    world.joints = joints.to_world(&indices);
 
     let mut camera_transform = camera.iter_mut().next().unwrap();
+    if ui_state.testing {
+        ui_state.test_view = Some(TestView {
+            camera_translation: camera_transform.translation.truncate(),
+            camera_scale: camera_transform.scale.x,
+            selection: selection_indices,
+        });
+    }
     camera_transform.scale.x = 1.0;
     camera_transform.scale.y = 1.0;
 }
 
+// Saves the worlds of all the tabs every AUTOSAVE_INTERVAL seconds, so they can be recovered if the app crashes.
+fn autosave_world(
+    time: Res<Time>,
+    mut editor_files: ResMut<EditorFiles>,
+    tabs: Res<EditorTabs>,
+    world: Res<World>,
+    ui_state: Res<EditorUiState>,
+    joints: Res<EditorJoints>,
+    objects: Query<(Entity, &mut EditorObject, &mut Transform)>,
+    custom_colors: Query<&CustomColor>,
+) {
+    if editor_files
+        .autosave_timer
+        .tick(time.delta())
+        .just_finished()
+    {
+        for (index, tab) in tabs.tabs.iter().enumerate() {
+            let result = match tab {
+                Some(tab) => tab.world.save(autosave_path(index)),
+                None => editor_world(
+                    &world,
+                    &objects,
+                    &custom_colors,
+                    ui_state.selected.as_ref(),
+                    &joints,
+                )
+                .save(autosave_path(index)),
+            };
+            if let Err(error) = result {
+                println!("Couldn't autosave the world of tab {}: {error}.", index + 1);
+            }
+        }
+        // The autosaves of closed tabs.
+        remove_autosaves(tabs.tabs.len());
+    }
+}
+
+// Checks whether the world file was changed by another program, like a script generating levels,
+// to offer reloading it.
+fn watch_world_file(
+    time: Res<Time>,
+    mut editor_files: ResMut<EditorFiles>,
+    mut ui_state: ResMut<EditorUiState>,
+) {
+    if !editor_files.watch_timer.tick(time.delta()).just_finished() {
+        return;
+    }
+    let Some(path) = &ui_state.save_path else {
+        return;
+    };
+    let modified = modified_time(path);
+    if modified.is_some() && modified != ui_state.save_path_modified {
+        ui_state.save_path_modified = modified;
+        ui_state.file_changed = true;
+    }
+}
+
+// Loads the imported reference image, and keeps its sprite up to date with its settings.
+fn update_reference_image(
+    mut commands: Commands,
+    mut reference_image: ResMut<ReferenceImage>,
+    mut images: ResMut<Assets<Image>>,
+    mut sprites: Query<
+        (Entity, &mut Transform, &mut Sprite, &mut Handle<Image>),
+        With<ReferenceImageSprite>,
+    >,
+) {
+    if let Some(path) = reference_image.path.take() {
+        let image = fs::read(&path)
+            .map_err(|error| error.to_string())
+            .and_then(|bytes| {
+                Image::from_buffer(
+                    &bytes,
+                    ImageType::Extension("png"),
+                    CompressedImageFormats::NONE,
+                    true,
+                )
+                .map_err(|error| error.to_string())
+            });
+        match image {
+            Ok(image) => reference_image.image = Some(images.add(image)),
+            Err(error) => {
+                // TODO: Show error in the UI.
+                println!("Couldn't open the reference image: {error}.");
+            }
+        }
+    }
+
+    let transform = Transform::from_translation(reference_image.offset.extend(REFERENCE_IMAGE_Z))
+        .with_scale(Vec3::new(reference_image.scale, reference_image.scale, 1.0));
+    let color = Color::rgba(1.0, 1.0, 1.0, reference_image.opacity);
+    match (&reference_image.image, sprites.get_single_mut()) {
+        (Some(image), Ok((_, mut sprite_transform, mut sprite, mut handle))) => {
+            *sprite_transform = transform;
+            sprite.color = color;
+            if *handle != *image {
+                *handle = image.clone();
+            }
+        }
+        (Some(image), Err(_)) => {
+            commands
+                .spawn(SpriteBundle {
+                    sprite: Sprite { color, ..default() },
+                    texture: image.clone(),
+                    transform,
+                    ..default()
+                })
+                .insert(ReferenceImageSprite);
+        }
+        (None, Ok((entity, ..))) => commands.entity(entity).despawn(),
+        (None, Err(_)) => {}
+    }
+}
+
+// Asks to save the unsaved changes before closing the window, in the editor.
+fn confirm_close(
+    mut commands: Commands,
+    mut close_requests: EventReader<WindowCloseRequested>,
+    mut ui_state: ResMut<EditorUiState>,
+    tabs: Res<EditorTabs>,
+    state: Res<State<AppState>>,
+    mut next_state: ResMut<NextState<AppState>>,
+) {
+    for request in close_requests.iter() {
+        if ui_state.unsaved_changes || tabs.unsaved_changes_in_other_tabs() {
+            ui_state.pending_action = Some(PendingAction::Quit);
+            if state.0 != AppState::Editor {
+                next_state.set(AppState::Editor);
+            }
+        } else {
+            commands.entity(request.window).despawn();
+        }
+    }
+}
+
+// Autosaves left when the app exits mean it crashed.
+fn remove_autosave(exit_events: EventReader<AppExit>) {
+    if !exit_events.is_empty() {
+        remove_autosaves(0);
+    }
+}
+
 fn load_world(
     world: &ResMut<World>,
     commands: &mut Commands,
@@ -810,6 +2720,7 @@ fn load_world(
     >,
     camera: &mut Transform,
     ui_state: &mut ResMut<EditorUiState>,
+    joints: &mut ResMut<EditorJoints>,
     meshes: &mut ResMut<Assets<Mesh>>,
     materials: &mut ResMut<Assets<ColorMaterial>>,
 ) {
@@ -818,10 +2729,10 @@ fn load_world(
     }
 
     for (entity, _, _) in objects.iter() {
-        commands.entity(entity).despawn();
+        commands.entity(entity).despawn_recursive();
     }
 
-    EditorObject::Player.create_entity(
+    EditorObject::Player(world.player_shape).create_entity(
         Transform::from_translation(Vec3::new(
             world.player_position[0],
             world.player_position[1],
@@ -831,29 +2742,114 @@ fn load_world(
         meshes,
         materials,
     );
-    for object_and_transform in world.objects.iter() {
-        EditorObject::WorldObject(object_and_transform.object.clone()).create_entity(
-            object_and_transform.transform(),
+    for position in &world.other_players {
+        EditorObject::OtherPlayer(world.player_shape).create_entity(
+            Transform::from_translation(Vec3::new(position[0], position[1], 0.0)),
             commands,
             meshes,
             materials,
         );
     }
+    let entities: Vec<_> = world
+        .objects
+        .iter()
+        .map(|object_and_transform| {
+            create_world_object(object_and_transform, commands, meshes, materials)
+        })
+        .collect();
+    **joints = EditorJoints::from_world(world, &entities);
     camera.translation.x = 0.0;
     camera.translation.y = 0.0;
     **ui_state = EditorUiState::default();
 }
 
+// Counts the changes made to the edited world, so unsaved changes are found without serializing the world.
+#[derive(Default, Resource)]
+struct EditorRevision {
+    revision: u64,
+    // The objects, the world's settings and the joints as of the latest revision. Bevy marks them as changed
+    // whenever they're borrowed mutably, so they're compared with these to only count actual changes.
+    objects: HashMap<Entity, (EditorObject, Transform, Option<CustomColor>)>,
+    settings: Option<World>,
+    joints: Vec<EditorJoint>,
+}
+
+// Increments the revision when the objects, the world's settings or the joints changed since the last frame.
+fn update_revision(
+    mut revision: ResMut<EditorRevision>,
+    mut world: ResMut<World>,
+    joints: Res<EditorJoints>,
+    ui_state: Res<EditorUiState>,
+    changed_objects: Query<
+        Entity,
+        (
+            With<EditorObject>,
+            Or<(
+                Changed<EditorObject>,
+                Changed<Transform>,
+                Changed<CustomColor>,
+            )>,
+        ),
+    >,
+    objects: Query<(&EditorObject, &Transform, Option<&CustomColor>)>,
+    mut removed_objects: RemovedComponents<EditorObject>,
+    mut removed_colors: RemovedComponents<CustomColor>,
+) {
+    let revision = &mut *revision;
+    let mut changed = false;
+    for entity in removed_objects.iter() {
+        changed |= revision.objects.remove(&entity).is_some();
+    }
+    for entity in changed_objects.iter().chain(removed_colors.iter()) {
+        let Ok((object, transform, custom_color)) = objects.get(entity) else {
+            continue;
+        };
+        let mut transform = *transform;
+        // The selected object is moved in front of the others, which isn't a change to the world.
+        if let Some(selected) = &ui_state.selected {
+            if selected.entity == entity {
+                transform.translation.z = selected.prev_z_index;
+            }
+        }
+        let state = (object.clone(), transform, custom_color.copied());
+        if revision.objects.get(&entity) != Some(&state) {
+            revision.objects.insert(entity, state);
+            changed = true;
+        }
+    }
+    if world.is_changed() {
+        // The objects are edited as entities, so the world's copy of them is out of date and left out.
+        let world = world.bypass_change_detection();
+        let world_objects = std::mem::take(&mut world.objects);
+        if revision.settings.as_ref() != Some(&*world) {
+            revision.settings = Some(world.clone());
+            changed = true;
+        }
+        world.objects = world_objects;
+    }
+    if joints.is_changed() && revision.joints != joints.0 {
+        revision.joints = joints.0.clone();
+        changed = true;
+    }
+    if changed {
+        revision.revision += 1;
+    }
+}
+
 fn editor_ui_system(
-    mut next_state: ResMut<NextState<AppState>>,
+    (mut next_state, mut app_exit_events): (ResMut<NextState<AppState>>, EventWriter<AppExit>),
     mut commands: Commands,
     mut contexts: EguiContexts,
     mut ui_state: ResMut<EditorUiState>,
-    mouse_button_input: Res<Input<MouseButton>>,
+    (mouse_button_input, keyboard_input): (Res<Input<MouseButton>>, Res<Input<KeyCode>>),
     mut world: ResMut<World>,
     mut camera: Query<&mut Transform, (With<Camera>, Without<EditorObject>)>,
     mut objects: Query<(Entity, &mut EditorObject, &mut Transform)>,
-    mut current_materials: Query<&mut Handle<ColorMaterial>>,
+    (mut current_materials, custom_colors): (
+        Query<&mut Handle<ColorMaterial>>,
+        Query<&CustomColor>,
+    ),
+    mut current_meshes: Query<&mut Mesh2dHandle>,
     mut meshes: ResMut<Assets<Mesh>>,
     mut materials: ResMut<Assets<ColorMaterial>>,
     mut transform_editors: Query<
@@ -861,46 +2857,442 @@ fn editor_ui_system(
         (Without<EditorObject>, Without<Camera>),
     >,
     mut mouse_wheel_events: EventReader<MouseWheel>,
+    (mut template_settings, mut prefab_library, mut editor_files, mut reference_image, mut tabs): (
+        ResMut<TemplateSettings>,
+        ResMut<PrefabLibrary>,
+        ResMut<EditorFiles>,
+        ResMut<ReferenceImage>,
+        ResMut<EditorTabs>,
+    ),
+    (mut joints, revision): (ResMut<EditorJoints>, Res<EditorRevision>),
 ) {
     let mut camera_transform = camera.iter_mut().next().unwrap();
 
-    let response = egui::Window::new("World editor")
+    // Reselects the objects which were selected before the play-test.
+    let restored_selection = std::mem::take(&mut ui_state.restored_selection);
+    match restored_selection.len() {
+        0 => {}
+        1 => {
+            ui_state.select(
+                restored_selection[0],
+                camera_transform.scale.x,
+                &mut objects,
+                &mut commands,
+                &mut meshes,
+                &mut materials,
+            );
+        }
+        _ => ui_state.group = GroupSelection::new(restored_selection),
+    }
+
+    if ui_state.saved_revision == SavedRevision::Pending {
+        ui_state.saved_revision = SavedRevision::Saved(revision.revision);
+    }
+    let unsaved_changes = ui_state.saved_revision != SavedRevision::Saved(revision.revision);
+    ui_state.unsaved_changes = unsaved_changes;
+
+    let shift_pressed = keyboard_input.any_pressed([KeyCode::LShift, KeyCode::RShift]);
+    // Dragging snaps to the other objects, unless alt is held.
+    let alt_pressed = keyboard_input.any_pressed([KeyCode::LAlt, KeyCode::RAlt]);
+    // Keyboard shortcuts, unless a text field is being edited.
+    if !contexts.ctx_mut().wants_keyboard_input() {
+        if keyboard_input.just_pressed(KeyCode::Escape) {
+            ui_state.drag_end();
+            ui_state.clear_selection(&mut objects, &mut commands);
+            ui_state.placement = None;
+            ui_state.brush = None;
+            ui_state.measure = None;
+        }
+        if keyboard_input.just_pressed(KeyCode::Delete) {
+            ui_state.delete_selection(&mut objects, &mut commands);
+        }
+
+        let nudge: Vec2 = [
+            (KeyCode::Left, Vec2::NEG_X),
+            (KeyCode::Right, Vec2::X),
+            (KeyCode::Up, Vec2::Y),
+            (KeyCode::Down, Vec2::NEG_Y),
+        ]
+        .into_iter()
+        .filter(|(key, _)| keyboard_input.just_pressed(*key))
+        .map(|(_, direction)| direction)
+        .sum();
+        if nudge != Vec2::ZERO {
+            let step = if shift_pressed { 10.0 } else { 1.0 };
+            ui_state.nudge_selection(nudge * step, &mut objects, &mut transform_editors);
+        }
+
+        let control_pressed = keyboard_input.any_pressed([KeyCode::LControl, KeyCode::RControl]);
+        if control_pressed && keyboard_input.just_pressed(KeyCode::C) {
+            // Players aren't copied, like when saving prefabs.
+            let copied: Vec<_> = ui_state
+                .selected_entities()
+                .into_iter()
+                .filter_map(|entity| match objects.get(entity) {
+                    Ok((_, EditorObject::WorldObject(object), transform)) => Some(
+                        object_and_transform(object, transform, custom_colors.get(entity).ok()),
+                    ),
+                    _ => None,
+                })
+                .collect();
+            if !copied.is_empty() {
+                prefab_library.clipboard = Some(Prefab::new("Clipboard".to_string(), copied));
+            }
+        }
+        if control_pressed && keyboard_input.just_pressed(KeyCode::V) {
+            if let Some(clipboard) = &prefab_library.clipboard {
+                let position = ui_state
+                    .pointer_position
+                    .unwrap_or(camera_transform.translation.truncate());
+                ui_state.insert_prefab(
+                    clipboard,
+                    position,
+                    &mut objects,
+                    &mut commands,
+                    &mut meshes,
+                    &mut materials,
+                );
+            }
+        }
+
+        if control_pressed && keyboard_input.just_pressed(KeyCode::S) {
+            let path = ui_state
+                .save_path
+                .clone()
+                .or_else(|| world_file_dialog().save_file());
+            if let Some(path) = path {
+                if let Err(error) = save_world(
+                    &world,
+                    path,
+                    &objects,
+                    &custom_colors,
+                    &joints,
+                    &mut ui_state,
+                    &mut editor_files,
+                ) {
+                    // TODO: Show error in the UI.
+                    println!("Couldn't save the world: {error}.");
+                }
+            }
+        }
+    }
+
+    let mut fit_view_clicked = false;
+    let title = if unsaved_changes {
+        "World editor *"
+    } else {
+        "World editor"
+    };
+    let response = egui::Window::new(title)
+        // The title changes, so it can't identify the window.
+        .id(egui::Id::new("World editor"))
         .scroll2([false, true])
         .show(contexts.ctx_mut(), |ui| {
             let mut new_state = None;
             let mut new_world_clicked = false;
+            // The pending action, once the unsaved changes are saved or discarded.
+            let mut confirmed_action = None;
 
-            ui.horizontal(|ui| {
-                if ui.button("Play world").clicked() {
-                    new_state = Some(AppState::Game);
+            let mut open_tab = None;
+            ui.horizontal_wrapped(|ui| {
+                for (index, tab) in tabs.tabs.iter().enumerate() {
+                    let name = match tab {
+                        Some(tab) => EditorTab::name(tab.save_path.as_ref(), tab.unsaved_changes),
+                        None => EditorTab::name(ui_state.save_path.as_ref(), unsaved_changes),
+                    };
+                    if ui.selectable_label(index == tabs.active, name).clicked()
+                        && index != tabs.active
+                    {
+                        open_tab = Some(index);
+                    }
+                }
+                if ui.button("+").on_hover_text("New tab").clicked() {
+                    tabs.tabs.push(Some(EditorTab::new(World::default())));
+                    open_tab = Some(tabs.tabs.len() - 1);
+                }
+                if tabs.tabs.len() > 1
+                    && ui.button("Close tab").clicked()
+                    && ui_state.confirm(PendingAction::CloseTab)
+                {
+                    confirmed_action = Some(PendingAction::CloseTab);
                 }
+            });
+            ui.add_space(10.0);
 
-                let has_goal = objects.iter().any(|(_, object, _)| {
-                    matches!(object, EditorObject::WorldObject(WorldObject::Goal))
+            if let Some(action) = ui_state.pending_action.clone() {
+                if action != PendingAction::Quit || unsaved_changes {
+                    ui.label("The world has unsaved changes.");
+                }
+                if action == PendingAction::Quit && tabs.unsaved_changes_in_other_tabs() {
+                    ui.label("Worlds in other tabs have unsaved changes, which are lost when quitting.");
+                }
+                ui.horizontal(|ui| {
+                    if ui.button("Save").clicked() {
+                        let path = ui_state
+                            .save_path
+                            .clone()
+                            .or_else(|| world_file_dialog().save_file());
+                        if let Some(path) = path {
+                            // The action only runs once the changes are saved.
+                            match save_world(
+                                &world,
+                                path,
+                                &objects,
+                                &custom_colors,
+                                &joints,
+                                &mut ui_state,
+                                &mut editor_files,
+                            ) {
+                                Ok(()) => confirmed_action = Some(action.clone()),
+                                Err(error) => {
+                                    ui_state.save_error =
+                                        Some(format!("Couldn't save the world: {error}."));
+                                }
+                            }
+                        }
+                    }
+                    if ui.button("Don't save").clicked() {
+                        confirmed_action = Some(action.clone());
+                    }
+                    if ui.button("Cancel").clicked() {
+                        ui_state.pending_action = None;
+                        ui_state.save_error = None;
+                    }
                 });
-
-                if has_goal && ui.button("Train agent on world").clicked() {
-                    new_state = Some(AppState::Train);
+                if let Some(error) = &ui_state.save_error {
+                    ui.colored_label(egui::Color32::RED, error.as_str());
                 }
-
-                if ui.button("New world").clicked() {
-                    new_world_clicked = true;
+                if confirmed_action.is_some() {
+                    ui_state.pending_action = None;
+                    ui_state.save_error = None;
                 }
-            });
+                ui.add_space(10.0);
+            }
 
-            if let Some(state) = new_state {
-                next_state.set(state);
+            if confirmed_action == Some(PendingAction::Quit) {
+                app_exit_events.send(AppExit);
                 return;
             }
 
-            if new_world_clicked {
-                ui_state.drag_end();
-                ui_state.clear_selection(&mut objects, &mut commands);
-                for (entity, object, mut transform) in objects.iter_mut() {
-                    if let EditorObject::Player = &*object {
-                        *transform = Transform::default();
-                    } else {
-                        commands.entity(entity).despawn();
+            if let Some(index) = open_tab {
+                let active = EditorTab::active(
+                    editor_world(
+                        &world,
+                        &objects,
+                        &custom_colors,
+                        ui_state.selected.as_ref(),
+                        &joints,
+                    ),
+                    &camera_transform,
+                    &mut ui_state,
+                );
+                let active_index = tabs.active;
+                tabs.tabs[active_index] = Some(active);
+                tabs.active = index;
+                if let Some(tab) = tabs.tabs[index].take() {
+                    tab.open(
+                        &mut world,
+                        &mut commands,
+                        &objects,
+                        &transform_editors,
+                        &mut camera_transform,
+                        &mut ui_state,
+                        &mut joints,
+                        &mut meshes,
+                        &mut materials,
+                    );
+                }
+                return;
+            }
+            if confirmed_action == Some(PendingAction::CloseTab) {
+                // The active world is discarded, and the tab before it is opened, or the next one for the first tab.
+                let index = if tabs.active > 0 { tabs.active - 1 } else { 1 };
+                if let Some(tab) = tabs.tabs[index].take() {
+                    let active_index = tabs.active;
+                    tabs.tabs.remove(active_index);
+                    tabs.active = active_index.saturating_sub(1);
+                    tab.open(
+                        &mut world,
+                        &mut commands,
+                        &objects,
+                        &transform_editors,
+                        &mut camera_transform,
+                        &mut ui_state,
+                        &mut joints,
+                        &mut meshes,
+                        &mut materials,
+                    );
+                }
+                return;
+            }
+
+            if !ui_state.migrations.is_empty() {
+                ui.label("The world was saved in an older format, and was updated when opening it:");
+                for migration in &ui_state.migrations {
+                    ui.label(migration.to_string());
+                }
+                if ui.button("Dismiss").clicked() {
+                    ui_state.migrations.clear();
+                }
+                ui.add_space(10.0);
+            }
+
+            if ui_state.file_changed {
+                let mut reload_clicked = false;
+                ui.label("The world file was changed by another program.");
+                ui.horizontal(|ui| {
+                    reload_clicked = ui
+                        .button("Reload")
+                        .on_hover_text("Discards the changes made in the editor.")
+                        .clicked();
+                    if ui.button("Ignore").clicked() {
+                        ui_state.file_changed = false;
+                    }
+                });
+                if reload_clicked {
+                    confirmed_action = ui_state
+                        .save_path
+                        .clone()
+                        .map(|path| PendingAction::Open(Some(path)));
+                    ui_state.file_changed = false;
+                }
+                ui.add_space(10.0);
+            }
+
+            let mut recover_clicked = confirmed_action == Some(PendingAction::Recover);
+            if !editor_files.recovered.is_empty() {
+                ui.label(
+                    "The editor didn't exit normally, the worlds autosaved before can be recovered.",
+                );
+                ui.horizontal(|ui| {
+                    if ui.button("Recover worlds").clicked()
+                        && ui_state.confirm(PendingAction::Recover)
+                    {
+                        recover_clicked = true;
+                    }
+                    if ui.button("Discard").clicked() {
+                        editor_files.take_recovered();
+                    }
+                });
+                ui.add_space(10.0);
+            }
+            if recover_clicked {
+                let mut recovered = editor_files.take_recovered().into_iter();
+                // The first tab's world replaces the active world, the others open in new tabs.
+                if let Some(recovered_world) = recovered.next() {
+                    *world = recovered_world;
+                    load_world(
+                        &world,
+                        &mut commands,
+                        &objects,
+                        &transform_editors,
+                        &mut camera_transform,
+                        &mut ui_state,
+                        &mut joints,
+                        &mut meshes,
+                        &mut materials,
+                    );
+                    // The autosave isn't where the world came from, and the world isn't saved anywhere else.
+                    ui_state.save_path = None;
+                    ui_state.saved_revision = SavedRevision::Unsaved;
+                }
+                for recovered_world in recovered {
+                    let mut tab = EditorTab::new(recovered_world);
+                    tab.saved_revision = SavedRevision::Unsaved;
+                    tab.unsaved_changes = true;
+                    tabs.tabs.push(Some(tab));
+                }
+            }
+
+            ui.horizontal(|ui| {
+                if (ui.button("Play world").clicked() && ui_state.confirm(PendingAction::Play))
+                    || confirmed_action == Some(PendingAction::Play)
+                {
+                    new_state = Some(AppState::Game);
+                }
+
+                // Returns to the same view and selection, so levels can be tweaked between quick tests.
+                if ui.button("Test from here").clicked() {
+                    ui_state.testing = true;
+                    new_state = Some(AppState::Game);
+                }
+
+                if ui.button("Train agent on world").clicked() {
+                    let issues = editor_world(
+                        &world,
+                        &objects,
+                        &custom_colors,
+                        ui_state.selected.as_ref(),
+                        &joints,
+                    )
+                    .validate();
+                    // Training on a world which can't be won wastes the whole run.
+                    if !issues.iter().any(ValidationIssue::is_error) {
+                        new_state = Some(AppState::Train);
+                    }
+                    ui_state.validation = Some(issues);
+                }
+
+                if ui.button("Validate").clicked() {
+                    ui_state.validation = Some(
+                        editor_world(
+                            &world,
+                            &objects,
+                            &custom_colors,
+                            ui_state.selected.as_ref(),
+                            &joints,
+                        )
+                        .validate(),
+                    );
+                }
+
+                if (ui.button("New world").clicked() && ui_state.confirm(PendingAction::NewWorld))
+                    || confirmed_action == Some(PendingAction::NewWorld)
+                {
+                    new_world_clicked = true;
+                }
+
+                if ui.button("Fit view").clicked() {
+                    fit_view_clicked = true;
+                }
+            });
+
+            if let Some(issues) = &ui_state.validation {
+                if issues.is_empty() {
+                    ui.label("No issues found.");
+                }
+                for issue in issues {
+                    if issue.is_error() {
+                        ui.colored_label(egui::Color32::RED, format!("Error: {issue}"));
+                    } else {
+                        ui.colored_label(egui::Color32::YELLOW, format!("Warning: {issue}"));
+                    }
+                }
+            }
+
+            if let Some(state) = new_state {
+                next_state.set(state);
+                return;
+            }
+
+            if new_world_clicked {
+                *world = World::default();
+                joints.0.clear();
+                ui_state.drag_end();
+                ui_state.clear_selection(&mut objects, &mut commands);
+                ui_state.save_path = None;
+                ui_state.file_changed = false;
+                ui_state.validation = None;
+                ui_state.saved_revision = SavedRevision::Pending;
+                for (entity, mut object, mut transform) in objects.iter_mut() {
+                    if let EditorObject::Player(shape) = &mut *object {
+                        *transform = Transform::default();
+                        *shape = world.player_shape;
+                        if let Ok(mut mesh) = current_meshes.get_mut(entity) {
+                            *mesh = meshes.add(shape.mesh()).into();
+                        }
+                    } else {
+                        commands.entity(entity).despawn_recursive();
                     }
                 }
                 camera_transform.translation.x = 0.0;
@@ -913,13 +3305,43 @@ fn editor_ui_system(
             ui.add_space(10.0);
 
             ui.horizontal(|ui| {
-                if ui.button("Open").clicked() {
-                    if let Some(path) = rfd::FileDialog::new().pick_file() {
-                        let new_world: Option<World> = fs::read_to_string(path)
-                            .ok()
-                            .and_then(|s| serde_json::from_str(&s).ok());
+                let mut open_path = match &confirmed_action {
+                    Some(PendingAction::Open(path)) => path.clone(),
+                    _ => None,
+                };
+                if (ui.button("Open").clicked() && ui_state.confirm(PendingAction::Open(None)))
+                    || confirmed_action == Some(PendingAction::Open(None))
+                {
+                    open_path = world_file_dialog()
+                        .add_filter("Tiled map", &["tmx"])
+                        .pick_file();
+                }
 
-                        if let Some(new_world) = new_world {
+                ui.add_enabled_ui(!editor_files.recent.is_empty(), |ui| {
+                    ui.menu_button("Recent worlds", |ui| {
+                        for path in &editor_files.recent {
+                            if ui.button(path.display().to_string()).clicked() {
+                                if ui_state.confirm(PendingAction::Open(Some(path.clone()))) {
+                                    open_path = Some(path.clone());
+                                }
+                                ui.close_menu();
+                            }
+                        }
+                    });
+                });
+
+                if let Some(path) = open_path {
+                    // Maps of other editors are imported, they're saved as worlds in another file.
+                    let imported = path
+                        .extension()
+                        .map_or(false, |extension| extension == "tmx");
+                    let result = if imported {
+                        World::import_tmx(&path).map(|world| (world, vec![]))
+                    } else {
+                        World::load_migrated(&path)
+                    };
+                    match result {
+                        Ok((new_world, migrations)) => {
                             *world = new_world;
                             load_world(
                                 &world,
@@ -928,42 +3350,136 @@ fn editor_ui_system(
                                 &transform_editors,
                                 &mut camera_transform,
                                 &mut ui_state,
+                                &mut joints,
                                 &mut meshes,
                                 &mut materials,
                             );
+                            if imported {
+                                ui_state.saved_revision = SavedRevision::Unsaved;
+                            } else {
+                                editor_files.add_recent(path.clone());
+                                ui_state.save_path_modified = modified_time(&path);
+                                ui_state.save_path = Some(path);
+                            }
+                            ui_state.migrations = migrations;
+                        }
+                        Err(error) => {
+                            // TODO: Show error in the UI.
+                            println!("Couldn't open the world: {error}.");
                         }
                     }
                 }
 
                 if ui.button("Save").clicked() {
-                    if let Some(path) = rfd::FileDialog::new().save_file() {
-                        let mut world = World::default();
-                        for (_, object, transform) in &objects {
-                            match object {
-                                EditorObject::Player => {
-                                    world.player_position[0] = transform.translation.x;
-                                    world.player_position[1] = transform.translation.y;
-                                }
-                                EditorObject::WorldObject(object) => {
-                                    world.objects.push(ObjectAndTransform {
-                                        object: object.clone(),
-                                        position: transform.translation.to_array(),
-                                        scale: transform.scale.truncate().to_array(),
-                                        rotation: transform.rotation.to_euler(EulerRot::XYZ).2,
-                                    });
-                                }
-                            }
-                        }
-                        if fs::write(path, serde_json::to_string(&world).unwrap()).is_err() {
+                    if let Some(path) = world_file_dialog().save_file() {
+                        if let Err(error) = save_world(
+                            &world,
+                            path,
+                            &objects,
+                            &custom_colors,
+                            &joints,
+                            &mut ui_state,
+                            &mut editor_files,
+                        ) {
                             // TODO: Show error in the UI.
-                            println!("Couldn't save the world.");
+                            println!("Couldn't save the world: {error}.");
+                        }
+                    }
+                }
+
+                if ui
+                    .button("Compare with file…")
+                    .on_hover_text("Lists the changes from a saved world to this world.")
+                    .clicked()
+                {
+                    if let Some(path) = world_file_dialog().pick_file() {
+                        match World::load(&path) {
+                            Ok(saved_world) => {
+                                let changes = saved_world.diff(&editor_world(
+                                    &world,
+                                    &objects,
+                                    &custom_colors,
+                                    ui_state.selected.as_ref(),
+                                    &joints,
+                                ));
+                                ui_state.comparison = Some((path, changes));
+                            }
+                            Err(error) => {
+                                // TODO: Show error in the UI.
+                                println!("Couldn't open the world: {error}.");
+                            }
                         }
                     }
                 }
             });
 
+            let mut close_comparison = false;
+            if let Some((path, changes)) = &ui_state.comparison {
+                ui.horizontal(|ui| {
+                    ui.label(format!("Changes from {}:", path.display()));
+                    close_comparison = ui.button("Close").clicked();
+                });
+                if changes.is_empty() {
+                    ui.label("No changes.");
+                }
+                egui::ScrollArea::vertical()
+                    .id_source("Comparison")
+                    .max_height(200.0)
+                    .show(ui, |ui| {
+                        for change in changes {
+                            ui.label(change.to_string());
+                        }
+                    });
+            }
+            if close_comparison {
+                ui_state.comparison = None;
+            }
+
+            ui.add_space(10.0);
+
+            ui.horizontal(|ui| {
+                let mut measuring = ui_state.measure.is_some();
+                if ui
+                    .toggle_value(&mut measuring, "Measure")
+                    .on_hover_text("Drag on the canvas to measure distances and angles.")
+                    .changed()
+                {
+                    ui_state.measure = measuring.then(MeasureTool::default);
+                    ui_state.placement = None;
+                    ui_state.brush = None;
+                }
+                if let Some(position) = ui_state.pointer_position {
+                    ui.label(format!("Cursor: {:.1}, {:.1}", position.x, position.y));
+                }
+            });
+
             ui.add_space(10.0);
 
+            // Teleporters other than the selected object, with their pairs.
+            let teleporters: Vec<_> = objects
+                .iter()
+                .filter(|(entity, _, _)| {
+                    Some(*entity) != ui_state.selected.as_ref().map(|selected| selected.entity)
+                })
+                .filter_map(|(_, object, _)| match object {
+                    EditorObject::WorldObject(WorldObject::Teleporter { id, pair, .. }) => {
+                        Some((*id, *pair))
+                    }
+                    _ => None,
+                })
+                .collect();
+            // A change in the pair of the selected teleporter: (id, previous pair, new pair).
+            let mut teleporter_link = None;
+            let mut z_order_change = None;
+            let mut repeat_clicked = false;
+            // Used to describe the joints of the selected object.
+            let object_names: HashMap<_, _> = objects
+                .iter()
+                .map(|(entity, object, _)| (entity, object.name()))
+                .collect();
+
+            // Borrows the fields separately.
+            let ui_state = &mut *ui_state;
             if let Some(selected) = &mut ui_state.selected {
                 let (_, mut object, mut transform) = objects.get_mut(selected.entity).unwrap();
 
@@ -978,7 +3494,8 @@ fn editor_ui_system(
 
                     ui.add_space(100.0);
 
-                    if !matches!(&*object, EditorObject::Player) && ui.button("Delete").clicked() {
+                    if !matches!(&*object, EditorObject::Player(_)) && ui.button("Delete").clicked()
+                    {
                         delete_clicked = true;
                     }
                 });
@@ -989,17 +3506,16 @@ fn editor_ui_system(
                 }
 
                 if delete_clicked {
-                    let entity = selected.entity;
-                    ui_state.clear_selection(&mut objects, &mut commands);
-                    commands.entity(entity).despawn();
+                    ui_state.delete_selection(&mut objects, &mut commands);
                     return;
                 }
 
                 ui.add_space(10.0);
 
+                let name = object.name();
                 match &mut *object {
-                    EditorObject::Player => {
-                        ui.label("Player");
+                    EditorObject::Player(_) | EditorObject::OtherPlayer(_) => {
+                        ui.label(name);
                         egui::Grid::new("Player grid")
                             .spacing([25.0, 5.0])
                             .show(ui, |ui| {
@@ -1011,10 +3527,16 @@ fn editor_ui_system(
                                 ui.end_row();
                             });
                     }
-                    EditorObject::WorldObject(WorldObject::Block { fixed }) => {
-                        let prev_fixed = *fixed;
-                        ui.label("Block");
-                        egui::Grid::new("Block grid")
+                    EditorObject::WorldObject(world_object) => {
+                        let custom_color = custom_colors.get(selected.entity).ok().copied();
+                        let mut new_custom_color = custom_color;
+                        let prev_color = object_color(world_object, custom_color.as_ref());
+                        let prev_points = match world_object {
+                            WorldObject::Polygon { points } => Some(points.clone()),
+                            _ => None,
+                        };
+                        ui.label(world_object.name());
+                        egui::Grid::new("Object properties grid")
                             .spacing([25.0, 5.0])
                             .show(ui, |ui| {
                                 ui.label("Translation:");
@@ -1038,113 +3560,932 @@ fn editor_ui_system(
                                 transform.rotation = Quat::from_rotation_z(rotation * PI / 180.0);
                                 ui.end_row();
 
-                                ui.label("Fixed");
-                                ui.checkbox(fixed, "");
+                                ui.label("Color:").on_hover_text(
+                                    "A custom color is shown instead of the object's default color.",
+                                );
+                                ui.horizontal(|ui| {
+                                    let mut custom = custom_color.is_some();
+                                    ui.checkbox(&mut custom, "Custom");
+                                    let mut color = custom_color.map_or_else(
+                                        || world_object.color().as_rgba_f32(),
+                                        |custom_color| custom_color.0,
+                                    );
+                                    if custom {
+                                        ui.color_edit_button_rgba_unmultiplied(&mut color);
+                                    }
+                                    new_custom_color = custom.then_some(CustomColor(color));
+                                });
                                 ui.end_row();
-                            });
-                        selected
-                            .transform_editors
-                            .update_transform(&transform, &mut transform_editors);
 
-                        if *fixed != prev_fixed {
-                            let mut selected_material =
-                                current_materials.get_mut(selected.entity).unwrap();
-                            let color = if *fixed {
-                                Color::BLACK
-                            } else {
-                                Color::DARK_GRAY
-                            };
-                            *selected_material = materials.add(ColorMaterial::from(color));
-                        }
-                    }
-                    EditorObject::WorldObject(WorldObject::Goal) => {
-                        ui.label("Goal");
-                        egui::Grid::new("Goal grid")
-                            .spacing([25.0, 5.0])
-                            .show(ui, |ui| {
-                                ui.label("Translation:");
+                                ui.label("Snap rotation").on_hover_text(format!(
+                                    "Snaps to {ROTATION_SNAP}° steps when dragging the ring. \
+                                     Holding shift also snaps."
+                                ));
+                                ui.checkbox(&mut ui_state.snap_rotation, "");
+                                ui.end_row();
+
+                                ui.label("Lock aspect ratio").on_hover_text(
+                                    "Dragging a side anchor scales both axes proportionally. \
+                                     Holding shift also locks the aspect ratio.",
+                                );
+                                ui.checkbox(&mut ui_state.lock_aspect_ratio, "");
+                                ui.end_row();
+
+                                ui.label("Order:").on_hover_text(
+                                    "The order objects are drawn in, \
+                                     objects in front cover the objects behind them.",
+                                );
                                 ui.horizontal(|ui| {
-                                    ui.add(DragValue::new(&mut transform.translation.x));
-                                    ui.add(DragValue::new(&mut transform.translation.y));
+                                    for change in [
+                                        ZOrderChange::ToBack,
+                                        ZOrderChange::Backward,
+                                        ZOrderChange::Forward,
+                                        ZOrderChange::ToFront,
+                                    ] {
+                                        if ui.button(change.name()).clicked() {
+                                            z_order_change = Some(change);
+                                        }
+                                    }
                                 });
                                 ui.end_row();
 
-                                ui.label("Scale:");
+                                ui.label("Flip:");
                                 ui.horizontal(|ui| {
-                                    ui.add(DragValue::new(&mut transform.scale.x));
-                                    ui.add(DragValue::new(&mut transform.scale.y));
+                                    for axis in FlipAxis::ALL {
+                                        if ui.button(axis.name()).clicked() {
+                                            flip_world_object(world_object, &mut transform, axis);
+                                        }
+                                    }
                                 });
                                 ui.end_row();
 
-                                ui.label("Rotation:");
-                                let mut rotation =
-                                    transform.rotation.to_euler(EulerRot::XYZ).2 * 180.0 / PI;
-                                ui.add(DragValue::new(&mut rotation));
-                                transform.rotation = Quat::from_rotation_z(rotation * PI / 180.0);
+                                ui.label("Repeat:").on_hover_text(
+                                    "Creates copies of the object, \
+                                     each offset from the previous one.",
+                                );
+                                ui.horizontal(|ui| {
+                                    ui.add(
+                                        DragValue::new(&mut ui_state.repeat.count)
+                                            .clamp_range(1..=1000)
+                                            .suffix(" copies"),
+                                    );
+                                    ui.label("every");
+                                    ui.add(DragValue::new(&mut ui_state.repeat.offset.x));
+                                    ui.add(DragValue::new(&mut ui_state.repeat.offset.y));
+                                    repeat_clicked = ui.button("Repeat").clicked();
+                                });
                                 ui.end_row();
+
+                                teleporter_link =
+                                    world_object_properties_ui(ui, world_object, &teleporters);
                             });
+                        if let WorldObject::Block {
+                            fixed: true,
+                            animation: Some(animation),
+                            ..
+                        } = world_object
+                        {
+                            ui.add_space(10.0);
+                            ui.label("Animation:");
+                            animation.timeline_ui(ui, &mut selected.selected_keyframe);
+                        }
+                        if let WorldObject::MovingPlatform { waypoints, .. }
+                        | WorldObject::Saw { waypoints, .. } = world_object
+                        {
+                            ui.add_space(10.0);
+                            ui.label("Waypoints:")
+                                .on_hover_text("Offsets from the object's position.");
+                            points_ui(ui, "Waypoints grid", waypoints, 0, 100.0);
+                        }
+                        if let WorldObject::Polygon { points } = world_object {
+                            ui.add_space(10.0);
+                            ui.label("Vertices:").on_hover_text(
+                                "Scaled by the polygon's scale, \
+                                from -0.5 to 0.5 covers the same area as a block.",
+                            );
+                            points_ui(ui, "Vertices grid", points, 3, 0.1);
+                            ui.checkbox(
+                                &mut selected.placing_vertices,
+                                "Place vertices by clicking",
+                            );
+                        } else {
+                            selected.placing_vertices = false;
+                        }
+                        ui.add_space(10.0);
+                        joints_ui(ui, selected, &mut joints, &object_names);
+                        if let WorldObject::Polygon { points } = world_object {
+                            if prev_points.as_ref() != Some(points) {
+                                let mut selected_mesh =
+                                    current_meshes.get_mut(selected.entity).unwrap();
+                                *selected_mesh = meshes.add(world_object.mesh()).into();
+                            }
+                        }
                         selected
                             .transform_editors
                             .update_transform(&transform, &mut transform_editors);
+
+                        if new_custom_color != custom_color {
+                            match new_custom_color {
+                                Some(custom_color) => {
+                                    commands.entity(selected.entity).insert(custom_color);
+                                }
+                                None => {
+                                    commands.entity(selected.entity).remove::<CustomColor>();
+                                }
+                            }
+                        }
+                        let color = object_color(world_object, new_custom_color.as_ref());
+                        if color != prev_color {
+                            let mut selected_material =
+                                current_materials.get_mut(selected.entity).unwrap();
+                            *selected_material = materials.add(ColorMaterial::from(color));
+                        }
+                    }
+                }
+            } else if let Some(group) = &mut ui_state.group {
+                let mut back_clicked = false;
+                let mut delete_clicked = false;
+
+                ui.horizontal(|ui| {
+                    if ui.button("Back").clicked() {
+                        back_clicked = true;
+                    }
+
+                    ui.add_space(100.0);
+
+                    if ui.button("Delete").clicked() {
+                        delete_clicked = true;
                     }
+                });
+
+                if back_clicked {
+                    ui_state.clear_selection(&mut objects, &mut commands);
+                    return;
+                }
+
+                if delete_clicked {
+                    ui_state.delete_selection(&mut objects, &mut commands);
+                    return;
                 }
+
+                ui.add_space(10.0);
+                ui.label(format!("{} objects selected", group.entities.len()))
+                    .on_hover_text(
+                        "Shift-click objects to add or remove them, \
+                         or shift-drag a box around them to add them.",
+                    );
+                ui.horizontal(|ui| {
+                    ui.label("Rotation:")
+                        .on_hover_text("Rotates the objects around the center of the group.");
+                    let mut rotation = group.rotation;
+                    ui.add(DragValue::new(&mut rotation).suffix("°"));
+                    if rotation != group.rotation {
+                        group.rotate((rotation - group.rotation) * PI / 180.0, &mut objects);
+                        group.rotation = rotation;
+                    }
+                });
+                ui.horizontal(|ui| {
+                    ui.label("Flip:")
+                        .on_hover_text("Mirrors the objects about the center of the group.");
+                    for axis in FlipAxis::ALL {
+                        if ui.button(axis.name()).clicked() {
+                            group.flip(axis, &mut objects, &mut current_meshes, &mut meshes);
+                        }
+                    }
+                });
+                ui.horizontal(|ui| {
+                    if ui
+                        .button("Jitter")
+                        .on_hover_text(
+                            "Moves and rotates each object by a random amount within the ranges.",
+                        )
+                        .clicked()
+                    {
+                        group.jitter(ui_state.jitter, &mut objects, &mut rand::thread_rng());
+                    }
+                    ui.label("Offset:");
+                    ui.add(DragValue::new(&mut ui_state.jitter.offset).clamp_range(0.0..=f32::MAX));
+                    ui.label("Rotation:");
+                    ui.add(
+                        DragValue::new(&mut ui_state.jitter.rotation)
+                            .clamp_range(0.0..=180.0)
+                            .suffix("°"),
+                    );
+                });
+                ui.horizontal(|ui| {
+                    ui.label("Prefab name:");
+                    ui.text_edit_singleline(&mut prefab_library.new_prefab_name);
+                    let save_prefab = ui
+                        .add_enabled(
+                            !prefab_library.new_prefab_name.is_empty(),
+                            egui::Button::new("Save as prefab"),
+                        )
+                        .on_hover_text("Adds the objects to the prefabs, players aren't included.");
+                    if save_prefab.clicked() {
+                        let prefab_objects: Vec<_> = group
+                            .entities
+                            .iter()
+                            .filter_map(|entity| match objects.get(*entity) {
+                                Ok((_, EditorObject::WorldObject(object), transform)) => {
+                                    Some(object_and_transform(
+                                        object,
+                                        transform,
+                                        custom_colors.get(*entity).ok(),
+                                    ))
+                                }
+                                _ => None,
+                            })
+                            .collect();
+                        if !prefab_objects.is_empty() {
+                            let name = std::mem::take(&mut prefab_library.new_prefab_name);
+                            prefab_library
+                                .prefabs
+                                .push(Prefab::new(name, prefab_objects));
+                        }
+                    }
+                });
             } else {
+                let next_teleporter_id = unused_teleporter_id(&objects);
+                if let Some(brush) = &mut ui_state.brush {
+                    let mut done_clicked = false;
+                    ui.horizontal(|ui| {
+                        ui.label("Paint on the canvas to add terrain.");
+                        done_clicked = ui.button("Done").clicked();
+                    });
+                    ui.horizontal(|ui| {
+                        ui.label("Brush width:");
+                        ui.add(DragValue::new(&mut brush.width).clamp_range(1.0..=f32::MAX));
+                    });
+                    if done_clicked {
+                        ui_state.brush = None;
+                    }
+                    ui.add_space(10.0);
+                }
+                if let Some(placement) = &ui_state.placement {
+                    let mut cancel_clicked = false;
+                    ui.horizontal(|ui| {
+                        ui.label(format!(
+                            "Drag a rectangle on the canvas or click to place the {}.",
+                            placement.object.name().to_lowercase()
+                        ));
+                        cancel_clicked = ui.button("Cancel").clicked();
+                    });
+                    if cancel_clicked {
+                        ui_state.placement = None;
+                    }
+                    ui.add_space(10.0);
+                }
                 ui.horizontal(|ui| {
                     let new_objects = [
-                        ("block", WorldObject::Block { fixed: true }),
-                        ("goal", WorldObject::Goal),
+                        (
+                            "block",
+                            WorldObject::Block {
+                                fixed: true,
+                                animation: None,
+                                ccd: false,
+                                friction: None,
+                                restitution: None,
+                                density: 1.0,
+                                material: None,
+                            },
+                        ),
+                        (
+                            "goal",
+                            WorldObject::Goal {
+                                reward: 1.0,
+                                optional: false,
+                            },
+                        ),
+                        ("water", WorldObject::Water { density: 1.5 }),
+                        ("conveyor", WorldObject::Conveyor { speed: 100.0 }),
+                        ("hazard", WorldObject::Hazard { reward: -1.0 }),
+                        (
+                            "enemy",
+                            WorldObject::Enemy {
+                                patrol: [200.0, 0.0],
+                                speed: 100.0,
+                                reward: -1.0,
+                            },
+                        ),
+                        ("ladder", WorldObject::Ladder { speed: 150.0 }),
+                        ("checkpoint", WorldObject::Checkpoint),
+                        (
+                            "sensor zone",
+                            WorldObject::SensorZone {
+                                name: String::new(),
+                            },
+                        ),
+                        (
+                            "text",
+                            WorldObject::Text {
+                                text: "Text".to_string(),
+                            },
+                        ),
+                        (
+                            "decoration",
+                            WorldObject::Decoration {
+                                color: [0.8, 0.8, 0.8, 1.0],
+                                background: true,
+                            },
+                        ),
+                        (
+                            "attractor",
+                            WorldObject::Attractor {
+                                strength: 2_000_000.0,
+                                radius: 300.0,
+                            },
+                        ),
+                        (
+                            "teleporter",
+                            WorldObject::Teleporter {
+                                id: next_teleporter_id,
+                                pair: None,
+                                keep_velocity: true,
+                            },
+                        ),
+                        ("coin", WorldObject::Coin),
+                        (
+                            "polygon",
+                            WorldObject::Polygon {
+                                points: vec![[-0.5, -0.5], [0.5, -0.5], [0.5, 0.5]],
+                            },
+                        ),
+                        (
+                            "moving platform",
+                            WorldObject::MovingPlatform {
+                                waypoints: vec![[200.0, 0.0]],
+                                speed: 100.0,
+                                loop_mode: LoopMode::PingPong,
+                                switch: None,
+                            },
+                        ),
+                        (
+                            "saw",
+                            WorldObject::Saw {
+                                waypoints: vec![[200.0, 0.0]],
+                                speed: 100.0,
+                                loop_mode: LoopMode::PingPong,
+                                reward: -1.0,
+                            },
+                        ),
+                        (
+                            "switch",
+                            WorldObject::Switch {
+                                id: 0,
+                                toggle: false,
+                            },
+                        ),
+                        ("pressure plate", WorldObject::PressurePlate { id: 0 }),
+                        ("door", WorldObject::Door { switch: 0 }),
+                        (
+                            "timed block",
+                            WorldObject::TimedBlock {
+                                period: 60,
+                                phase: 0,
+                            },
+                        ),
                     ];
                     for (name, object) in new_objects {
                         if ui.button(format!("New {name}")).clicked() {
-                            ui_state.create_and_select(
-                                object,
-                                camera_transform.translation.truncate(),
-                                camera_transform.scale.x,
-                                &mut objects,
-                                &mut commands,
-                                &mut meshes,
-                                &mut materials,
-                            );
+                            ui_state.brush = None;
+                            ui_state.measure = None;
+                            ui_state.placement = Some(NewObjectPlacement { object, rect: None });
                         }
                     }
+                    if ui.button("New player").clicked() {
+                        ui_state.create_and_select(
+                            EditorObject::OtherPlayer(world.player_shape),
+                            camera_transform.translation.truncate(),
+                            Vec2::splat(NEW_OBJECT_SIZE),
+                            camera_transform.scale.x,
+                            &mut objects,
+                            &mut commands,
+                            &mut meshes,
+                            &mut materials,
+                        );
+                    }
+                    if ui.button("Terrain brush").clicked() {
+                        ui_state.placement = None;
+                        ui_state.measure = None;
+                        ui_state.brush = Some(TerrainBrush {
+                            width: TERRAIN_BRUSH_WIDTH,
+                            stroke: vec![],
+                        });
+                    }
                 });
 
                 ui.add_space(10.0);
 
-                ui.label("Objects:");
+                egui::CollapsingHeader::new("Templates").show(ui, |ui| {
+                    if let Some(template) = template_settings.ui(ui) {
+                        ui_state.insert_template(
+                            template_settings.blocks(template),
+                            camera_transform.translation.truncate(),
+                            &mut objects,
+                            &mut commands,
+                            &mut meshes,
+                            &mut materials,
+                        );
+                    }
+                });
 
-                egui::Grid::new("Object grid")
-                    .spacing([50.0, 5.0])
-                    .show(ui, |ui| {
-                        for (entity, object, transform) in objects.iter_mut() {
-                            let name = match *object {
-                                EditorObject::Player => "Player",
-                                EditorObject::WorldObject(WorldObject::Block { .. }) => "Block",
-                                EditorObject::WorldObject(WorldObject::Goal) => "Goal",
-                            };
-                            if ui.button(name).clicked() {
-                                camera_transform.translation.x = transform.translation.x;
-                                camera_transform.translation.y = transform.translation.y;
-                                ui_state.select(
-                                    entity,
-                                    camera_transform.scale.x,
-                                    &mut objects,
-                                    &mut commands,
-                                    &mut meshes,
-                                    &mut materials,
-                                );
-                                return;
-                            }
+                ui.add_space(10.0);
 
-                            if !matches!(&*object, EditorObject::Player)
-                                && ui.button("Delete").clicked()
-                            {
-                                commands.entity(entity).despawn();
-                                return;
+                egui::CollapsingHeader::new("Prefabs").show(ui, |ui| {
+                    let mut inserted = None;
+                    let mut removed = None;
+                    egui::Grid::new("Prefab grid")
+                        .spacing([25.0, 5.0])
+                        .show(ui, |ui| {
+                            for (index, prefab) in prefab_library.prefabs.iter().enumerate() {
+                                ui.label(&prefab.name);
+                                ui.label(format!("{} objects", prefab.objects.len()));
+                                if ui.button("Insert").clicked() {
+                                    inserted = Some(index);
+                                }
+                                if ui.button("Export").clicked() {
+                                    if let Some(path) = rfd::FileDialog::new().save_file() {
+                                        if let Err(error) = prefab.save(path) {
+                                            // TODO: Show error in the UI.
+                                            println!("Couldn't save the prefab: {error}.");
+                                        }
+                                    }
+                                }
+                                if ui.button("Remove").clicked() {
+                                    removed = Some(index);
+                                }
+                                ui.end_row();
+                            }
+                        });
+                    if prefab_library.prefabs.is_empty() {
+                        ui.label("Shift-click objects to select a group and save it as a prefab.");
+                    }
+                    ui.horizontal(|ui| {
+                        if ui.button("Import").clicked() {
+                            if let Some(path) = rfd::FileDialog::new().pick_file() {
+                                match Prefab::load(path) {
+                                    Ok(prefab) => prefab_library.prefabs.push(prefab),
+                                    Err(error) => {
+                                        // TODO: Show error in the UI.
+                                        println!("Couldn't open the prefab: {error}.");
+                                    }
+                                }
                             }
-                            ui.end_row();
                         }
-                    });
+                        if ui
+                            .button("Import SVG")
+                            .on_hover_text(
+                                "Adds the rectangles, polygons and paths of an SVG image \
+                                 as a prefab of blocks and polygons.",
+                            )
+                            .clicked()
+                        {
+                            if let Some(path) = rfd::FileDialog::new()
+                                .add_filter("SVG", &["svg"])
+                                .pick_file()
+                            {
+                                match Prefab::import_svg(path) {
+                                    Ok(prefab) => prefab_library.prefabs.push(prefab),
+                                    Err(error) => {
+                                        // TODO: Show error in the UI.
+                                        println!("Couldn't import the SVG: {error}.");
+                                    }
+                                }
+                            }
+                        }
+                    });
+
+                    if let Some(index) = inserted {
+                        ui_state.insert_prefab(
+                            &prefab_library.prefabs[index],
+                            camera_transform.translation.truncate(),
+                            &mut objects,
+                            &mut commands,
+                            &mut meshes,
+                            &mut materials,
+                        );
+                    }
+                    if let Some(index) = removed {
+                        prefab_library.prefabs.remove(index);
+                    }
+                });
+
+                ui.add_space(10.0);
+
+                egui::CollapsingHeader::new("Reference image")
+                    .show(ui, |ui| reference_image.ui(ui))
+                    .header_response
+                    .on_hover_text("An image drawn behind the objects to trace the level from.");
+
+                ui.add_space(10.0);
+
+                egui::CollapsingHeader::new("World settings").show(ui, |ui| {
+                    ui.label("Optional moves:");
+                    ui.checkbox(&mut world.capabilities.down, "Fast fall (down)");
+                    ui.checkbox(&mut world.capabilities.dash, "Dash");
+                    ui.checkbox(&mut world.capabilities.grab, "Grab and carry blocks");
+                    ui.horizontal(|ui| {
+                        ui.label("Air control:");
+                        ui.add(
+                            DragValue::new(&mut world.capabilities.air_control)
+                                .speed(0.01)
+                                .clamp_range(0.0..=1.0),
+                        );
+                    })
+                    .response
+                    .on_hover_text("Fraction of the move impulse applied in the air.");
+                    ui.horizontal(|ui| {
+                        ui.label("Air jumps:");
+                        ui.add(
+                            DragValue::new(&mut world.capabilities.air_jumps).clamp_range(0..=5),
+                        );
+                    })
+                    .response
+                    .on_hover_text("1 for a double jump.");
+
+                    ui.add_space(10.0);
+                    let mut has_stamina = world.stamina.is_some();
+                    ui.checkbox(&mut has_stamina, "Stamina")
+                        .on_hover_text("Jumping uses energy, standing still regains it.");
+                    if has_stamina != world.stamina.is_some() {
+                        world.stamina = has_stamina.then(Stamina::default);
+                    }
+                    if let Some(stamina) = &mut world.stamina {
+                        egui::Grid::new("Stamina grid")
+                            .spacing([25.0, 5.0])
+                            .show(ui, |ui| {
+                                ui.label("Max energy:");
+                                ui.add(
+                                    DragValue::new(&mut stamina.max_energy)
+                                        .speed(0.01)
+                                        .clamp_range(0.01..=100.0),
+                                );
+                                ui.end_row();
+                                ui.label("Jump cost:");
+                                ui.add(
+                                    DragValue::new(&mut stamina.jump_cost)
+                                        .speed(0.01)
+                                        .clamp_range(0.0..=100.0),
+                                );
+                                ui.end_row();
+                                ui.label("Regeneration per step:");
+                                ui.add(
+                                    DragValue::new(&mut stamina.regeneration)
+                                        .speed(0.001)
+                                        .clamp_range(0.0..=100.0),
+                                );
+                                ui.end_row();
+                            });
+                    }
+
+                    ui.add_space(10.0);
+                    ui.horizontal(|ui| {
+                        ui.label("Win condition:");
+                        egui::ComboBox::from_id_source("Win condition")
+                            .selected_text(world.win_condition.name())
+                            .show_ui(ui, |ui| {
+                                for win_condition in [
+                                    WinCondition::ReachGoal,
+                                    WinCondition::Survive {
+                                        steps: DEFAULT_SURVIVE_STEPS,
+                                    },
+                                    WinCondition::CollectCoins,
+                                    WinCondition::PushBlock,
+                                ] {
+                                    let selected = std::mem::discriminant(&world.win_condition)
+                                        == std::mem::discriminant(&win_condition);
+                                    if ui
+                                        .selectable_label(selected, win_condition.name())
+                                        .clicked()
+                                        && !selected
+                                    {
+                                        world.win_condition = win_condition;
+                                    }
+                                }
+                            });
+                    });
+                    if let WinCondition::Survive { steps } = &mut world.win_condition {
+                        ui.horizontal(|ui| {
+                            ui.label("Steps:");
+                            ui.add(DragValue::new(steps).clamp_range(1..=1_000_000));
+                        });
+                    }
+
+                    ui.add_space(10.0);
+                    ui.label("Physics:");
+                    egui::Grid::new("Physics grid")
+                        .spacing([25.0, 5.0])
+                        .show(ui, |ui| {
+                            ui.label("Gravity:");
+                            ui.add(
+                                DragValue::new(&mut world.physics.gravity)
+                                    .speed(0.01)
+                                    .clamp_range(-100.0..=100.0),
+                            );
+                            ui.end_row();
+                            ui.label("Player impulse:");
+                            ui.add(
+                                DragValue::new(&mut world.physics.player_impulse)
+                                    .speed(0.0001)
+                                    .clamp_range(0.0..=1.0),
+                            );
+                            ui.end_row();
+                            ui.label("Jump impulse:");
+                            ui.add(
+                                DragValue::new(&mut world.physics.jump_impulse)
+                                    .speed(0.001)
+                                    .clamp_range(0.0..=10.0),
+                            );
+                            ui.end_row();
+                            ui.label("Friction:");
+                            ui.add(
+                                DragValue::new(&mut world.physics.friction)
+                                    .speed(0.01)
+                                    .clamp_range(0.0..=10.0),
+                            );
+                            ui.end_row();
+                            ui.label("Restitution:");
+                            ui.add(
+                                DragValue::new(&mut world.physics.restitution)
+                                    .speed(0.01)
+                                    .clamp_range(0.0..=1.0),
+                            );
+                            ui.end_row();
+                        });
+
+                    ui.add_space(10.0);
+                    ui.label("Solver:");
+                    egui::Grid::new("Solver grid")
+                        .spacing([25.0, 5.0])
+                        .show(ui, |ui| {
+                            ui.label("Time step:");
+                            ui.add(
+                                DragValue::new(&mut world.solver.dt)
+                                    .speed(0.0001)
+                                    .clamp_range(0.001..=0.1)
+                                    .suffix(" s"),
+                            );
+                            ui.end_row();
+                            ui.label("Velocity iterations:");
+                            ui.add(
+                                DragValue::new(&mut world.solver.velocity_iterations)
+                                    .clamp_range(1..=50),
+                            );
+                            ui.end_row();
+                            ui.label("Stabilization iterations:");
+                            ui.add(
+                                DragValue::new(&mut world.solver.stabilization_iterations)
+                                    .clamp_range(0..=50),
+                            );
+                            ui.end_row();
+                        });
+
+                    ui.add_space(10.0);
+                    ui.label("Randomization:").on_hover_text(
+                        "Random changes to the level, for environments created with a seed.",
+                    );
+                    let physics = world.physics;
+                    let randomization = &mut world.randomization;
+                    egui::Grid::new("Randomization grid")
+                        .spacing([25.0, 5.0])
+                        .show(ui, |ui| {
+                            range_ui(ui, "Gravity:", &mut randomization.gravity, physics.gravity);
+                            ui.end_row();
+                            range_ui(
+                                ui,
+                                "Friction:",
+                                &mut randomization.friction,
+                                physics.friction,
+                            );
+                            ui.end_row();
+                            ui.label("Block offset:");
+                            ui.add(
+                                DragValue::new(&mut randomization.block_offset)
+                                    .clamp_range(0.0..=1000.0),
+                            );
+                            ui.end_row();
+                            ui.label("Block scale:");
+                            ui.add(
+                                DragValue::new(&mut randomization.block_scale)
+                                    .speed(0.01)
+                                    .clamp_range(0.0..=0.9),
+                            );
+                            ui.end_row();
+                        });
+
+                    ui.add_space(10.0);
+                    let mut has_bounds = world.bounds.is_some();
+                    ui.checkbox(&mut has_bounds, "Bounds")
+                        .on_hover_text("Leaving the bounds ends the episode.");
+                    match (has_bounds, world.bounds) {
+                        (true, None) => world.bounds = Some(objects_bounds(&objects)),
+                        (false, Some(_)) => world.bounds = None,
+                        _ => {}
+                    }
+                    if let Some(bounds) = &mut world.bounds {
+                        egui::Grid::new("Bounds grid")
+                            .spacing([25.0, 5.0])
+                            .show(ui, |ui| {
+                                ui.label("Bottom left:");
+                                ui.horizontal(|ui| {
+                                    ui.add(DragValue::new(&mut bounds.min[0]));
+                                    ui.add(DragValue::new(&mut bounds.min[1]));
+                                });
+                                ui.end_row();
+                                ui.label("Top right:");
+                                ui.horizontal(|ui| {
+                                    ui.add(
+                                        DragValue::new(&mut bounds.max[0])
+                                            .clamp_range(bounds.min[0]..=f32::INFINITY),
+                                    );
+                                    ui.add(
+                                        DragValue::new(&mut bounds.max[1])
+                                            .clamp_range(bounds.min[1]..=f32::INFINITY),
+                                    );
+                                });
+                                ui.end_row();
+                            });
+                        if ui.button("Fit to objects").clicked() {
+                            *bounds = objects_bounds(&objects);
+                        }
+                    }
+
+                    let mut has_spawn_region = world.spawn_region.is_some();
+                    ui.checkbox(&mut has_spawn_region, "Spawn region")
+                        .on_hover_text("The player starts at a random position in the region.");
+                    match (has_spawn_region, world.spawn_region) {
+                        (true, None) => {
+                            let player_position = Vec2::from(world.player_position);
+                            world.spawn_region = Some(Bounds {
+                                min: (player_position - SPAWN_REGION_HALF_SIZE).to_array(),
+                                max: (player_position + SPAWN_REGION_HALF_SIZE).to_array(),
+                            });
+                        }
+                        (false, Some(_)) => world.spawn_region = None,
+                        _ => {}
+                    }
+                    if let Some(spawn_region) = &mut world.spawn_region {
+                        egui::Grid::new("Spawn region grid")
+                            .spacing([25.0, 5.0])
+                            .show(ui, |ui| {
+                                ui.label("Bottom left:");
+                                ui.horizontal(|ui| {
+                                    ui.add(DragValue::new(&mut spawn_region.min[0]));
+                                    ui.add(DragValue::new(&mut spawn_region.min[1]));
+                                });
+                                ui.end_row();
+                                ui.label("Top right:");
+                                ui.horizontal(|ui| {
+                                    ui.add(
+                                        DragValue::new(&mut spawn_region.max[0])
+                                            .clamp_range(spawn_region.min[0]..=f32::INFINITY),
+                                    );
+                                    ui.add(
+                                        DragValue::new(&mut spawn_region.max[1])
+                                            .clamp_range(spawn_region.min[1]..=f32::INFINITY),
+                                    );
+                                });
+                                ui.end_row();
+                            });
+                    }
+
+                    ui.add_space(10.0);
+                    ui.label("Player:");
+                    let mut player_shape = world.player_shape;
+                    egui::Grid::new("Player shape grid")
+                        .spacing([25.0, 5.0])
+                        .show(ui, |ui| {
+                            ui.label("Radius:");
+                            ui.add(
+                                DragValue::new(&mut player_shape.radius).clamp_range(1.0..=500.0),
+                            );
+                            ui.end_row();
+                            ui.label("Depth:");
+                            ui.add(
+                                DragValue::new(&mut player_shape.depth).clamp_range(0.0..=1000.0),
+                            );
+                            ui.end_row();
+                            ui.label("Mass:");
+                            ui.horizontal(|ui| {
+                                let mut custom_mass = player_shape.mass.is_some();
+                                ui.checkbox(&mut custom_mass, "Custom");
+                                match (custom_mass, player_shape.mass) {
+                                    (true, None) => player_shape.mass = Some(1.0),
+                                    (false, Some(_)) => player_shape.mass = None,
+                                    _ => {}
+                                }
+                                if let Some(mass) = &mut player_shape.mass {
+                                    ui.add(
+                                        DragValue::new(mass)
+                                            .speed(0.01)
+                                            .clamp_range(0.001..=1000.0),
+                                    );
+                                }
+                            });
+                            ui.end_row();
+                        });
+                    if player_shape != world.player_shape {
+                        world.player_shape = player_shape;
+                        for (entity, mut object, _) in objects.iter_mut() {
+                            if let EditorObject::Player(shape) | EditorObject::OtherPlayer(shape) =
+                                &mut *object
+                            {
+                                *shape = player_shape;
+                                if let Ok(mut mesh) = current_meshes.get_mut(entity) {
+                                    *mesh = meshes.add(player_shape.mesh()).into();
+                                }
+                            }
+                        }
+                    }
+
+                    ui.add_space(10.0);
+                    egui::CollapsingHeader::new("Calibrate impulses").show(ui, |ui| {
+                        ui.label(
+                            "Finds the smallest impulses with which the player can just clear \
+                            the given jump height and gap.",
+                        );
+                        ui_state
+                            .calibration_target
+                            .ui(ui, &mut world.physics, player_shape);
+                    });
+                });
+
+                ui.add_space(10.0);
+
+                ui.label("Objects:");
+
+                egui::Grid::new("Object grid")
+                    .spacing([50.0, 5.0])
+                    .show(ui, |ui| {
+                        for (entity, object, transform) in objects.iter_mut() {
+                            if ui.button(object.name()).clicked() {
+                                camera_transform.translation.x = transform.translation.x;
+                                camera_transform.translation.y = transform.translation.y;
+                                ui_state.select(
+                                    entity,
+                                    camera_transform.scale.x,
+                                    &mut objects,
+                                    &mut commands,
+                                    &mut meshes,
+                                    &mut materials,
+                                );
+                                return;
+                            }
+
+                            if !matches!(&*object, EditorObject::Player(_))
+                                && ui.button("Delete").clicked()
+                            {
+                                commands.entity(entity).despawn_recursive();
+                                return;
+                            }
+                            ui.end_row();
+                        }
+                    });
+            }
+
+            if let (Some(change), Some(selected)) = (z_order_change, &mut ui_state.selected) {
+                change_z_order(change, selected, &mut objects);
+            }
+
+            if repeat_clicked {
+                ui_state.repeat_selection(
+                    &mut objects,
+                    &custom_colors,
+                    &mut commands,
+                    &mut meshes,
+                    &mut materials,
+                );
+            }
+
+            // Keep the pairs symmetric.
+            if let Some((id, previous_pair, pair)) = teleporter_link {
+                for (entity, mut object, _) in objects.iter_mut() {
+                    let EditorObject::WorldObject(world_object) = &mut *object else {
+                        continue;
+                    };
+                    let WorldObject::Teleporter {
+                        id: other_id,
+                        pair: other_pair,
+                        ..
+                    } = world_object
+                    else {
+                        continue;
+                    };
+                    if *other_id == id {
+                        continue;
+                    }
+                    if Some(*other_id) == previous_pair {
+                        *other_pair = None;
+                    }
+                    if Some(*other_id) == pair {
+                        *other_pair = Some(id);
+                    }
+                    // Unpaired teleporters have a different color.
+                    if let Ok(mut material) = current_materials.get_mut(entity) {
+                        let color = object_color(world_object, custom_colors.get(entity).ok());
+                        *material = materials.add(ColorMaterial::from(color));
+                    }
+                }
             }
         });
 
@@ -1154,23 +4495,94 @@ fn editor_ui_system(
         return;
     };
 
+    let screen_rect = contexts.ctx_mut().screen_rect();
+    if fit_view_clicked {
+        let (min, max) = objects.iter().fold(
+            (Vec2::splat(f32::INFINITY), Vec2::splat(f32::NEG_INFINITY)),
+            |(min, max), (_, object, transform)| {
+                let (object_min, object_max) = object.extent(transform);
+                (min.min(object_min), max.max(object_max))
+            },
+        );
+        let size = (max - min) / Vec2::new(screen_rect.width(), screen_rect.height());
+        let scale = (size.max_element() * (1.0 + 2.0 * FIT_VIEW_MARGIN)).max(0.01);
+        camera_transform.translation.x = (min.x + max.x) / 2.0;
+        camera_transform.translation.y = (min.y + max.y) / 2.0;
+        set_camera_scale(&mut camera_transform, scale, &mut transform_editors);
+    }
+
+    let (minimap_rect, minimap_position) =
+        minimap_ui(contexts.ctx_mut(), &objects, &camera_transform);
+    // Clicking or dragging on the minimap moves the view there.
+    if let Some(position) = minimap_position {
+        camera_transform.translation.x = position.x;
+        camera_transform.translation.y = position.y;
+    }
+
     let pointer_position = if let Some(position) = contexts.ctx_mut().pointer_latest_pos() {
         position
     } else {
         return;
     };
 
-    let pointer_on_egui = response.rect.contains(pointer_position);
+    let pointer_on_egui =
+        response.rect.contains(pointer_position) || minimap_rect.contains(pointer_position);
 
-    let screen_rect = contexts.ctx_mut().screen_rect();
     let pointer_offset_from_center = pointer_position - screen_rect.center();
     let mut pointer_offset_from_center =
         Vec2::new(pointer_offset_from_center.x, pointer_offset_from_center.y);
     pointer_offset_from_center.y *= -1.0; // Bevy's and EGUI's +y-axis have different directions.
     pointer_offset_from_center *= camera_transform.scale.x;
     let pointer_position = camera_transform.translation.truncate() + pointer_offset_from_center;
+    ui_state.pointer_position = (!pointer_on_egui).then_some(pointer_position);
 
-    if mouse_button_input.just_pressed(MouseButton::Left) {
+    let placing_vertex = ui_state
+        .selected
+        .as_ref()
+        .map_or(false, |selected| selected.placing_vertices);
+    let connecting = ui_state
+        .selected
+        .as_ref()
+        .map_or(false, |selected| selected.connecting.is_some());
+    if connecting && mouse_button_input.just_pressed(MouseButton::Left) {
+        if !pointer_on_egui {
+            let selected = ui_state.selected.as_mut().unwrap();
+            if let Some(joint) = connect_objects(selected, pointer_position, &objects) {
+                joints.0.push(joint);
+            }
+            selected.connecting = None;
+        }
+    } else if placing_vertex && mouse_button_input.just_pressed(MouseButton::Left) {
+        if !pointer_on_egui {
+            let entity = ui_state.selected.as_ref().unwrap().entity;
+            let (_, mut object, transform) = objects.get_mut(entity).unwrap();
+            if let EditorObject::WorldObject(world_object) = &mut *object {
+                if let WorldObject::Polygon { points } = world_object {
+                    let local = transform.rotation.inverse()
+                        * (pointer_position.extend(0.0) - transform.translation);
+                    points.push((local.truncate() / transform.scale.truncate()).to_array());
+                }
+                let mut mesh = current_meshes.get_mut(entity).unwrap();
+                *mesh = meshes.add(world_object.mesh()).into();
+            }
+        }
+    } else if mouse_button_input.just_pressed(MouseButton::Left) && ui_state.measure.is_some() {
+        if let (false, Some(measure)) = (pointer_on_egui, &mut ui_state.measure) {
+            measure.line = Some((pointer_position, pointer_position));
+        }
+    } else if mouse_button_input.just_pressed(MouseButton::Left) && ui_state.brush.is_some() {
+        if let (false, Some(brush)) = (pointer_on_egui, &mut ui_state.brush) {
+            brush.stroke = vec![pointer_position];
+        }
+    } else if mouse_button_input.just_pressed(MouseButton::Left) && ui_state.placement.is_some() {
+        if let (false, Some(placement)) = (pointer_on_egui, &mut ui_state.placement) {
+            placement.rect = Some((pointer_position, pointer_position));
+        }
+    } else if mouse_button_input.just_pressed(MouseButton::Left) && shift_pressed {
+        if !pointer_on_egui {
+            ui_state.shift_click(pointer_position, &mut objects, &mut commands);
+        }
+    } else if mouse_button_input.just_pressed(MouseButton::Left) {
         if !pointer_on_egui {
             ui_state.drag_start(
                 pointer_position,
@@ -1184,11 +4596,37 @@ fn editor_ui_system(
             );
         }
     } else if mouse_button_input.pressed(MouseButton::Left) {
+        if let Some((_, end)) = &mut ui_state.selection_box {
+            *end = pointer_position;
+        }
+        if let Some(NewObjectPlacement {
+            rect: Some((_, end)),
+            ..
+        }) = &mut ui_state.placement
+        {
+            *end = pointer_position;
+        }
+        if let Some(MeasureTool {
+            line: Some((_, end)),
+        }) = &mut ui_state.measure
+        {
+            *end = pointer_position;
+        }
+        if let Some(brush) = &mut ui_state.brush {
+            let spacing = TERRAIN_BRUSH_SPACING * camera_transform.scale.x;
+            if let Some(last) = brush.stroke.last() {
+                if last.distance(pointer_position) >= spacing {
+                    brush.stroke.push(pointer_position);
+                }
+            }
+        }
         ui_state.on_drag(
             pointer_offset_from_center,
             &mut objects,
             &mut transform_editors,
             &mut camera_transform,
+            shift_pressed,
+            !alt_pressed,
         );
     } else if mouse_button_input.just_released(MouseButton::Left) {
         ui_state.on_drag(
@@ -1196,36 +4634,982 @@ fn editor_ui_system(
             &mut objects,
             &mut transform_editors,
             &mut camera_transform,
+            shift_pressed,
+            !alt_pressed,
         );
         ui_state.drag_end();
+        ui_state.box_select(&mut objects, &mut commands);
+        ui_state.finish_placement(
+            camera_transform.scale.x,
+            &mut objects,
+            &mut commands,
+            &mut meshes,
+            &mut materials,
+        );
+        ui_state.finish_stroke(&mut objects, &mut commands, &mut meshes, &mut materials);
     }
 
     if !pointer_on_egui && ui_state.drag.is_none() && !mouse_wheel_events.is_empty() {
         let scale = camera_transform.scale.x;
         let total_scroll = mouse_wheel_events.iter().map(|event| event.y).sum::<f32>();
         let new_scale = (scale * 0.9_f32.powf(total_scroll)).max(0.01);
+        set_camera_scale(&mut camera_transform, new_scale, &mut transform_editors);
+
+        let new_translation = new_scale
+            * (camera_transform.translation.truncate() / scale
+                + pointer_position * (1.0 / new_scale - 1.0 / scale));
+        camera_transform.translation.x = new_translation.x;
+        camera_transform.translation.y = new_translation.y;
+    }
+}
 
-        camera_transform.scale.x = new_scale;
-        camera_transform.scale.y = new_scale;
+// Zooms the camera, keeping the transform editors the same size on the screen.
+fn set_camera_scale(
+    camera_transform: &mut Transform,
+    scale: f32,
+    transform_editors: &mut Query<
+        (Entity, &mut Transform, &TransformEditor),
+        (Without<EditorObject>, Without<Camera>),
+    >,
+) {
+    camera_transform.scale.x = scale;
+    camera_transform.scale.y = scale;
 
-        for (_, mut transform, transform_editor) in transform_editors.iter_mut() {
-            match transform_editor {
-                TransformEditor::Anchor => {
-                    transform.scale.x = new_scale;
-                    transform.scale.y = new_scale;
+    for (_, mut transform, transform_editor) in transform_editors.iter_mut() {
+        match transform_editor {
+            TransformEditor::Anchor => {
+                transform.scale.x = scale;
+                transform.scale.y = scale;
+            }
+            TransformEditor::Ring => {
+                // The torus was initially parallel to the XZ plane, so we scale those directions.
+                transform.scale.x = scale;
+                transform.scale.z = scale;
+            }
+        }
+    }
+}
+
+// Shows the objects and the part of the world in view in the bottom right corner of the screen.
+// Returns the minimap's rect, and the world position under the pointer while the minimap is clicked or dragged.
+fn minimap_ui(
+    ctx: &egui::Context,
+    objects: &Query<(Entity, &mut EditorObject, &mut Transform)>,
+    camera_transform: &Transform,
+) -> (egui::Rect, Option<Vec2>) {
+    let screen_rect = ctx.screen_rect();
+    let view_center = camera_transform.translation.truncate();
+    let view_half_size =
+        Vec2::new(screen_rect.width(), screen_rect.height()) * camera_transform.scale.x / 2.0;
+    let extents: Vec<_> = objects
+        .iter()
+        .map(|(_, object, transform)| (object.extent(transform), object.color()))
+        .collect();
+    // The minimap always shows the view, even when it's away from the objects.
+    let (min, max) = extents.iter().fold(
+        (view_center - view_half_size, view_center + view_half_size),
+        |(min, max), ((object_min, object_max), _)| (min.min(*object_min), max.max(*object_max)),
+    );
+    let center = (min + max) / 2.0;
+    let map_scale = (MINIMAP_SIZE / (max - min)).min_element();
+
+    let response = egui::Area::new("Minimap")
+        .anchor(egui::Align2::RIGHT_BOTTOM, egui::vec2(-10.0, -10.0))
+        .show(ctx, |ui| {
+            egui::Frame::popup(ui.style())
+                .show(ui, |ui| {
+                    let (response, painter) = ui.allocate_painter(
+                        egui::vec2(MINIMAP_SIZE.x, MINIMAP_SIZE.y),
+                        egui::Sense::click_and_drag(),
+                    );
+                    // Bevy's and EGUI's +y-axis have different directions.
+                    let to_map = |position: Vec2| {
+                        let offset = (position - center) * map_scale;
+                        response.rect.center() + egui::vec2(offset.x, -offset.y)
+                    };
+                    for ((object_min, object_max), color) in &extents {
+                        let [r, g, b, a] = color.as_rgba_u8();
+                        painter.rect_filled(
+                            egui::Rect::from_two_pos(to_map(*object_min), to_map(*object_max)),
+                            0.0,
+                            Color32::from_rgba_unmultiplied(r, g, b, a),
+                        );
+                    }
+                    painter.rect_stroke(
+                        egui::Rect::from_two_pos(
+                            to_map(view_center - view_half_size),
+                            to_map(view_center + view_half_size),
+                        ),
+                        0.0,
+                        egui::Stroke::new(1.5, SELECTION_COLOR),
+                    );
+
+                    response
+                        .interact_pointer_pos()
+                        .filter(|_| response.is_pointer_button_down_on())
+                        .map(|pointer| {
+                            let offset = (pointer - response.rect.center()) / map_scale;
+                            center + Vec2::new(offset.x, -offset.y)
+                        })
+                })
+                .inner
+        });
+    (response.response.rect, response.inner)
+}
+
+// Grid rows for the properties specific to the object type.
+// `teleporters` are the other teleporters in the world along with their pairs.
+// Returns (id, previous pair, new pair) if the pair of a teleporter was changed.
+fn world_object_properties_ui(
+    ui: &mut egui::Ui,
+    world_object: &mut WorldObject,
+    teleporters: &[(u32, Option<u32>)],
+) -> Option<(u32, Option<u32>, Option<u32>)> {
+    match world_object {
+        WorldObject::Teleporter {
+            id,
+            pair,
+            keep_velocity,
+        } => {
+            ui.label("Id:");
+            ui.label(id.to_string());
+            ui.end_row();
+
+            let previous_pair = *pair;
+            ui.label("Pair:");
+            egui::ComboBox::from_id_source("Teleporter pair")
+                .selected_text(pair.map_or("None".to_string(), |pair| pair.to_string()))
+                .show_ui(ui, |ui| {
+                    ui.selectable_value(pair, None, "None");
+                    // Teleporters paired with another teleporter can't be chosen.
+                    for (other_id, other_pair) in teleporters {
+                        if other_pair.is_none() || *other_pair == Some(*id) {
+                            ui.selectable_value(pair, Some(*other_id), other_id.to_string());
+                        }
+                    }
+                });
+            ui.end_row();
+
+            ui.label("Keep velocity");
+            ui.checkbox(keep_velocity, "");
+            ui.end_row();
+
+            if *pair != previous_pair {
+                return Some((*id, previous_pair, *pair));
+            }
+        }
+        WorldObject::Block {
+            fixed,
+            animation,
+            ccd,
+            friction,
+            restitution,
+            density,
+            material,
+        } => {
+            ui.label("Fixed");
+            ui.checkbox(fixed, "");
+            ui.end_row();
+
+            if *fixed {
+                ui.label("Animated");
+                let mut animated = animation.is_some();
+                if ui.checkbox(&mut animated, "").changed() {
+                    *animation = animated.then(Animation::default);
                 }
-                TransformEditor::Ring => {
-                    // The torus was initially parallel to the XZ plane, so we scale those directions.
-                    transform.scale.x = new_scale;
-                    transform.scale.z = new_scale;
+                ui.end_row();
+            } else {
+                ui.label("Continuous collision").on_hover_text(
+                    "Stops the block from passing through thin blocks when moving fast.",
+                );
+                ui.checkbox(ccd, "");
+                ui.end_row();
+
+                ui.label("Density:")
+                    .on_hover_text("Denser blocks are heavier, the default density is 1.");
+                ui.add(
+                    DragValue::new(density)
+                        .speed(0.01)
+                        .clamp_range(0.01..=f32::INFINITY),
+                );
+                ui.end_row();
+            }
+
+            ui.label("Material:")
+                .on_hover_text("Sets the friction, restitution and drag of the block's surface.");
+            let previous_material = *material;
+            egui::ComboBox::from_id_source("Block material")
+                .selected_text(material.map_or("None", |material| material.name()))
+                .show_ui(ui, |ui| {
+                    ui.selectable_value(material, None, "None");
+                    for preset in SurfaceMaterial::ALL {
+                        ui.selectable_value(material, Some(preset), preset.name());
+                    }
+                });
+            // The preset replaces values tuned by hand.
+            if *material != previous_material && material.is_some() {
+                *friction = None;
+                *restitution = None;
+            }
+            ui.end_row();
+
+            optional_value_ui(
+                ui,
+                "Friction:",
+                friction,
+                material.map_or(0.5, |material| material.friction()),
+            );
+            ui.end_row();
+            optional_value_ui(
+                ui,
+                "Restitution:",
+                restitution,
+                material.map_or(0.0, |material| material.restitution()),
+            );
+            ui.end_row();
+        }
+        WorldObject::Goal { reward, optional } => {
+            ui.label("Reward:");
+            ui.add(DragValue::new(reward).speed(0.01));
+            ui.end_row();
+
+            ui.label("Optional").on_hover_text(
+                "Optional goals only give their reward, reaching them doesn't win the world.",
+            );
+            ui.checkbox(optional, "");
+            ui.end_row();
+        }
+        WorldObject::Coin | WorldObject::Checkpoint => {}
+        WorldObject::MovingPlatform {
+            speed,
+            loop_mode,
+            switch,
+            ..
+        } => {
+            ui.label("Speed:");
+            ui.add(DragValue::new(speed).clamp_range(0.0..=f32::INFINITY));
+            ui.end_row();
+
+            ui.label("Loop mode:");
+            egui::ComboBox::from_id_source("Loop mode")
+                .selected_text(loop_mode.name())
+                .show_ui(ui, |ui| {
+                    for mode in [LoopMode::Loop, LoopMode::PingPong, LoopMode::Once] {
+                        ui.selectable_value(loop_mode, mode, mode.name());
+                    }
+                });
+            ui.end_row();
+
+            let mut has_switch = switch.is_some();
+            ui.label("Switch")
+                .on_hover_text("The platform only moves while a switch with this id is active.");
+            ui.horizontal(|ui| {
+                ui.checkbox(&mut has_switch, "");
+                if has_switch {
+                    let mut id = switch.unwrap_or(0);
+                    ui.add(DragValue::new(&mut id));
+                    *switch = Some(id);
+                } else {
+                    *switch = None;
+                }
+            });
+            ui.end_row();
+        }
+        WorldObject::Switch { id, toggle } => {
+            ui.label("Id:");
+            ui.add(DragValue::new(id));
+            ui.end_row();
+
+            ui.label("Toggle").on_hover_text(
+                "Toggle switches change between active and inactive every time they're pressed, \
+                 instead of only being active while pressed.",
+            );
+            ui.checkbox(toggle, "");
+            ui.end_row();
+        }
+        WorldObject::PressurePlate { id } => {
+            ui.label("Id:").on_hover_text(
+                "The plate activates the doors and moving platforms linked to this id \
+                 while a player or a block rests on it.",
+            );
+            ui.add(DragValue::new(id));
+            ui.end_row();
+        }
+        WorldObject::Door { switch } => {
+            ui.label("Switch:").on_hover_text(
+                "The door is removed while a switch or pressure plate with this id is active.",
+            );
+            ui.add(DragValue::new(switch));
+            ui.end_row();
+        }
+        WorldObject::Hazard { reward } => {
+            ui.label("Reward:")
+                .on_hover_text("Given when the hazard kills the player.");
+            ui.add(DragValue::new(reward).speed(0.01));
+            ui.end_row();
+        }
+        WorldObject::Enemy {
+            patrol,
+            speed,
+            reward,
+        } => {
+            ui.label("Patrol:").on_hover_text(
+                "The enemy moves back and forth between its position and this offset.",
+            );
+            ui.horizontal(|ui| {
+                ui.add(DragValue::new(&mut patrol[0]));
+                ui.add(DragValue::new(&mut patrol[1]));
+            });
+            ui.end_row();
+
+            ui.label("Speed:");
+            ui.add(DragValue::new(speed).clamp_range(0.0..=f32::INFINITY));
+            ui.end_row();
+
+            ui.label("Reward:")
+                .on_hover_text("Given when the enemy kills the player.");
+            ui.add(DragValue::new(reward).speed(0.01));
+            ui.end_row();
+        }
+        WorldObject::Saw {
+            speed,
+            loop_mode,
+            reward,
+            ..
+        } => {
+            ui.label("Speed:");
+            ui.add(DragValue::new(speed).clamp_range(0.0..=f32::INFINITY));
+            ui.end_row();
+
+            ui.label("Loop mode:");
+            egui::ComboBox::from_id_source("Loop mode")
+                .selected_text(loop_mode.name())
+                .show_ui(ui, |ui| {
+                    for mode in [LoopMode::Loop, LoopMode::PingPong, LoopMode::Once] {
+                        ui.selectable_value(loop_mode, mode, mode.name());
+                    }
+                });
+            ui.end_row();
+
+            ui.label("Reward:")
+                .on_hover_text("Given when the saw kills the player.");
+            ui.add(DragValue::new(reward).speed(0.01));
+            ui.end_row();
+        }
+        WorldObject::Text { text } => {
+            ui.label("Text:");
+            ui.text_edit_multiline(text);
+            ui.end_row();
+        }
+        WorldObject::Decoration { color, background } => {
+            ui.label("Color:");
+            ui.color_edit_button_rgba_unmultiplied(color);
+            ui.end_row();
+
+            ui.label("Background")
+                .on_hover_text("Background decorations are drawn behind every other object.");
+            ui.checkbox(background, "");
+            ui.end_row();
+        }
+        WorldObject::SensorZone { name } => {
+            ui.label("Name:").on_hover_text(
+                "Only shown in the editor. Whether the player overlaps the zone is part of the observations.",
+            );
+            ui.text_edit_singleline(name);
+            ui.end_row();
+        }
+        WorldObject::TimedBlock { period, phase } => {
+            ui.label("Period:")
+                .on_hover_text("Physics steps the block stays solid, then intangible.");
+            ui.add(DragValue::new(period).clamp_range(1..=usize::MAX));
+            ui.end_row();
+
+            ui.label("Phase:")
+                .on_hover_text("Physics steps into the cycle the world starts at.");
+            ui.add(DragValue::new(phase));
+            ui.end_row();
+        }
+        WorldObject::Ladder { speed } => {
+            ui.label("Speed:");
+            ui.add(DragValue::new(speed).clamp_range(0.0..=f32::INFINITY));
+            ui.end_row();
+        }
+        WorldObject::Conveyor { speed } => {
+            ui.label("Speed:");
+            ui.add(DragValue::new(speed));
+            ui.end_row();
+        }
+        WorldObject::Attractor { strength, radius } => {
+            ui.label("Strength:");
+            ui.add(DragValue::new(strength).speed(1000.0));
+            ui.end_row();
+            ui.label("Radius:");
+            ui.add(DragValue::new(radius).clamp_range(0.0..=f32::INFINITY));
+            ui.end_row();
+        }
+        WorldObject::Water { density } => {
+            ui.label("Density:");
+            ui.add(DragValue::new(density).speed(0.01).clamp_range(0.0..=10.0));
+            ui.end_row();
+        }
+    }
+    None
+}
+
+// The objects' bounding rectangle, with a margin around it.
+// A row per point, such as the waypoints of a moving platform, with buttons to add and remove points.
+// Points can't be removed below `min_points`, and new points are added `step` to the right of the last one.
+fn points_ui(
+    ui: &mut egui::Ui,
+    id_source: &str,
+    points: &mut Vec<[f32; 2]>,
+    min_points: usize,
+    step: f32,
+) {
+    let mut removed = None;
+    let can_remove = points.len() > min_points;
+    egui::Grid::new(id_source)
+        .spacing([25.0, 5.0])
+        .show(ui, |ui| {
+            for (index, point) in points.iter_mut().enumerate() {
+                ui.label(format!("{}:", index + 1));
+                ui.horizontal(|ui| {
+                    ui.add(DragValue::new(&mut point[0]).speed(step / 100.0));
+                    ui.add(DragValue::new(&mut point[1]).speed(step / 100.0));
+                    if can_remove && ui.small_button("Remove").clicked() {
+                        removed = Some(index);
+                    }
+                });
+                ui.end_row();
+            }
+        });
+    if let Some(index) = removed {
+        points.remove(index);
+    }
+    if ui.button("Add point").clicked() {
+        let last = points.last().copied().unwrap_or_default();
+        points.push([last[0] + step, last[1]]);
+    }
+}
+
+// An optional range, starting as an empty range at `value` when enabled.
+fn range_ui(ui: &mut egui::Ui, label: &str, range: &mut Option<[f32; 2]>, value: f32) {
+    ui.label(label);
+    ui.horizontal(|ui| {
+        let mut enabled = range.is_some();
+        ui.checkbox(&mut enabled, "");
+        match (enabled, *range) {
+            (true, None) => *range = Some([value, value]),
+            (false, Some(_)) => *range = None,
+            _ => {}
+        }
+        if let Some([start, end]) = range {
+            ui.add(DragValue::new(start).speed(0.01));
+            ui.add(
+                DragValue::new(end)
+                    .speed(0.01)
+                    .clamp_range(*start..=f32::INFINITY),
+            );
+        }
+    });
+}
+
+// A checkbox for overriding a value of the world, with a DragValue for the value when it's overridden.
+// The joints of the selected object, and buttons for connecting it to another object.
+fn joints_ui(
+    ui: &mut egui::Ui,
+    selected: &mut SelectedState,
+    joints: &mut EditorJoints,
+    object_names: &HashMap<Entity, &'static str>,
+) {
+    ui.label("Joints:");
+    let mut removed = None;
+    for (index, joint) in joints.0.iter_mut().enumerate() {
+        let Some(side) = joint
+            .entities
+            .iter()
+            .position(|entity| *entity == selected.entity)
+        else {
+            continue;
+        };
+        // Joints to deleted objects are removed when saving.
+        let Some(other_name) = object_names.get(&joint.entities[1 - side]) else {
+            continue;
+        };
+        egui::Grid::new(("Joint grid", index))
+            .spacing([25.0, 5.0])
+            .show(ui, |ui| {
+                ui.label(format!("{} joint with {other_name}", joint.kind.name()));
+                if ui.button("Remove").clicked() {
+                    removed = Some(index);
+                }
+                ui.end_row();
+
+                ui.label("Anchor:")
+                    .on_hover_text("Relative to this object's position, along its axes.");
+                ui.horizontal(|ui| {
+                    ui.add(DragValue::new(&mut joint.anchors[side][0]));
+                    ui.add(DragValue::new(&mut joint.anchors[side][1]));
+                });
+                ui.end_row();
+
+                match &mut joint.kind {
+                    JointKind::Revolute => {}
+                    JointKind::Prismatic { axis, limits } => {
+                        ui.label("Axis:")
+                            .on_hover_text("In the frame of the first connected object.");
+                        ui.horizontal(|ui| {
+                            ui.add(DragValue::new(&mut axis[0]).speed(0.01));
+                            ui.add(DragValue::new(&mut axis[1]).speed(0.01));
+                        });
+                        ui.end_row();
+
+                        range_ui(ui, "Limits:", limits, 0.0);
+                        ui.end_row();
+                    }
+                    JointKind::Rope { length } => {
+                        ui.label("Length:");
+                        ui.add(DragValue::new(length).clamp_range(0.0..=f32::INFINITY));
+                        ui.end_row();
+                    }
                 }
+            });
+    }
+    if let Some(index) = removed {
+        joints.0.remove(index);
+    }
+
+    ui.horizontal(|ui| {
+        ui.label("Connect:").on_hover_text(
+            "Click another object to connect it to this one. \
+            Ropes are attached to this object's center.",
+        );
+        for kind in [
+            JointKind::Revolute,
+            JointKind::Prismatic {
+                axis: [1.0, 0.0],
+                limits: None,
+            },
+            JointKind::Rope { length: 0.0 },
+        ] {
+            let active =
+                selected.connecting.map(|connecting| connecting.name()) == Some(kind.name());
+            if ui.selectable_label(active, kind.name()).clicked() {
+                selected.connecting = if active { None } else { Some(kind) };
             }
         }
+    });
+}
 
-        let new_translation = new_scale
-            * (camera_transform.translation.truncate() / scale
-                + pointer_position * (1.0 / new_scale - 1.0 / scale));
-        camera_transform.translation.x = new_translation.x;
-        camera_transform.translation.y = new_translation.y;
+// A joint between the selected object and the topmost other object under the pointer, anchored at the pointer.
+// Ropes are anchored at the selected object's center instead, with the distance to the pointer as their length.
+fn connect_objects(
+    selected: &SelectedState,
+    pointer_position: Vec2,
+    objects: &Query<(Entity, &mut EditorObject, &mut Transform)>,
+) -> Option<EditorJoint> {
+    let kind = selected.connecting?;
+    let (other, _, other_transform) = objects
+        .iter()
+        .filter(|(entity, object, transform)| {
+            *entity != selected.entity
+                && matches!(object, EditorObject::WorldObject(_))
+                && object.can_drag(transform, pointer_position)
+        })
+        .max_by(|(_, _, transform1), (_, _, transform2)| {
+            transform1
+                .translation
+                .z
+                .total_cmp(&transform2.translation.z)
+        })?;
+    let (_, _, selected_transform) = objects.get(selected.entity).ok()?;
+    let local = |transform: &Transform| {
+        (transform.rotation.inverse() * (pointer_position.extend(0.0) - transform.translation))
+            .truncate()
+            .to_array()
+    };
+    let (anchors, kind) = match kind {
+        JointKind::Rope { .. } => (
+            [[0.0, 0.0], local(other_transform)],
+            JointKind::Rope {
+                length: (pointer_position - selected_transform.translation.truncate()).length(),
+            },
+        ),
+        _ => ([local(selected_transform), local(other_transform)], kind),
+    };
+    Some(EditorJoint {
+        entities: [selected.entity, other],
+        anchors,
+        kind,
+    })
+}
+
+fn optional_value_ui(ui: &mut egui::Ui, label: &str, value: &mut Option<f32>, default: f32) {
+    ui.label(label)
+        .on_hover_text("The material's or world's value is used when unchecked.");
+    ui.horizontal(|ui| {
+        let mut enabled = value.is_some();
+        ui.checkbox(&mut enabled, "");
+        match (enabled, *value) {
+            (true, None) => *value = Some(default),
+            (false, Some(_)) => *value = None,
+            _ => {}
+        }
+        if let Some(value) = value {
+            ui.add(
+                DragValue::new(value)
+                    .speed(0.01)
+                    .clamp_range(0.0..=f32::INFINITY),
+            );
+        }
+    });
+}
+
+fn objects_bounds(objects: &Query<(Entity, &mut EditorObject, &mut Transform)>) -> Bounds {
+    let (min, max) = objects.iter().fold(
+        (Vec2::splat(f32::INFINITY), Vec2::splat(f32::NEG_INFINITY)),
+        |(min, max), (_, object, transform)| {
+            // Large enough for any rotation of the object.
+            let half_size = match &*object {
+                EditorObject::Player(shape) | EditorObject::OtherPlayer(shape) => {
+                    shape.half_height()
+                }
+                EditorObject::WorldObject(_) => transform.scale.truncate().length() / 2.0,
+            };
+            let translation = transform.translation.truncate();
+            (
+                min.min(translation - half_size),
+                max.max(translation + half_size),
+            )
+        },
+    );
+    Bounds {
+        min: (min - BOUNDS_MARGIN).to_array(),
+        max: (max + BOUNDS_MARGIN).to_array(),
+    }
+}
+
+// Redraws the dashed outlines of the world's bounds and spawn region when they change.
+// Draws the text of the text objects over their shapes, and outlines the group selection and the selection box.
+// Also draws the stroke being painted with the terrain brush and the measured line with its length and angle.
+fn draw_overlay(
+    ui_state: Res<EditorUiState>,
+    world: Res<World>,
+    objects: Query<(&EditorObject, &Transform)>,
+    mut contexts: EguiContexts,
+    camera: Query<&Transform, (With<Camera>, Without<EditorObject>)>,
+    windows: Query<&Window, With<PrimaryWindow>>,
+) {
+    let mut overlay = OverlayPainter::default();
+    for (object, transform) in objects.iter() {
+        if let EditorObject::WorldObject(WorldObject::Text { text }) = object {
+            overlay.text(
+                transform.translation.truncate().to_array(),
+                text.as_str(),
+                Color32::BLACK,
+            );
+        }
+    }
+
+    let Ok(camera) = camera.get_single() else {
+        return;
+    };
+    let width = 2.0 * camera.scale.x;
+    if let Some(group) = &ui_state.group {
+        for (object, transform) in group
+            .entities
+            .iter()
+            .filter_map(|entity| objects.get(*entity).ok())
+        {
+            match object {
+                EditorObject::Player(shape) | EditorObject::OtherPlayer(shape) => {
+                    overlay.circle(
+                        transform.translation.truncate().to_array(),
+                        shape.half_height(),
+                        SELECTION_COLOR,
+                    );
+                }
+                EditorObject::WorldObject(_) => {
+                    let corners: Vec<_> = [
+                        [-0.5, -0.5],
+                        [0.5, -0.5],
+                        [0.5, 0.5],
+                        [-0.5, 0.5],
+                        [-0.5, -0.5],
+                    ]
+                    .into_iter()
+                    .map(|[x, y]| {
+                        transform
+                            .transform_point(Vec3::new(x, y, 0.0))
+                            .truncate()
+                            .to_array()
+                    })
+                    .collect();
+                    overlay.path(&corners, width, SELECTION_COLOR);
+                }
+            }
+        }
+    }
+    for (start, end) in &ui_state.guides {
+        overlay.path(&[start.to_array(), end.to_array()], width, GUIDE_COLOR);
+    }
+    let placement_rect = ui_state
+        .placement
+        .as_ref()
+        .and_then(|placement| placement.rect);
+    if let Some(brush) = &ui_state.brush {
+        let stroke: Vec<_> = brush.stroke.iter().map(|point| point.to_array()).collect();
+        overlay.path(&stroke, brush.width, SELECTION_COLOR);
+    }
+    if let Some(MeasureTool {
+        line: Some((start, end)),
+    }) = ui_state.measure
+    {
+        overlay.path(&[start.to_array(), end.to_array()], width, SELECTION_COLOR);
+        let offset = end - start;
+        // Distances relative to the player's size help to check whether jumps are possible.
+        overlay.text(
+            ((start + end) / 2.0).to_array(),
+            format!(
+                "{:.1} ({:.1} player radii) at {:.1}°",
+                offset.length(),
+                offset.length() / world.player_shape.radius,
+                offset.y.atan2(offset.x).to_degrees()
+            ),
+            Color32::BLACK,
+        );
+    }
+    for (start, end) in ui_state.selection_box.into_iter().chain(placement_rect) {
+        overlay.path(
+            &[
+                [start.x, start.y],
+                [end.x, start.y],
+                [end.x, end.y],
+                [start.x, end.y],
+                [start.x, start.y],
+            ],
+            width,
+            SELECTION_COLOR,
+        );
+    }
+
+    if let (false, Ok(window)) = (overlay.is_empty(), windows.get_single()) {
+        overlay.paint_in_background(contexts.ctx_mut(), "Editor overlay", camera, window);
+    }
+
+    // The angle of the selected object next to the pointer while rotating it.
+    if let (
+        Some(_),
+        Some(SelectedState {
+            entity,
+            transform_editors:
+                TransformEditors::Rect {
+                    dragging: RectDrag::Rotation(_),
+                    ..
+                },
+            ..
+        }),
+    ) = (&ui_state.drag, &ui_state.selected)
+    {
+        let ctx = contexts.ctx_mut();
+        if let (Some(pointer), Ok((_, transform))) =
+            (ctx.pointer_latest_pos(), objects.get(*entity))
+        {
+            let angle = transform.rotation.to_euler(EulerRot::XYZ).2.to_degrees();
+            ctx.layer_painter(egui::LayerId::new(
+                egui::Order::Foreground,
+                egui::Id::new("Rotation readout"),
+            ))
+            .text(
+                pointer + egui::vec2(15.0, -15.0),
+                egui::Align2::LEFT_BOTTOM,
+                format!("{angle:.1}°"),
+                egui::FontId::default(),
+                Color32::BLACK,
+            );
+        }
+    }
+}
+
+fn update_bounds_outline(
+    mut commands: Commands,
+    world: Res<World>,
+    outline: Query<Entity, With<BoundsOutline>>,
+    mut drawn: Local<(Option<Bounds>, Option<Bounds>)>,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<ColorMaterial>>,
+) {
+    // The outlines are despawned when leaving the editor, so they are also redrawn when they are missing.
+    let nothing_to_draw = world.bounds.is_none() && world.spawn_region.is_none();
+    if *drawn == (world.bounds, world.spawn_region) && outline.is_empty() == nothing_to_draw {
+        return;
+    }
+    for entity in outline.iter() {
+        commands.entity(entity).despawn();
+    }
+    *drawn = (world.bounds, world.spawn_region);
+
+    let mesh: Mesh2dHandle = meshes.add(shape::Quad::new(Vec2::ONE).into()).into();
+    for (bounds, color) in [
+        (world.bounds, Color::RED),
+        (world.spawn_region, Color::BLUE),
+    ] {
+        if let Some(bounds) = bounds {
+            let material = materials.add(ColorMaterial::from(color));
+            spawn_dashed_rectangle(&mut commands, bounds, &mesh, &material);
+        }
+    }
+}
+
+// Redraws the background grid when the view changes. The spacing of the lines grows with the zoom,
+// so they stay at least GRID_MIN_SPACING pixels apart.
+fn update_grid(
+    mut commands: Commands,
+    camera: Query<&Transform, (With<Camera>, Without<EditorObject>)>,
+    windows: Query<&Window, With<PrimaryWindow>>,
+    grid: Query<Entity, With<GridLines>>,
+    mut drawn: Local<Option<(Vec3, Vec3, Vec2)>>,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<ColorMaterial>>,
+) {
+    let (Ok(camera), Ok(window)) = (camera.get_single(), windows.get_single()) else {
+        return;
+    };
+    let view = (
+        camera.translation,
+        camera.scale,
+        Vec2::new(window.width(), window.height()),
+    );
+    // The grid is despawned when leaving the editor, so it's also redrawn when it's missing.
+    if *drawn == Some(view) && !grid.is_empty() {
+        return;
+    }
+    for entity in grid.iter() {
+        commands.entity(entity).despawn();
+    }
+    *drawn = Some(view);
+
+    let scale = camera.scale.x;
+    let half_size = view.2 / 2.0 * scale;
+    let min = camera.translation.truncate() - half_size;
+    let max = camera.translation.truncate() + half_size;
+    let spacing = grid_spacing(GRID_MIN_SPACING * scale);
+    let mut lines = vec![];
+    // The lines at 0 are the axes.
+    for index in (min.x / spacing).ceil() as i64..=(max.x / spacing).floor() as i64 {
+        let x = index as f32 * spacing;
+        if index != 0 {
+            lines.push(([x, min.y], [x, max.y]));
+        }
+    }
+    for index in (min.y / spacing).ceil() as i64..=(max.y / spacing).floor() as i64 {
+        let y = index as f32 * spacing;
+        if index != 0 {
+            lines.push(([min.x, y], [max.x, y]));
+        }
+    }
+    let axes = [([min.x, 0.0], [max.x, 0.0]), ([0.0, min.y], [0.0, max.y])];
+
+    for (lines, width, color) in [
+        (&lines[..], scale, GRID_COLOR),
+        (&axes[..], 2.0 * scale, GRID_AXES_COLOR),
+    ] {
+        commands
+            .spawn(MaterialMesh2dBundle {
+                mesh: meshes.add(lines_mesh(lines, width)).into(),
+                material: materials.add(ColorMaterial::from(color)),
+                transform: Transform::from_xyz(0.0, 0.0, GRID_Z),
+                ..default()
+            })
+            .insert(GridLines);
+    }
+}
+
+// The smallest spacing of 1, 2 or 5 times a power of 10 which is at least `min_spacing`.
+fn grid_spacing(min_spacing: f32) -> f32 {
+    let power = 10.0_f32.powf(min_spacing.log10().floor());
+    [1.0, 2.0, 5.0]
+        .into_iter()
+        .map(|multiple| multiple * power)
+        .find(|spacing| *spacing >= min_spacing)
+        .unwrap_or(10.0 * power)
+}
+
+fn spawn_dashed_rectangle(
+    commands: &mut Commands,
+    bounds: Bounds,
+    mesh: &Mesh2dHandle,
+    material: &Handle<ColorMaterial>,
+) {
+    let corners = [
+        Vec2::from(bounds.min),
+        Vec2::new(bounds.max[0], bounds.min[1]),
+        Vec2::from(bounds.max),
+        Vec2::new(bounds.min[0], bounds.max[1]),
+    ];
+    for (index, start) in corners.iter().enumerate() {
+        let end = corners[(index + 1) % corners.len()];
+        let length = start.distance(end);
+        if length == 0.0 {
+            continue;
+        }
+        let direction = (end - *start) / length;
+        let period = (2.0 * BOUNDS_DASH_LENGTH).max(length / MAX_BOUNDS_DASHES);
+        let mut offset = 0.0;
+        while offset < length {
+            let dash_length = (period / 2.0).min(length - offset);
+            let center = *start + direction * (offset + dash_length / 2.0);
+            let scale = if direction.x != 0.0 {
+                Vec3::new(dash_length, BOUNDS_OUTLINE_THICKNESS, 1.0)
+            } else {
+                Vec3::new(BOUNDS_OUTLINE_THICKNESS, dash_length, 1.0)
+            };
+            commands
+                .spawn(MaterialMesh2dBundle {
+                    mesh: mesh.clone(),
+                    material: material.clone(),
+                    transform: Transform::from_translation(center.extend(BOUNDS_OUTLINE_Z))
+                        .with_scale(scale),
+                    ..default()
+                })
+                .insert(BoundsOutline);
+            offset += period;
+        }
+    }
+}
+
+fn update_indicators(
+    objects: Query<(&EditorObject, &Transform), Without<Indicator>>,
+    mut indicators: Query<(&Parent, &mut Transform, &mut Visibility, &Indicator)>,
+) {
+    for (parent, mut transform, mut visibility, indicator) in indicators.iter_mut() {
+        let Ok((EditorObject::WorldObject(object), parent_transform)) = objects.get(parent.get())
+        else {
+            continue;
+        };
+        match (indicator, object) {
+            (Indicator::ConveyorArrow, WorldObject::Conveyor { speed }) => {
+                // The triangle points up, so we rotate it to point along the conveyor.
+                transform.rotation =
+                    Quat::from_rotation_z(if *speed < 0.0 { PI / 2.0 } else { -PI / 2.0 });
+                transform.scale = Vec3::new(0.5, 0.5, 1.0);
+                *visibility = if *speed == 0.0 {
+                    Visibility::Hidden
+                } else {
+                    Visibility::Inherited
+                };
+            }
+            (Indicator::AttractorRing, WorldObject::Attractor { radius, .. }) => {
+                // The torus lies in the XZ plane, so we rotate it into the XY plane,
+                // and undo the parent's scale so the ring has the attractor's radius.
+                transform.rotation = Quat::from_rotation_x(PI / 2.0);
+                transform.scale = Vec3::new(
+                    radius / parent_transform.scale.x.abs().max(1e-3),
+                    1.0,
+                    radius / parent_transform.scale.y.abs().max(1e-3),
+                );
+            }
+            _ => {}
+        }
     }
 }
@@ -0,0 +1,96 @@
+use std::{io, path::PathBuf};
+
+use thiserror::Error;
+
+/// Errors returned by the public APIs of the crate.
+#[derive(Error, Debug)]
+pub enum Error {
+    /// The environment doesn't contain any goals, so distances to goals aren't defined.
+    #[error("the environment doesn't contain any goals")]
+    NoGoals,
+    /// A goal index was out of range.
+    #[error("goal {goal} is out of range, the environment has {goal_count} goals")]
+    GoalOutOfRange { goal: usize, goal_count: usize },
+    /// A world file couldn't be read or written.
+    #[error("couldn't access the world file {path:?}")]
+    WorldIo {
+        path: PathBuf,
+        #[source]
+        source: io::Error,
+    },
+    /// A world couldn't be serialized or deserialized.
+    #[error("couldn't parse the world")]
+    WorldFormat(#[from] serde_json::Error),
+    /// A RON world couldn't be parsed.
+    #[error("couldn't parse the world")]
+    WorldRonParse(#[from] ron::error::SpannedError),
+    /// A world couldn't be serialized as RON.
+    #[error("couldn't serialize the world")]
+    WorldRonSerialize(#[from] ron::Error),
+    /// A binary world couldn't be encoded or decoded.
+    #[error("couldn't encode or decode the binary world")]
+    WorldBinary(#[from] bincode::Error),
+    /// A world file was saved by a newer version of the crate, in a format this version can't read.
+    #[error("the world has version {version}, newer than the supported version {supported}")]
+    WorldVersion { version: u32, supported: u32 },
+    /// A policy table file couldn't be read or written.
+    #[error("couldn't access the policy table file {path:?}")]
+    PolicyIo {
+        path: PathBuf,
+        #[source]
+        source: io::Error,
+    },
+    /// A policy table couldn't be serialized or deserialized.
+    #[error("couldn't parse the policy table")]
+    PolicyFormat(#[source] serde_json::Error),
+    /// A prefab file couldn't be read or written.
+    #[error("couldn't access the prefab file {path:?}")]
+    PrefabIo {
+        path: PathBuf,
+        #[source]
+        source: io::Error,
+    },
+    /// A prefab couldn't be serialized or deserialized.
+    #[error("couldn't parse the prefab")]
+    PrefabFormat(#[source] serde_json::Error),
+    /// A prefab file was saved by a newer version of the crate, in a format this version can't read.
+    #[error("the prefab has version {version}, newer than the supported version {supported}")]
+    PrefabVersion { version: u32, supported: u32 },
+    /// A Tiled map file couldn't be read.
+    #[error("couldn't access the Tiled map file {path:?}")]
+    TiledIo {
+        path: PathBuf,
+        #[source]
+        source: io::Error,
+    },
+    /// A Tiled map wasn't valid XML.
+    #[error("couldn't parse the Tiled map")]
+    TiledXml(#[from] roxmltree::Error),
+    /// A Tiled map couldn't be converted to a world.
+    #[error("couldn't import the Tiled map: {0}")]
+    TiledMap(String),
+    /// An SVG file couldn't be read.
+    #[error("couldn't access the SVG file {path:?}")]
+    SvgIo {
+        path: PathBuf,
+        #[source]
+        source: io::Error,
+    },
+    /// An SVG image wasn't valid XML.
+    #[error("couldn't parse the SVG")]
+    SvgXml(#[source] roxmltree::Error),
+    /// The shapes of an SVG image couldn't be read.
+    #[error("couldn't import the SVG: {0}")]
+    SvgFormat(String),
+    /// An observation didn't have the number of values a policy table was discretized for.
+    #[error("expected an observation with {expected} values, got {actual}")]
+    ObservationSize { expected: usize, actual: usize },
+    /// A batch of moves didn't have one move per environment of a [`VecEnvironment`](crate::VecEnvironment).
+    #[error("expected {expected} moves, got {actual}")]
+    BatchSize { expected: usize, actual: usize },
+    /// [`Environment::step_multi`](crate::Environment::step_multi) wasn't given one move per player.
+    #[error("expected {expected} moves, one per player, got {actual}")]
+    PlayerCount { expected: usize, actual: usize },
+}
+
+pub type Result<T, E = Error> = std::result::Result<T, E>;
@@ -0,0 +1,125 @@
+use std::sync::Mutex;
+
+use rapier2d::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::common::Environment;
+
+/// An axis aligned rectangle in world units, see [`Environment::set_bounds`].
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq)]
+pub struct Bounds {
+    /// The bottom left corner.
+    pub min: [f32; 2],
+    /// The top right corner.
+    pub max: [f32; 2],
+}
+
+impl Bounds {
+    pub fn contains(&self, point: [f32; 2]) -> bool {
+        (self.min[0]..=self.max[0]).contains(&point[0])
+            && (self.min[1]..=self.max[1]).contains(&point[1])
+    }
+}
+
+/// A player started or stopped touching a collider.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct ContactEvent {
+    /// Index of the player, see [`Environment::add_player`].
+    pub player: usize,
+    pub collider: ColliderHandle,
+    /// The rigid body the collider is attached to, None for fixed blocks.
+    pub rigid_body: Option<RigidBodyHandle>,
+    /// Whether the contact started (otherwise it stopped).
+    pub started: bool,
+    /// Whether the collider is a sensor, such as a teleporter.
+    pub sensor: bool,
+}
+
+/// A player reached a goal, winning the environment.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct GoalEvent {
+    pub player: usize,
+    /// Index of the goal, in the order goals were added to the environment.
+    pub goal: usize,
+}
+
+/// A player left the environment's [`Bounds`].
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct BoundsEvent {
+    pub player: usize,
+    /// The position of the player after leaving the bounds, in world units.
+    pub position: [f32; 2],
+}
+
+/// An event raised during [`Environment::step`].
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub(crate) enum EnvEvent {
+    Contact(ContactEvent),
+    GoalReached(GoalEvent),
+    LeftBounds(BoundsEvent),
+}
+
+type Callback<Event> = Box<dyn FnMut(&Event, &Environment) + Send + Sync>;
+
+/// The callbacks registered with [`Environment::on_contact`], [`Environment::on_goal_reached`]
+/// and [`Environment::on_left_bounds`].
+#[derive(Default)]
+pub(crate) struct EventCallbacks {
+    pub contact: Vec<Callback<ContactEvent>>,
+    pub goal_reached: Vec<Callback<GoalEvent>>,
+    pub left_bounds: Vec<Callback<BoundsEvent>>,
+}
+
+impl EventCallbacks {
+    pub fn is_empty(&self) -> bool {
+        self.contact.is_empty() && self.goal_reached.is_empty() && self.left_bounds.is_empty()
+    }
+
+    pub fn dispatch(&mut self, event: &EnvEvent, environment: &Environment) {
+        match event {
+            EnvEvent::Contact(event) => {
+                for callback in &mut self.contact {
+                    callback(event, environment);
+                }
+            }
+            EnvEvent::GoalReached(event) => {
+                for callback in &mut self.goal_reached {
+                    callback(event, environment);
+                }
+            }
+            EnvEvent::LeftBounds(event) => {
+                for callback in &mut self.left_bounds {
+                    callback(event, environment);
+                }
+            }
+        }
+    }
+}
+
+// Collects the collision events of a physics step, the players' colliders are the only ones with active events.
+#[derive(Default)]
+pub(crate) struct CollisionCollector {
+    pub events: Mutex<Vec<CollisionEvent>>,
+}
+
+impl EventHandler for CollisionCollector {
+    fn handle_collision_event(
+        &self,
+        _bodies: &RigidBodySet,
+        _colliders: &ColliderSet,
+        event: CollisionEvent,
+        _contact_pair: Option<&ContactPair>,
+    ) {
+        self.events.lock().unwrap().push(event);
+    }
+
+    fn handle_contact_force_event(
+        &self,
+        _dt: Real,
+        _bodies: &RigidBodySet,
+        _colliders: &ColliderSet,
+        _contact_pair: &ContactPair,
+        _total_force_magnitude: Real,
+    ) {
+    }
+}
@@ -1,15 +1,23 @@
-use crate::common::{
-    AppState, Environment, Move, World, WorldObject, BEVY_TO_PHYSICS_SCALE, PLAYER_DEPTH,
-    PLAYER_RADIUS,
+use crate::{
+    common::{
+        player_color, AppState, Environment, Move, WinCondition, World, WorldObject,
+        BEVY_TO_PHYSICS_SCALE,
+    },
+    overlay::OverlayPainter,
 };
 
-use bevy::{prelude::*, sprite::MaterialMesh2dBundle};
-use bevy_egui::{egui, EguiContexts};
+use bevy::{prelude::*, sprite::MaterialMesh2dBundle, window::PrimaryWindow};
+use bevy_egui::{
+    egui::{self, Color32},
+    EguiContexts,
+};
 use rapier2d::prelude::RigidBodyHandle;
 
 pub fn add_game_systems(app: &mut App) {
     app.add_system(setup_game.in_schedule(OnEnter(AppState::Game)))
-        .add_systems((game_ui_system, update_game).in_set(OnUpdate(AppState::Game)))
+        .add_systems(
+            (game_ui_system, update_game, draw_text_objects).in_set(OnUpdate(AppState::Game)),
+        )
         .add_system(cleanup_game.in_schedule(OnExit(AppState::Game)));
 }
 
@@ -19,66 +27,39 @@ fn setup_game(
     mut meshes: ResMut<Assets<Mesh>>,
     mut materials: ResMut<Assets<ColorMaterial>>,
 ) {
-    let mut physics_environment = Environment::new(world.player_position);
-
-    let capsule = bevy::prelude::shape::Capsule {
-        radius: PLAYER_RADIUS,
-        rings: 5,
-        depth: PLAYER_DEPTH,
-        latitudes: 10,
-        longitudes: 10,
-        uv_profile: bevy::prelude::shape::CapsuleUvProfile::Uniform,
-    };
-    let mut player = commands.spawn(MaterialMesh2dBundle {
-        mesh: meshes.add(capsule.into()).into(),
-        material: materials.add(ColorMaterial::from(Color::GRAY)),
-        transform: Transform::from_translation(Vec3::new(
-            world.player_position[0],
-            world.player_position[1],
-            0.0,
-        )),
-        ..default()
-    });
-    player.insert(GameObject);
-    player.insert(RigidBodyId(physics_environment.player_handle()));
+    let (physics_environment, rigid_body_handles) = Environment::from_world(&world);
 
-    for object_and_transform in world.objects.iter() {
+    let player_positions =
+        std::iter::once(world.player_position).chain(world.other_players.iter().copied());
+    for (index, (position, player_handle)) in player_positions
+        .zip(physics_environment.player_handles())
+        .enumerate()
+    {
+        let mut player = commands.spawn(MaterialMesh2dBundle {
+            mesh: meshes.add(world.player_shape.mesh()).into(),
+            material: materials.add(ColorMaterial::from(player_color(index))),
+            transform: Transform::from_translation(Vec3::new(position[0], position[1], 0.0)),
+            ..default()
+        });
+        player.insert(GameObject);
+        player.insert(RigidBodyId(player_handle));
+    }
+
+    for (object_and_transform, rigid_body_handle) in world.objects.iter().zip(rigid_body_handles) {
         let object = &object_and_transform.object;
+        if !object.visible() {
+            continue;
+        }
         let transform = object_and_transform.transform();
-        let rigid_body_handle = physics_environment.add_object(object_and_transform);
-        match object {
-            WorldObject::Block { fixed } => {
-                let color = if *fixed {
-                    Color::BLACK
-                } else {
-                    Color::DARK_GRAY
-                };
-                let mut block = commands.spawn(MaterialMesh2dBundle {
-                    mesh: meshes
-                        .add(Mesh::from(bevy::prelude::shape::Quad::new(Vec2::ONE)))
-                        .into(),
-                    material: materials.add(ColorMaterial::from(color)),
-                    transform,
-                    ..default()
-                });
-                block.insert(GameObject);
-                if let Some(rigid_body_handle) = rigid_body_handle {
-                    block.insert(RigidBodyId(rigid_body_handle));
-                }
-            }
-            WorldObject::Goal => {
-                commands
-                    .spawn(MaterialMesh2dBundle {
-                        mesh: meshes
-                            .add(Mesh::from(bevy::prelude::shape::Quad::new(Vec2::ONE)))
-                            .into(),
-                        material: materials
-                            .add(ColorMaterial::from(Color::rgba(0.0, 1.0, 0.0, 0.5))),
-                        transform,
-                        ..default()
-                    })
-                    .insert(GameObject);
-            }
+        let mut entity = commands.spawn(MaterialMesh2dBundle {
+            mesh: meshes.add(object.mesh()).into(),
+            material: materials.add(ColorMaterial::from(object_and_transform.color())),
+            transform,
+            ..default()
+        });
+        entity.insert(GameObject);
+        if let Some(rigid_body_handle) = rigid_body_handle {
+            entity.insert(RigidBodyId(rigid_body_handle));
         }
     }
 
@@ -90,7 +71,7 @@ fn setup_game(
 
 fn game_ui_system(
     mut next_state: ResMut<NextState<AppState>>,
-    game_state: Res<GameState>,
+    mut game_state: ResMut<GameState>,
     mut contexts: EguiContexts,
 ) {
     egui::Window::new("Game").show(contexts.ctx_mut(), |ui| {
@@ -104,10 +85,52 @@ fn game_ui_system(
             }
         });
         ui.add_space(5.0);
+        let capabilities = game_state.physics_environment.capabilities();
+        let mut controls = "Controls: A/D to move, W to jump".to_string();
+        if capabilities.down {
+            controls += ", S to fall faster";
+        }
+        if capabilities.dash {
+            controls += ", Shift to dash";
+        }
+        if capabilities.grab {
+            controls += ", hold E to carry blocks";
+        }
+        if capabilities.air_jumps > 0 {
+            controls += ", W in the air to jump again";
+        }
+        if game_state.physics_environment.has_ladders() {
+            controls += ", W/S to climb ladders";
+        }
+        ui.label(controls);
+        ui.add_space(5.0);
         ui.label(format!("Steps: {}", game_state.steps));
+        let environment = &game_state.physics_environment;
+        if let (Some(stamina), Some(energy)) = (environment.stamina(), environment.energy()) {
+            ui.add(
+                egui::ProgressBar::new(energy / stamina.max_energy)
+                    .desired_width(200.0)
+                    .text(format!("Energy: {energy:.2}")),
+            );
+        }
+        if game_state.physics_environment.win_condition() == WinCondition::CollectCoins {
+            ui.label(format!(
+                "Coins remaining: {}",
+                game_state.physics_environment.coins_remaining()
+            ));
+        }
         if game_state.physics_environment.won() {
             ui.add_space(5.0);
             ui.label("Won!");
+        } else if game_state.physics_environment.out_of_bounds() {
+            ui.add_space(5.0);
+            ui.label("Out of bounds!");
+        } else if game_state.physics_environment.dead() {
+            ui.add_space(5.0);
+            ui.label("You died!");
+            if ui.button("Respawn (R)").clicked() {
+                game_state.respawn();
+            }
         }
     });
 }
@@ -115,9 +138,18 @@ fn game_ui_system(
 fn update_game(
     input: Res<Input<KeyCode>>,
     mut game_state: ResMut<GameState>,
-    mut rigid_bodies: Query<(&mut Transform, &RigidBodyId)>,
+    mut rigid_bodies: Query<(&mut Transform, &mut Visibility, &RigidBodyId)>,
     mut camera: Query<&mut Transform, (With<Camera>, Without<RigidBodyId>)>,
 ) {
+    // The game is paused after dying, until the player respawns.
+    if game_state.physics_environment.dead() {
+        if input.just_pressed(KeyCode::R) {
+            game_state.respawn();
+        } else {
+            return;
+        }
+    }
+
     let GameState {
         physics_environment,
         steps,
@@ -127,15 +159,24 @@ fn update_game(
         left: input.pressed(KeyCode::A),
         right: input.pressed(KeyCode::D),
         up: input.pressed(KeyCode::W),
+        down: input.pressed(KeyCode::S),
+        dash: input.pressed(KeyCode::LShift),
+        grab: input.pressed(KeyCode::E),
     };
     physics_environment.step(player_move);
     *steps += 1;
 
-    for (mut transform, RigidBodyId(rigid_body_handle)) in rigid_bodies.iter_mut() {
+    for (mut transform, mut visibility, RigidBodyId(rigid_body_handle)) in rigid_bodies.iter_mut() {
         let rigid_body = &physics_environment.rigid_body_set()[*rigid_body_handle];
         transform.translation.x = rigid_body.translation().x / BEVY_TO_PHYSICS_SCALE;
         transform.translation.y = rigid_body.translation().y / BEVY_TO_PHYSICS_SCALE;
         transform.rotation = Quat::from_rotation_z(rigid_body.rotation().angle());
+        // Collected coins are disabled.
+        *visibility = if rigid_body.is_enabled() {
+            Visibility::Inherited
+        } else {
+            Visibility::Hidden
+        };
     }
 
     let player_translation =
@@ -145,6 +186,29 @@ fn update_game(
     camera_transform.translation.y = player_translation.y / BEVY_TO_PHYSICS_SCALE;
 }
 
+// Draws the text objects over the world, their shapes aren't shown.
+fn draw_text_objects(
+    world: Res<World>,
+    mut contexts: EguiContexts,
+    camera: Query<&Transform, With<Camera>>,
+    windows: Query<&Window, With<PrimaryWindow>>,
+) {
+    let mut overlay = OverlayPainter::default();
+    for object_and_transform in &world.objects {
+        if let WorldObject::Text { text } = &object_and_transform.object {
+            let [x, y, _] = object_and_transform.position;
+            overlay.text([x, y], text.as_str(), Color32::BLACK);
+        }
+    }
+    if let (false, Ok(camera), Ok(window)) = (
+        overlay.is_empty(),
+        camera.get_single(),
+        windows.get_single(),
+    ) {
+        overlay.paint_in_background(contexts.ctx_mut(), "Text objects", camera, window);
+    }
+}
+
 fn cleanup_game(mut commands: Commands, game_objects: Query<Entity, With<GameObject>>) {
     for entity in game_objects.iter() {
         commands.entity(entity).despawn();
@@ -157,6 +221,14 @@ struct GameState {
     steps: usize,
 }
 
+impl GameState {
+    // Restarts the episode from the start of the world.
+    fn respawn(&mut self) {
+        self.physics_environment.reset();
+        self.steps = 0;
+    }
+}
+
 #[derive(Component)]
 struct GameObject;
 
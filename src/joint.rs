@@ -0,0 +1,42 @@
+use serde::{Deserialize, Serialize};
+
+/// How a [`WorldJoint`] constrains the objects it connects.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+pub enum JointKind {
+    /// The anchors stay together while the objects rotate freely around them,
+    /// for pendulums and bridges.
+    Revolute,
+    /// The second anchor slides along `axis` (in the first object's frame) through the first anchor,
+    /// within `limits` (in world units) if there are limits. The objects can't rotate relative to each other.
+    Prismatic {
+        axis: [f32; 2],
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        limits: Option<[f32; 2]>,
+    },
+    /// Keeps the anchors at most `length` world units apart, for swinging platforms.
+    Rope { length: f32 },
+}
+
+impl JointKind {
+    pub fn name(&self) -> &'static str {
+        match self {
+            JointKind::Revolute => "Revolute",
+            JointKind::Prismatic { .. } => "Prismatic",
+            JointKind::Rope { .. } => "Rope",
+        }
+    }
+}
+
+/// A joint connecting two objects of a [`World`](crate::World).
+///
+/// Objects without a rigid body, like fixed blocks, are connected through a fixed rigid body at their position.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct WorldJoint {
+    /// Indices of the connected objects in [`World::objects`](crate::World::objects).
+    /// Joints with invalid indices, or connecting an object to itself, are ignored.
+    pub objects: [usize; 2],
+    /// The point each object is attached at, relative to the object's position
+    /// along its rotated axes, in world units.
+    pub anchors: [[f32; 2]; 2],
+    pub kind: JointKind,
+}
@@ -8,19 +8,22 @@
 //! use physics_reinforcement_learning_environment::{
 //!     Move, World, Environment,
 //!     egui::{self, Ui}, Sender, Receiver,
-//!     Agent, TrainingDetails, Algorithm, run
+//!     Agent, Policy, TrainingDetails, Algorithm, run
 //! };
 //! #[derive(Clone)]
 //! pub struct SingleMoveAgent {
 //!     player_move: Move,
 //! }
 //!
-//! // We implement the Agent trait for our agent.
-//! impl Agent for SingleMoveAgent {
+//! // We implement the Policy trait for our agent, choosing its moves.
+//! impl Policy for SingleMoveAgent {
 //!     fn get_move(&mut self, _environment: &Environment) -> Move {
 //!         self.player_move
 //!     }
+//! }
 //!
+//! // We implement the Agent trait for our agent, which shows it in the app.
+//! impl Agent for SingleMoveAgent {
 //!     // Show the agent details UI. Uses egui for the UI.
 //!     fn details_ui(&self, ui: &mut Ui, environment: &Environment) {
 //!         ui.label(format!("Move: {:?}", self.player_move));
@@ -85,37 +88,27 @@
 //!     // The application doesn't stop the training thread - it's your responsibillity
 //!     // to return if you detect that the receiver is dropped.
 //!     fn train(&self, world: World, sender: Sender<SingleMoveMessage>) {
-//!         for left in [false, true] {
-//!             for right in [false, true] {
-//!                 for up in [false, true] {
-//!                     let player_move = Move {
-//!                         left,
-//!                         right,
-//!                         up
-//!                     };
-//!
-//!                     let (mut environment, _) = Environment::from_world(&world);
-//!                     let mut score = f32::INFINITY;
-//!                     for _ in 0..self.number_of_steps {
-//!                         environment.step(player_move);
-//!                         score = score.min(environment.distance_to_goals().unwrap());
-//!                         
-//!                         if environment.won() {
-//!                             break;
-//!                         }
-//!                     }
-//!
-//!                     if sender
-//!                         .send((
-//!                             SingleMoveAgent { player_move },
-//!                             score
-//!                         ))
-//!                         .is_err() {
-//!                         // Can't send a message, so we return.
-//!                         return;
-//!                     }
+//!         for player_move in Move::BASIC {
+//!             let (mut environment, _) = Environment::from_world(&world);
+//!             let mut score = f32::INFINITY;
+//!             for _ in 0..self.number_of_steps {
+//!                 environment.step(player_move);
+//!                 score = score.min(environment.distance_to_goals().unwrap());
+//!
+//!                 if environment.won() {
+//!                     break;
 //!                 }
 //!             }
+//!
+//!             if sender
+//!                 .send((
+//!                     SingleMoveAgent { player_move },
+//!                     score
+//!                 ))
+//!                 .is_err() {
+//!                 // Can't send a message, so we return.
+//!                 return;
+//!             }
 //!         }
 //!     }
 //!
@@ -141,34 +134,141 @@
 //!
 //! run::<SingleMoveAgent, SingleMoveMessage, SingleMoveTrainingDetails, SingleMoveAlgorithm>();
 //! ```
+//!
+//! # Headless use
+//! The editor and the training UI use Bevy, egui and rfd, which are behind the default `gui` feature.
+//! Without it, only the environment is built, for example for training on a server:
+//! ```toml
+//! physics_reinforcement_learning_environment = { version = "0.1", default-features = false }
+//! ```
+//! ```no_run
+//! use physics_reinforcement_learning_environment::{Environment, Move, World};
+//!
+//! let world = World::load("level.json").unwrap();
+//! let (mut environment, _) = Environment::from_world(&world);
+//! while !environment.won() {
+//!     environment.step(Move::BASIC[0]);
+//! }
+//! ```
 
 #![allow(clippy::too_many_arguments, clippy::type_complexity)]
 
+#[cfg(feature = "gui")]
 mod algorithm;
+mod animation;
+mod binary;
+mod calibration;
 mod common;
+mod diff;
+#[cfg(feature = "gui")]
 mod editor;
+mod error;
+mod events;
+#[cfg(feature = "gui")]
 mod game;
+mod joint;
+mod material;
+mod migration;
+#[cfg(feature = "gui")]
+mod overlay;
+mod path_distance;
+mod policy;
+#[cfg(feature = "gui")]
+mod polygon;
+mod prefab;
+mod randomization;
+mod regression;
+mod reward;
+mod rollout;
+mod seed;
+mod shaping;
+#[cfg(feature = "gui")]
+mod sparkline;
+mod svg;
+mod tiled;
+#[cfg(feature = "gui")]
 mod train;
+mod trajectory;
+mod validation;
+mod vec_env;
+#[cfg(feature = "gui")]
 use common::AppState;
+#[cfg(feature = "gui")]
 use editor::add_editor_systems;
+#[cfg(feature = "gui")]
 use game::add_game_systems;
+#[cfg(feature = "gui")]
 use train::add_train_systems;
 
+#[cfg(feature = "gui")]
 use bevy::prelude::*;
+#[cfg(feature = "gui")]
 use bevy_egui::EguiPlugin;
 
+#[cfg(feature = "gui")]
 pub use self::algorithm::Agent;
+#[cfg(feature = "gui")]
 pub use self::algorithm::Algorithm;
+#[cfg(feature = "gui")]
 pub use self::algorithm::TrainingDetails;
+pub use self::animation::{Animation, Keyframe, LoopMode};
+pub use self::calibration::{jump_distance, jump_height, CalibrationTarget};
+pub use self::common::ActionNoise;
+pub use self::common::AdaptiveStep;
+pub use self::common::Capabilities;
+pub use self::common::ContactInfo;
+pub use self::common::Disturbance;
+pub use self::common::EnvConfig;
+pub use self::common::EnvInfo;
+pub use self::common::EnvSnapshot;
 pub use self::common::Environment;
 pub use self::common::Move;
 pub use self::common::ObjectAndTransform;
+pub use self::common::Observation;
+pub use self::common::ObservationConfig;
+pub use self::common::PhysicsConfig;
+pub use self::common::PlayerShape;
+pub use self::common::SolverConfig;
+pub use self::common::Stamina;
+pub use self::common::StepInfo;
+pub use self::common::StepResult;
+pub use self::common::WinCondition;
 pub use self::common::World;
 pub use self::common::WorldObject;
+pub use self::diff::WorldChange;
+pub use self::error::{Error, Result};
+pub use self::events::{Bounds, BoundsEvent, ContactEvent, GoalEvent};
+pub use self::joint::{JointKind, WorldJoint};
+pub use self::material::SurfaceMaterial;
+pub use self::migration::{Migration, WORLD_VERSION};
+#[cfg(feature = "gui")]
+pub use self::overlay::{OverlayPainter, OverlayShape};
+pub use self::path_distance::DistanceField;
+#[cfg(feature = "gui")]
+pub use self::policy::PolicyInspector;
+pub use self::policy::{Discretization, Policy, PolicyTable};
+pub use self::prefab::Prefab;
+pub use self::randomization::Randomization;
+pub use self::regression::{
+    regression_cases, run_regression_suite, RegressionCase, RegressionResult,
+};
+pub use self::reward::{DenseReward, RewardFn, Transition};
+pub use self::rollout::RolloutResult;
+pub use self::seed::{derive_seed, RunSeed, Seeds};
+pub use self::shaping::{
+    distance_potential, distance_shaping, potential_shaping, PotentialShaping, ShapedReward,
+};
+#[cfg(feature = "gui")]
+pub use self::sparkline::{sparkline_path, sparkline_ui};
+pub use self::trajectory::{Trajectory, TrajectoryStep};
+pub use self::validation::ValidationIssue;
+pub use self::vec_env::{VecEnvironment, VecStepResult};
+#[cfg(feature = "gui")]
 pub use bevy_egui::egui;
 pub use crossbeam::channel::{Receiver, Sender};
 pub use rapier2d;
 
+#[cfg(feature = "gui")]
 pub fn run<
     AgentType: Agent,
     Message: Send + Sync + 'static,
@@ -178,8 +278,13 @@ pub fn run<
     let mut app = App::new();
     app.insert_resource(ClearColor(Color::WHITE))
         .init_resource::<World>()
+        .init_resource::<Seeds>()
         .add_state::<AppState>()
-        .add_plugins(DefaultPlugins)
+        .add_plugins(DefaultPlugins.set(WindowPlugin {
+            // The editor asks to save unsaved changes before closing.
+            close_when_requested: false,
+            ..default()
+        }))
         .add_plugin(EguiPlugin)
         .add_startup_system(setup_graphics);
     add_editor_systems(&mut app);
@@ -188,6 +293,7 @@ pub fn run<
     app.run();
 }
 
+#[cfg(feature = "gui")]
 fn setup_graphics(mut commands: Commands) {
     commands.spawn(Camera2dBundle::default());
 }
@@ -2,7 +2,8 @@
 
 use physics_reinforcement_learning_environment::{
     egui::{self, DragValue, RichText, Ui},
-    Agent, Algorithm, Environment, Move, Receiver, Sender, TrainingDetails, World,
+    sparkline_path, sparkline_ui, Agent, Algorithm, EnvConfig, Environment, Move, Policy, Receiver,
+    Sender, TrainingDetails, World,
 };
 use rand::prelude::*;
 use std::cmp::Ordering;
@@ -16,13 +17,14 @@ fn main() {
     >();
 }
 
-#[derive(PartialEq, Clone, Copy)]
+#[derive(PartialEq, Clone)]
 pub struct GeneticAlgorithm {
     number_of_steps: usize,
     number_of_agents: usize,
-    repeat_move: usize,
     mutation_rate: f32,
     keep_best: bool,
+    env_config: EnvConfig,
+    seed: u64,
 }
 
 impl Default for GeneticAlgorithm {
@@ -30,41 +32,66 @@ impl Default for GeneticAlgorithm {
         GeneticAlgorithm {
             number_of_steps: 1000,
             number_of_agents: 1000,
-            repeat_move: 20,
             mutation_rate: 0.1,
             keep_best: false,
+            // Each move is held for 20 physics steps.
+            env_config: EnvConfig {
+                decision_interval: 20,
+                ..EnvConfig::default()
+            },
+            seed: 0,
         }
     }
 }
 
+// The score of an agent at a step, lower is better: the distance to the goals, or for worlds without goals
+// (like worlds won by collecting coins) one more than the number of coins remaining. Winning scores 0.
+fn environment_score(environment: &Environment) -> f32 {
+    if environment.won() {
+        return 0.0;
+    }
+    environment
+        .path_distance_to_goals()
+        .unwrap_or_else(|_| (environment.coins_remaining() + 1) as f32)
+}
+
+impl GeneticAlgorithm {
+    // The number of moves of an agent, each held for the decision interval.
+    // The remaining physics steps are simulated without a move when scoring the agent.
+    fn number_of_moves(&self) -> usize {
+        self.number_of_steps / self.env_config.decision_interval.max(1)
+    }
+}
+
 impl Algorithm<GeneticAgent, GeneticMessage, GeneticTrainingDetails> for GeneticAlgorithm {
     fn train(&self, world: World, sender: Sender<GeneticMessage>) {
-        let mut rng = thread_rng();
+        let mut rng = StdRng::seed_from_u64(self.seed);
 
-        let agent_score = |agent: &Vec<Move>| {
-            let (mut environment, _) = Environment::from_world(&world);
+        let (mut environment, _) =
+            Environment::from_world_with_config(&world, self.env_config.clone());
+        let decision_interval = self.env_config.decision_interval.max(1);
+        // The score is sampled after every decision instead of after every physics step, as the moves
+        // are held with the environment's decision interval. This matches the scores to the sparklines and
+        // the visualization, which also only see the decisions, but a player passing close to a goal
+        // between two decisions isn't credited for it, so scores can be slightly higher than when
+        // every physics step was sampled.
+        let mut agent_score = |agent: &Vec<Move>| {
+            environment.reset();
             let mut score = f32::INFINITY;
             for player_move in agent.iter() {
-                for _ in 0..self.repeat_move {
-                    environment.step(*player_move);
-                    score = score.min(environment.distance_to_goals().unwrap());
+                environment.step(*player_move);
+                score = score.min(environment_score(&environment));
 
-                    if environment.won() {
-                        break;
-                    }
-                }
-
-                if environment.won() {
+                if environment.done() {
                     break;
                 }
             }
-            for _ in 0..self.number_of_steps % self.repeat_move {
+            let remaining_steps = self.number_of_steps % decision_interval;
+            if remaining_steps > 0 && !environment.done() {
+                environment.set_decision_interval(remaining_steps);
                 environment.step(Move::default());
-                score = score.min(environment.distance_to_goals().unwrap());
-
-                if environment.won() {
-                    break;
-                }
+                score = score.min(environment_score(&environment));
+                environment.set_decision_interval(decision_interval);
             }
             score
         };
@@ -72,11 +99,14 @@ impl Algorithm<GeneticAgent, GeneticMessage, GeneticTrainingDetails> for Genetic
         let mut generation = vec![];
         for _ in 0..self.number_of_agents {
             let mut agent = vec![];
-            for _ in 0..self.number_of_steps / self.repeat_move {
+            for _ in 0..self.number_of_moves() {
                 agent.push(Move {
                     left: rng.gen(),
                     right: rng.gen(),
                     up: rng.gen(),
+                    down: world.uses_down() && rng.gen(),
+                    dash: world.capabilities.dash && rng.gen(),
+                    grab: world.capabilities.grab && rng.gen(),
                 });
             }
 
@@ -109,17 +139,13 @@ impl Algorithm<GeneticAgent, GeneticMessage, GeneticTrainingDetails> for Genetic
                 })
                 .unwrap()
                 .0;
-            if sender
-                .send((
-                    min_agent.0,
-                    GeneticAgent {
-                        moves: min_agent.1.clone(),
-                        curr: 0,
-                        repeat_move: self.repeat_move,
-                    },
-                ))
-                .is_err()
-            {
+            let agent = GeneticAgent {
+                moves: min_agent.1.clone(),
+                curr: 0,
+                env_config: self.env_config.clone(),
+            };
+            let path = sparkline_path(&world, &agent, self.number_of_moves());
+            if sender.send((min_agent.0, agent, path)).is_err() {
                 return;
             }
 
@@ -138,7 +164,7 @@ impl Algorithm<GeneticAgent, GeneticMessage, GeneticTrainingDetails> for Genetic
                 let parent2 = &parents.next().unwrap().1;
 
                 let mut agent = vec![];
-                for i in 0..self.number_of_steps / self.repeat_move {
+                for i in 0..self.number_of_moves() {
                     if rng.gen() {
                         agent.push(parent1[i]);
                     } else {
@@ -155,6 +181,15 @@ impl Algorithm<GeneticAgent, GeneticMessage, GeneticTrainingDetails> for Genetic
                     if rng.gen::<f32>() < self.mutation_rate {
                         player_move.up = rng.gen();
                     }
+                    if world.uses_down() && rng.gen::<f32>() < self.mutation_rate {
+                        player_move.down = rng.gen();
+                    }
+                    if world.capabilities.dash && rng.gen::<f32>() < self.mutation_rate {
+                        player_move.dash = rng.gen();
+                    }
+                    if world.capabilities.grab && rng.gen::<f32>() < self.mutation_rate {
+                        player_move.grab = rng.gen();
+                    }
                 }
                 new_generation.push((agent_score(&agent), agent));
             }
@@ -172,9 +207,6 @@ impl Algorithm<GeneticAgent, GeneticMessage, GeneticTrainingDetails> for Genetic
                 ui.label("Number of agents: ");
                 ui.add(DragValue::new(&mut self.number_of_agents).clamp_range(10..=1000));
                 ui.end_row();
-                ui.label("Repeat move: ");
-                ui.add(DragValue::new(&mut self.repeat_move).clamp_range(1..=100));
-                ui.end_row();
                 ui.label("Mutation rate: ");
                 ui.add(DragValue::new(&mut self.mutation_rate).clamp_range(0.0..=1.0));
                 ui.end_row();
@@ -184,6 +216,21 @@ impl Algorithm<GeneticAgent, GeneticMessage, GeneticTrainingDetails> for Genetic
             });
     }
 
+    fn env_config(&self) -> EnvConfig {
+        self.env_config.clone()
+    }
+
+    fn env_config_mut(&mut self) -> Option<&mut EnvConfig> {
+        Some(&mut self.env_config)
+    }
+
+    fn set_seed(&mut self, seed: u64) {
+        self.seed = seed;
+        if let Some(action_noise) = &mut self.env_config.action_noise {
+            action_noise.seed = seed;
+        }
+    }
+
     fn training_details_receiver(
         &self,
         _world: &World,
@@ -197,7 +244,7 @@ impl Algorithm<GeneticAgent, GeneticMessage, GeneticTrainingDetails> for Genetic
 }
 
 pub struct GeneticTrainingDetails {
-    agents: Vec<(f32, GeneticAgent)>,
+    agents: Vec<GeneticMessage>,
     receiver: Receiver<GeneticMessage>,
 }
 
@@ -208,8 +255,9 @@ impl TrainingDetails<GeneticAgent, GeneticMessage> for GeneticTrainingDetails {
 
     fn details_ui(&mut self, ui: &mut Ui) -> Option<&GeneticAgent> {
         let mut selected_agent = None;
-        for (score, agent) in self.agents.iter() {
+        for (score, agent, path) in self.agents.iter() {
             ui.horizontal(|ui| {
+                sparkline_ui(ui, path);
                 ui.label(format!("Score {}", score));
                 if ui.button("Visualize agent").clicked() {
                     selected_agent = Some(agent);
@@ -220,22 +268,38 @@ impl TrainingDetails<GeneticAgent, GeneticMessage> for GeneticTrainingDetails {
     }
 }
 
-type GeneticMessage = (f32, GeneticAgent);
+// The score, the agent and its sparkline path.
+type GeneticMessage = (f32, GeneticAgent, Vec<[f32; 2]>);
 
 #[derive(Clone)]
 pub struct GeneticAgent {
     moves: Vec<Move>,
     curr: usize,
-    repeat_move: usize,
+    env_config: EnvConfig,
+}
+
+impl Policy for GeneticAgent {
+    fn get_move(&mut self, _environment: &Environment) -> Move {
+        if self.curr < self.moves.len() {
+            let player_move = self.moves[self.curr];
+            self.curr += 1;
+            player_move
+        } else {
+            Move::default()
+        }
+    }
 }
 
 impl Agent for GeneticAgent {
     fn details_ui(&self, ui: &mut Ui, _environment: &Environment) {
-        ui.label(format!("Repeat move: {}", self.repeat_move));
+        ui.label(format!(
+            "Decision interval: {}",
+            self.env_config.decision_interval
+        ));
         ui.add_space(10.0);
 
-        if self.curr / self.repeat_move < self.moves.len() {
-            let index = self.curr / self.repeat_move;
+        if self.curr < self.moves.len() {
+            let index = self.curr;
             let mut text = format!("{}. ", index + 1);
             let player_move = self.moves[index];
             if player_move.up {
@@ -247,6 +311,15 @@ impl Agent for GeneticAgent {
             if player_move.right {
                 text += " RIGHT ";
             }
+            if player_move.down {
+                text += " DOWN ";
+            }
+            if player_move.dash {
+                text += " DASH ";
+            }
+            if player_move.grab {
+                text += " GRAB ";
+            }
             ui.label(RichText::new(text).strong());
         }
         ui.add_space(10.0);
@@ -263,8 +336,17 @@ impl Agent for GeneticAgent {
                 if player_move.right {
                     text += " RIGHT ";
                 }
+                if player_move.down {
+                    text += " DOWN ";
+                }
+                if player_move.dash {
+                    text += " DASH ";
+                }
+                if player_move.grab {
+                    text += " GRAB ";
+                }
 
-                if self.curr / self.repeat_move == index {
+                if self.curr == index {
                     ui.label(RichText::new(text).strong());
                 } else {
                     ui.label(text);
@@ -273,13 +355,7 @@ impl Agent for GeneticAgent {
         }
     }
 
-    fn get_move(&mut self, _environment: &Environment) -> Move {
-        if self.curr / self.repeat_move < self.moves.len() {
-            let player_move = self.moves[self.curr / self.repeat_move];
-            self.curr += 1;
-            player_move
-        } else {
-            Move::default()
-        }
+    fn env_config(&self) -> EnvConfig {
+        self.env_config.clone()
     }
 }
@@ -0,0 +1,72 @@
+#[cfg(feature = "gui")]
+use bevy::prelude::Color;
+use serde::{Deserialize, Serialize};
+
+/// A surface preset for blocks, so levels don't need hand tuned friction and restitution.
+///
+/// A block's own friction and restitution take precedence over its material's.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SurfaceMaterial {
+    /// Almost no friction, so the player slides.
+    Ice,
+    /// Very bouncy.
+    Rubber,
+    /// High friction and drag, slowing down the bodies touching it.
+    Mud,
+}
+
+impl SurfaceMaterial {
+    pub const ALL: [SurfaceMaterial; 3] = [
+        SurfaceMaterial::Ice,
+        SurfaceMaterial::Rubber,
+        SurfaceMaterial::Mud,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            SurfaceMaterial::Ice => "Ice",
+            SurfaceMaterial::Rubber => "Rubber",
+            SurfaceMaterial::Mud => "Mud",
+        }
+    }
+
+    pub fn friction(&self) -> f32 {
+        match self {
+            SurfaceMaterial::Ice => 0.02,
+            SurfaceMaterial::Rubber => 1.0,
+            SurfaceMaterial::Mud => 1.5,
+        }
+    }
+
+    pub fn restitution(&self) -> f32 {
+        match self {
+            SurfaceMaterial::Ice => 0.0,
+            SurfaceMaterial::Rubber => 0.9,
+            SurfaceMaterial::Mud => 0.0,
+        }
+    }
+
+    /// Fraction of the velocity lost per second by the dynamic bodies touching the surface.
+    pub fn drag(&self) -> f32 {
+        match self {
+            SurfaceMaterial::Ice | SurfaceMaterial::Rubber => 0.0,
+            SurfaceMaterial::Mud => 5.0,
+        }
+    }
+
+    /// The color of blocks made of the material.
+    #[cfg(feature = "gui")]
+    pub fn color(&self, fixed: bool) -> Color {
+        let color = match self {
+            SurfaceMaterial::Ice => Color::rgb(0.6, 0.85, 1.0),
+            SurfaceMaterial::Rubber => Color::rgb(0.9, 0.3, 0.5),
+            SurfaceMaterial::Mud => Color::rgb(0.4, 0.25, 0.1),
+        };
+        // Movable blocks are darker, like blocks without a material.
+        if fixed {
+            color
+        } else {
+            color * 0.7
+        }
+    }
+}
@@ -0,0 +1,105 @@
+use std::fmt;
+
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+
+use crate::{
+    common::World,
+    error::{Error, Result},
+};
+
+/// The version of the world file format written by [`World::save`], also used by [`Prefab::save`](crate::Prefab::save).
+pub const WORLD_VERSION: u32 = 1;
+
+/// A change made to a world saved in an older format while loading it, see [`World::load_migrated`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Migration {
+    /// The version the migration updates from, to the next version.
+    /// Worlds saved before the format had versions are version 0.
+    pub from: u32,
+    pub description: &'static str,
+}
+
+impl fmt::Display for Migration {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "Version {} to {}: {}",
+            self.from,
+            self.from + 1,
+            self.description
+        )
+    }
+}
+
+// The migrations from each version to the next, in order.
+// Adding a migration should come with incrementing WORLD_VERSION.
+const MIGRATIONS: [(Migration, fn(&mut Value)); 1] = [(
+    Migration {
+        from: 0,
+        description: "Goals saved without properties get the default reward.",
+    },
+    migrate_goal_properties,
+)];
+
+// Worlds saved before goals had properties store goals as "Goal".
+fn migrate_goal_properties(world: &mut Value) {
+    let Some(objects) = world.get_mut("objects").and_then(Value::as_array_mut) else {
+        return;
+    };
+    for object_and_transform in objects {
+        if let Some(object) = object_and_transform.get_mut("object") {
+            if *object == "Goal" {
+                // The missing properties take their defaults.
+                *object = json!({ "Goal": {} });
+            }
+        }
+    }
+}
+
+// Updates the world to the current version, returning the migrations applied.
+pub(crate) fn migrate(world: &mut Value) -> Result<Vec<Migration>> {
+    let version = match world.get("version") {
+        Some(version) => u32::deserialize(version)?,
+        None => 0,
+    };
+    if version > WORLD_VERSION {
+        return Err(Error::WorldVersion {
+            version,
+            supported: WORLD_VERSION,
+        });
+    }
+    let mut applied = vec![];
+    for (migration, apply) in MIGRATIONS {
+        if migration.from >= version {
+            apply(world);
+            applied.push(migration);
+        }
+    }
+    Ok(applied)
+}
+
+// A world saved as RON. The version is stored next to the world,
+// as RON can't be read without the types so migrations can't edit it like JSON.
+#[derive(Serialize, Deserialize)]
+pub(crate) struct RonWorld<W> {
+    pub(crate) version: u32,
+    pub(crate) world: W,
+}
+
+// The world as it's saved, with the version of the format.
+#[derive(Serialize)]
+pub(crate) struct VersionedWorld<'a> {
+    version: u32,
+    #[serde(flatten)]
+    world: &'a World,
+}
+
+impl<'a> VersionedWorld<'a> {
+    pub(crate) fn new(world: &'a World) -> VersionedWorld<'a> {
+        VersionedWorld {
+            version: WORLD_VERSION,
+            world,
+        }
+    }
+}
@@ -0,0 +1,159 @@
+use bevy::prelude::{Transform, Vec2, Window};
+use bevy_egui::egui::{self, Align2, Color32, FontId, Pos2, Stroke};
+
+/// A primitive drawn over the visualized world, in world units.
+#[derive(Clone, Debug, PartialEq)]
+pub enum OverlayShape {
+    Line {
+        from: [f32; 2],
+        to: [f32; 2],
+        width: f32,
+        color: Color32,
+    },
+    Circle {
+        center: [f32; 2],
+        radius: f32,
+        filled: bool,
+        color: Color32,
+    },
+    Text {
+        position: [f32; 2],
+        text: String,
+        color: Color32,
+    },
+}
+
+/// Collects the shapes an agent draws over the visualized world, see [`Agent::draw_overlay`](crate::Agent::draw_overlay).
+/// Positions and sizes are in world units, so they line up with the objects in the world.
+#[derive(Default, Clone, Debug)]
+pub struct OverlayPainter {
+    shapes: Vec<OverlayShape>,
+}
+
+impl OverlayPainter {
+    pub fn line(&mut self, from: [f32; 2], to: [f32; 2], width: f32, color: Color32) {
+        self.shapes.push(OverlayShape::Line {
+            from,
+            to,
+            width,
+            color,
+        });
+    }
+
+    /// Draws connected lines through the points, for example a planned path.
+    pub fn path(&mut self, points: &[[f32; 2]], width: f32, color: Color32) {
+        for segment in points.windows(2) {
+            self.line(segment[0], segment[1], width, color);
+        }
+    }
+
+    pub fn circle(&mut self, center: [f32; 2], radius: f32, color: Color32) {
+        self.shapes.push(OverlayShape::Circle {
+            center,
+            radius,
+            filled: false,
+            color,
+        });
+    }
+
+    pub fn filled_circle(&mut self, center: [f32; 2], radius: f32, color: Color32) {
+        self.shapes.push(OverlayShape::Circle {
+            center,
+            radius,
+            filled: true,
+            color,
+        });
+    }
+
+    /// Draws text centered on the position. The text size doesn't change with the zoom.
+    pub fn text(&mut self, position: [f32; 2], text: impl Into<String>, color: Color32) {
+        self.shapes.push(OverlayShape::Text {
+            position,
+            text: text.into(),
+            color,
+        });
+    }
+
+    pub fn shapes(&self) -> &[OverlayShape] {
+        &self.shapes
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.shapes.is_empty()
+    }
+
+    pub fn clear(&mut self) {
+        self.shapes.clear();
+    }
+
+    // Paints the shapes behind the egui windows, as seen by a 2D camera with the given transform in the window.
+    pub(crate) fn paint_in_background(
+        &self,
+        ctx: &egui::Context,
+        id: &str,
+        camera: &Transform,
+        window: &Window,
+    ) {
+        let painter = ctx.layer_painter(egui::LayerId::new(
+            egui::Order::Background,
+            egui::Id::new(id),
+        ));
+        self.paint(&painter, camera, Vec2::new(window.width(), window.height()));
+    }
+
+    // Paints the shapes as seen by a 2D camera with the given transform in a window of the given size.
+    pub(crate) fn paint(&self, painter: &egui::Painter, camera: &Transform, window_size: Vec2) {
+        let scale = 1.0 / camera.scale.x;
+        let to_screen = |position: [f32; 2]| {
+            Pos2::new(
+                window_size.x / 2.0 + (position[0] - camera.translation.x) * scale,
+                window_size.y / 2.0 - (position[1] - camera.translation.y) * scale,
+            )
+        };
+
+        for shape in &self.shapes {
+            match shape {
+                OverlayShape::Line {
+                    from,
+                    to,
+                    width,
+                    color,
+                } => {
+                    painter.line_segment(
+                        [to_screen(*from), to_screen(*to)],
+                        Stroke::new(width * scale, *color),
+                    );
+                }
+                OverlayShape::Circle {
+                    center,
+                    radius,
+                    filled,
+                    color,
+                } => {
+                    if *filled {
+                        painter.circle_filled(to_screen(*center), radius * scale, *color);
+                    } else {
+                        painter.circle_stroke(
+                            to_screen(*center),
+                            radius * scale,
+                            Stroke::new(1.0, *color),
+                        );
+                    }
+                }
+                OverlayShape::Text {
+                    position,
+                    text,
+                    color,
+                } => {
+                    painter.text(
+                        to_screen(*position),
+                        Align2::CENTER_CENTER,
+                        text,
+                        FontId::default(),
+                        *color,
+                    );
+                }
+            }
+        }
+    }
+}
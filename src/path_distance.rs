@@ -0,0 +1,152 @@
+use std::{cmp::Ordering, collections::BinaryHeap};
+
+/// Geodesic distances to the goals on a grid, so obstacles between the player and a goal are walked around
+/// instead of ignored like in [`Environment::distance_to_goals`](crate::Environment::distance_to_goals).
+///
+/// Created by [`Environment::compute_distance_field`](crate::Environment::compute_distance_field)
+/// from the colliders of the fixed blocks, so moving blocks are ignored.
+/// Distances are in the same units as [`World`](crate::World) positions.
+#[derive(Clone, Debug)]
+pub struct DistanceField {
+    // Bottom left corner of the grid.
+    origin: [f32; 2],
+    cell_size: f32,
+    columns: usize,
+    rows: usize,
+    // Distance from the center of each cell to the goals, row by row from the bottom.
+    distances: Vec<f32>,
+}
+
+// A cell in the Dijkstra queue, ordered by increasing distance.
+struct QueuedCell {
+    distance: f32,
+    index: usize,
+}
+
+impl PartialEq for QueuedCell {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for QueuedCell {}
+
+impl PartialOrd for QueuedCell {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for QueuedCell {
+    fn cmp(&self, other: &Self) -> Ordering {
+        other.distance.total_cmp(&self.distance)
+    }
+}
+
+impl DistanceField {
+    // Runs Dijkstra's algorithm from the goal cells through the cells which aren't blocked,
+    // with moves to the 8 neighbouring cells.
+    pub(crate) fn compute(
+        origin: [f32; 2],
+        cell_size: f32,
+        columns: usize,
+        rows: usize,
+        blocked: &[bool],
+        goals: &[bool],
+    ) -> DistanceField {
+        let mut distances = vec![f32::INFINITY; columns * rows];
+        let mut queue = BinaryHeap::new();
+        for (index, _) in goals.iter().enumerate().filter(|(_, goal)| **goal) {
+            distances[index] = 0.0;
+            queue.push(QueuedCell {
+                distance: 0.0,
+                index,
+            });
+        }
+
+        while let Some(QueuedCell { distance, index }) = queue.pop() {
+            if distance > distances[index] {
+                continue;
+            }
+            let (column, row) = (index % columns, index / columns);
+            for (dx, dy) in [
+                (-1, -1),
+                (0, -1),
+                (1, -1),
+                (-1, 0),
+                (1, 0),
+                (-1, 1),
+                (0, 1),
+                (1, 1),
+            ] {
+                let (Some(neighbour_column), Some(neighbour_row)) = (
+                    column
+                        .checked_add_signed(dx)
+                        .filter(|column| *column < columns),
+                    row.checked_add_signed(dy).filter(|row| *row < rows),
+                ) else {
+                    continue;
+                };
+                let neighbour = neighbour_row * columns + neighbour_column;
+                if blocked[neighbour] {
+                    continue;
+                }
+                let step = if dx != 0 && dy != 0 {
+                    std::f32::consts::SQRT_2
+                } else {
+                    1.0
+                };
+                let neighbour_distance = distance + step * cell_size;
+                if neighbour_distance < distances[neighbour] {
+                    distances[neighbour] = neighbour_distance;
+                    queue.push(QueuedCell {
+                        distance: neighbour_distance,
+                        index: neighbour,
+                    });
+                }
+            }
+        }
+
+        DistanceField {
+            origin,
+            cell_size,
+            columns,
+            rows,
+            distances,
+        }
+    }
+
+    pub fn cell_size(&self) -> f32 {
+        self.cell_size
+    }
+
+    /// The distance from the position to the goals along the grid,
+    /// or infinity if the goals can't be reached from the position or the position is outside the grid.
+    pub fn distance(&self, position: [f32; 2]) -> f32 {
+        let column = ((position[0] - self.origin[0]) / self.cell_size).floor();
+        let row = ((position[1] - self.origin[1]) / self.cell_size).floor();
+        if column < 0.0 || row < 0.0 {
+            return f32::INFINITY;
+        }
+        let (column, row) = (column as usize, row as usize);
+
+        // The player's center can be in a blocked cell while touching an obstacle,
+        // so the neighbouring cells are also considered.
+        let mut distance = f32::INFINITY;
+        for neighbour_row in row.saturating_sub(1)..=(row + 1).min(self.rows.saturating_sub(1)) {
+            for neighbour_column in
+                column.saturating_sub(1)..=(column + 1).min(self.columns.saturating_sub(1))
+            {
+                let center = [
+                    self.origin[0] + (neighbour_column as f32 + 0.5) * self.cell_size,
+                    self.origin[1] + (neighbour_row as f32 + 0.5) * self.cell_size,
+                ];
+                let offset =
+                    ((position[0] - center[0]).powi(2) + (position[1] - center[1]).powi(2)).sqrt();
+                distance = distance
+                    .min(self.distances[neighbour_row * self.columns + neighbour_column] + offset);
+            }
+        }
+        distance
+    }
+}
@@ -0,0 +1,349 @@
+use std::{fs, path::Path};
+
+#[cfg(feature = "gui")]
+use bevy_egui::egui::{self, DragValue, RichText, Ui};
+use serde::{Deserialize, Serialize};
+
+#[cfg(feature = "gui")]
+use crate::{algorithm::Agent, common::EnvConfig};
+use crate::{
+    common::{Environment, Move, ObservationConfig},
+    error::{Error, Result},
+};
+
+/// Chooses the moves of the first player, for example to play it with [`Environment::rollout`].
+///
+/// Unlike `Agent`, this is available without the `gui` feature.
+pub trait Policy {
+    fn get_move(&mut self, environment: &Environment) -> Move;
+}
+
+/// Splits the range `min..max` of one observation value into `bins` equal bins.
+/// Values outside the range are put in the first or last bin.
+/// Zero bins are treated as one bin, like [`PolicyTable::new`] does.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq)]
+pub struct Discretization {
+    pub min: f32,
+    pub max: f32,
+    pub bins: usize,
+}
+
+impl Discretization {
+    pub fn bin(&self, value: f32) -> usize {
+        let bins = self.bins.max(1);
+        let fraction = (value - self.min) / (self.max - self.min);
+        if fraction.is_nan() {
+            0
+        } else {
+            ((fraction * bins as f32) as usize).min(bins - 1)
+        }
+    }
+
+    /// The range of values covered by the bin.
+    pub fn range(&self, bin: usize) -> (f32, f32) {
+        let width = (self.max - self.min) / self.bins.max(1) as f32;
+        (
+            self.min + width * bin as f32,
+            self.min + width * (bin + 1) as f32,
+        )
+    }
+}
+
+/// A policy which maps a discretized observation (a bin) to a move.
+///
+/// Any agent which can be expressed as a table (for example tabular Q-learning)
+/// can be exported to this format with [`Agent::policy_table`],
+/// so policies from different algorithms can be compared, inspected and edited by hand.
+/// The table is itself an [`Agent`].
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct PolicyTable {
+    observation: ObservationConfig,
+    dimensions: Vec<Discretization>,
+    actions: Vec<Move>,
+}
+
+impl PolicyTable {
+    /// Creates a table playing the default move in every bin.
+    /// `dimensions` has one entry per value returned by `observation`.
+    pub fn new(observation: ObservationConfig, dimensions: Vec<Discretization>) -> PolicyTable {
+        let bin_count = dimensions
+            .iter()
+            .map(|dimension| dimension.bins.max(1))
+            .product();
+        let dimensions = dimensions
+            .into_iter()
+            .map(|dimension| Discretization {
+                bins: dimension.bins.max(1),
+                ..dimension
+            })
+            .collect();
+        PolicyTable {
+            observation,
+            dimensions,
+            actions: vec![Move::default(); bin_count],
+        }
+    }
+
+    /// Reads a table from a JSON file.
+    pub fn load(path: impl AsRef<Path>) -> Result<PolicyTable> {
+        let path = path.as_ref();
+        let contents = fs::read_to_string(path).map_err(|source| Error::PolicyIo {
+            path: path.to_path_buf(),
+            source,
+        })?;
+        PolicyTable::from_json(&contents)
+    }
+
+    /// Writes the table to a JSON file.
+    pub fn save(&self, path: impl AsRef<Path>) -> Result<()> {
+        let path = path.as_ref();
+        fs::write(path, self.to_json()?).map_err(|source| Error::PolicyIo {
+            path: path.to_path_buf(),
+            source,
+        })
+    }
+
+    pub fn from_json(json: &str) -> Result<PolicyTable> {
+        let table: PolicyTable = serde_json::from_str(json).map_err(Error::PolicyFormat)?;
+        let bin_count: usize = table
+            .dimensions
+            .iter()
+            .map(|dimension| dimension.bins)
+            .product();
+        if table.dimensions.iter().any(|dimension| dimension.bins == 0)
+            || table.actions.len() != bin_count
+        {
+            return Err(Error::PolicyFormat(serde::de::Error::custom(
+                "the number of actions doesn't match the number of bins",
+            )));
+        }
+        Ok(table)
+    }
+
+    pub fn to_json(&self) -> Result<String> {
+        serde_json::to_string(self).map_err(Error::PolicyFormat)
+    }
+
+    pub fn observation(&self) -> ObservationConfig {
+        self.observation
+    }
+
+    pub fn dimensions(&self) -> &[Discretization] {
+        &self.dimensions
+    }
+
+    pub fn bin_count(&self) -> usize {
+        self.actions.len()
+    }
+
+    /// The bin containing the observation.
+    /// Returns [`Error::ObservationSize`] if the observation doesn't have one value per dimension.
+    pub fn bin(&self, observation: &[f32]) -> Result<usize> {
+        if observation.len() != self.dimensions.len() {
+            return Err(Error::ObservationSize {
+                expected: self.dimensions.len(),
+                actual: observation.len(),
+            });
+        }
+        Ok(self.bin_from_coordinates(
+            &self
+                .dimensions
+                .iter()
+                .zip(observation)
+                .map(|(dimension, value)| dimension.bin(*value))
+                .collect::<Vec<_>>(),
+        ))
+    }
+
+    /// The bin index along every dimension.
+    pub fn bin_coordinates(&self, mut bin: usize) -> Vec<usize> {
+        // The last dimension varies the fastest.
+        let mut coordinates = vec![0; self.dimensions.len()];
+        for (coordinate, dimension) in coordinates.iter_mut().zip(&self.dimensions).rev() {
+            *coordinate = bin % dimension.bins;
+            bin /= dimension.bins;
+        }
+        coordinates
+    }
+
+    /// Inverse of [`PolicyTable::bin_coordinates`].
+    pub fn bin_from_coordinates(&self, coordinates: &[usize]) -> usize {
+        coordinates
+            .iter()
+            .zip(&self.dimensions)
+            .fold(0, |bin, (coordinate, dimension)| {
+                bin * dimension.bins + coordinate.min(&(dimension.bins - 1))
+            })
+    }
+
+    pub fn action(&self, bin: usize) -> Move {
+        self.actions[bin]
+    }
+
+    pub fn set_action(&mut self, bin: usize, player_move: Move) {
+        self.actions[bin] = player_move;
+    }
+
+    /// The bin the environment is currently in.
+    pub fn current_bin(&self, environment: &Environment) -> Result<usize> {
+        self.bin(&environment.observe(&self.observation))
+    }
+}
+
+impl Policy for PolicyTable {
+    fn get_move(&mut self, environment: &Environment) -> Move {
+        self.current_bin(environment)
+            .map_or(Move::default(), |bin| self.action(bin))
+    }
+}
+
+#[cfg(feature = "gui")]
+impl Agent for PolicyTable {
+    fn details_ui(&self, ui: &mut Ui, environment: &Environment) {
+        ui.label(format!("Bins: {}", self.bin_count()));
+        match self.current_bin(environment) {
+            Ok(bin) => {
+                ui.label(format!("Current bin: {:?}", self.bin_coordinates(bin)));
+                ui.label(format!("Move: {}", move_text(self.action(bin))));
+            }
+            Err(error) => {
+                ui.colored_label(egui::Color32::RED, error.to_string());
+            }
+        }
+    }
+
+    fn env_config(&self) -> EnvConfig {
+        EnvConfig {
+            observation: self.observation,
+            ..EnvConfig::default()
+        }
+    }
+
+    fn policy_table(&self) -> Option<PolicyTable> {
+        Some(self.clone())
+    }
+}
+
+/// UI state for browsing and editing a [`PolicyTable`].
+#[cfg(feature = "gui")]
+#[derive(Default)]
+pub struct PolicyInspector {
+    selected: Vec<usize>,
+}
+
+#[cfg(feature = "gui")]
+impl PolicyInspector {
+    /// Shows the bin selection, the selected bin's range and move, and a list of all bins.
+    /// `current_bin` is highlighted, and the selected bin's move can be edited.
+    pub fn ui(&mut self, ui: &mut Ui, table: &mut PolicyTable, current_bin: Option<usize>) {
+        self.selected.resize(table.dimensions.len(), 0);
+
+        egui::Grid::new("Policy bin grid")
+            .spacing([25.0, 5.0])
+            .show(ui, |ui| {
+                for (index, (coordinate, dimension)) in
+                    self.selected.iter_mut().zip(&table.dimensions).enumerate()
+                {
+                    ui.label(format!("Dimension {}: ", index + 1));
+                    ui.add(DragValue::new(coordinate).clamp_range(0..=dimension.bins - 1));
+                    let (min, max) = dimension.range(*coordinate);
+                    ui.label(format!("{:.2} to {:.2}", min, max));
+                    ui.end_row();
+                }
+            });
+
+        let bin = table.bin_from_coordinates(&self.selected);
+        let mut player_move = table.action(bin);
+        ui.horizontal(|ui| {
+            ui.checkbox(&mut player_move.left, "Left");
+            ui.checkbox(&mut player_move.right, "Right");
+            ui.checkbox(&mut player_move.up, "Up");
+            ui.checkbox(&mut player_move.down, "Down");
+            ui.checkbox(&mut player_move.dash, "Dash");
+            ui.checkbox(&mut player_move.grab, "Grab");
+        });
+        table.set_action(bin, player_move);
+
+        ui.add_space(10.0);
+
+        let row_height = ui.text_style_height(&egui::TextStyle::Body);
+        egui::ScrollArea::vertical().max_height(300.0).show_rows(
+            ui,
+            row_height,
+            table.bin_count(),
+            |ui, rows| {
+                for row in rows {
+                    let mut text = RichText::new(format!(
+                        "{:?}: {}",
+                        table.bin_coordinates(row),
+                        move_text(table.action(row))
+                    ));
+                    if Some(row) == current_bin {
+                        text = text.strong();
+                    }
+                    if ui.selectable_label(row == bin, text).clicked() {
+                        self.selected = table.bin_coordinates(row);
+                    }
+                }
+            },
+        );
+    }
+}
+
+#[cfg(feature = "gui")]
+fn move_text(player_move: Move) -> String {
+    let mut text = String::new();
+    if player_move.up {
+        text += " UP ";
+    }
+    if player_move.left {
+        text += " LEFT ";
+    }
+    if player_move.right {
+        text += " RIGHT ";
+    }
+    if player_move.down {
+        text += " DOWN ";
+    }
+    if player_move.dash {
+        text += " DASH ";
+    }
+    if player_move.grab {
+        text += " GRAB ";
+    }
+    if text.is_empty() {
+        text += " NONE ";
+    }
+    text
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Discretization;
+
+    #[test]
+    fn zero_bins_is_one_bin() {
+        let discretization = Discretization {
+            min: 0.0,
+            max: 1.0,
+            bins: 0,
+        };
+        for value in [-1.0, 0.0, 0.5, 1.0, 2.0, f32::NAN] {
+            assert_eq!(discretization.bin(value), 0);
+        }
+        assert_eq!(discretization.range(0), (0.0, 1.0));
+    }
+
+    #[test]
+    fn values_outside_the_range_use_the_end_bins() {
+        let discretization = Discretization {
+            min: 0.0,
+            max: 1.0,
+            bins: 4,
+        };
+        assert_eq!(discretization.bin(-1.0), 0);
+        assert_eq!(discretization.bin(0.6), 2);
+        assert_eq!(discretization.bin(1.0), 3);
+        assert_eq!(discretization.bin(2.0), 3);
+    }
+}
@@ -0,0 +1,122 @@
+use bevy::{
+    prelude::Mesh,
+    render::{mesh::Indices, render_resource::PrimitiveTopology},
+};
+
+// The z component of the cross product of (b - a) and (c - a).
+fn cross(a: [f32; 2], b: [f32; 2], c: [f32; 2]) -> f32 {
+    (b[0] - a[0]) * (c[1] - a[1]) - (b[1] - a[1]) * (c[0] - a[0])
+}
+
+/// Triangulates a simple polygon by ear clipping, returning the indices of the triangles' vertices
+/// in counterclockwise order. The vertices can be in either winding order.
+pub(crate) fn triangulate(points: &[[f32; 2]]) -> Vec<u32> {
+    let count = points.len();
+    let mut remaining: Vec<usize> = (0..count).collect();
+    let signed_area: f32 = (0..count)
+        .map(|index| cross([0.0, 0.0], points[index], points[(index + 1) % count]))
+        .sum();
+    if signed_area < 0.0 {
+        remaining.reverse();
+    }
+
+    let mut indices = vec![];
+    while remaining.len() >= 3 {
+        let len = remaining.len();
+        let corners = |index: usize| {
+            (
+                remaining[(index + len - 1) % len],
+                remaining[index],
+                remaining[(index + 1) % len],
+            )
+        };
+        let is_ear = |index: usize| {
+            let (a, b, c) = corners(index);
+            cross(points[a], points[b], points[c]) > 0.0
+                && !remaining.iter().any(|&other| {
+                    other != a
+                        && other != b
+                        && other != c
+                        && cross(points[a], points[b], points[other]) >= 0.0
+                        && cross(points[b], points[c], points[other]) >= 0.0
+                        && cross(points[c], points[a], points[other]) >= 0.0
+                })
+        };
+        // Self-intersecting polygons may not have an ear, so a vertex is clipped anyway to terminate.
+        let ear = (0..len).find(|index| is_ear(*index)).unwrap_or(0);
+        let (a, b, c) = corners(ear);
+        indices.extend([a as u32, b as u32, c as u32]);
+        remaining.remove(ear);
+    }
+    indices
+}
+
+/// A flat mesh of a simple polygon, see [`triangulate`].
+pub(crate) fn polygon_mesh(points: &[[f32; 2]]) -> Mesh {
+    let positions: Vec<[f32; 3]> = points.iter().map(|[x, y]| [*x, *y, 0.0]).collect();
+    let normals = vec![[0.0, 0.0, 1.0]; points.len()];
+    let uvs: Vec<[f32; 2]> = points.iter().map(|[x, y]| [x + 0.5, 0.5 - y]).collect();
+    let mut mesh = Mesh::new(PrimitiveTopology::TriangleList);
+    mesh.insert_attribute(Mesh::ATTRIBUTE_POSITION, positions);
+    mesh.insert_attribute(Mesh::ATTRIBUTE_NORMAL, normals);
+    mesh.insert_attribute(Mesh::ATTRIBUTE_UV_0, uvs);
+    mesh.set_indices(Some(Indices::U32(triangulate(points))));
+    mesh
+}
+
+/// The outline of a stroke through the points with the given width, as a polygon.
+/// Corners are mitered, with the miters limited to twice the width so sharp turns don't make spikes.
+pub(crate) fn stroke_outline(points: &[[f32; 2]], width: f32) -> Vec<[f32; 2]> {
+    let normal = |a: [f32; 2], b: [f32; 2]| {
+        let (x, y) = (b[0] - a[0], b[1] - a[1]);
+        let length = x.hypot(y).max(f32::EPSILON);
+        [-y / length, x / length]
+    };
+    let count = points.len();
+    let mut left = Vec::with_capacity(count);
+    let mut right = Vec::with_capacity(count);
+    for index in 0..count {
+        let previous = normal(points[index.saturating_sub(1)], points[index]);
+        let next = normal(points[index], points[(index + 1).min(count - 1)]);
+        // The first and last points only have one segment.
+        let (previous, next) = match (index, count - 1 - index) {
+            (0, _) => (next, next),
+            (_, 0) => (previous, previous),
+            _ => (previous, next),
+        };
+        let sum = [previous[0] + next[0], previous[1] + next[1]];
+        let length = sum[0].hypot(sum[1]).max(f32::EPSILON);
+        let miter = [sum[0] / length, sum[1] / length];
+        let scale = (width / 2.0) / (miter[0] * next[0] + miter[1] * next[1]).max(0.25);
+        let [x, y] = points[index];
+        left.push([x + miter[0] * scale, y + miter[1] * scale]);
+        right.push([x - miter[0] * scale, y - miter[1] * scale]);
+    }
+    right.reverse();
+    left.extend(right);
+    left
+}
+
+/// A flat mesh of line segments with the given width, made of a rectangle per segment.
+pub(crate) fn lines_mesh(lines: &[([f32; 2], [f32; 2])], width: f32) -> Mesh {
+    let mut positions = Vec::with_capacity(4 * lines.len());
+    let mut indices = Vec::with_capacity(6 * lines.len());
+    for (start, end) in lines {
+        let (x, y) = (end[0] - start[0], end[1] - start[1]);
+        let length = x.hypot(y).max(f32::EPSILON);
+        let offset = [-y / length * width / 2.0, x / length * width / 2.0];
+        let first = positions.len() as u32;
+        for ([x, y], side) in [(start, 1.0), (start, -1.0), (end, -1.0), (end, 1.0)] {
+            positions.push([x + side * offset[0], y + side * offset[1], 0.0]);
+        }
+        indices.extend([first, first + 1, first + 2, first, first + 2, first + 3]);
+    }
+    let normals = vec![[0.0, 0.0, 1.0]; positions.len()];
+    let uvs = vec![[0.0, 0.0]; positions.len()];
+    let mut mesh = Mesh::new(PrimitiveTopology::TriangleList);
+    mesh.insert_attribute(Mesh::ATTRIBUTE_POSITION, positions);
+    mesh.insert_attribute(Mesh::ATTRIBUTE_NORMAL, normals);
+    mesh.insert_attribute(Mesh::ATTRIBUTE_UV_0, uvs);
+    mesh.set_indices(Some(Indices::U32(indices)));
+    mesh
+}
@@ -0,0 +1,136 @@
+use std::{fs, path::Path};
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::{
+    common::ObjectAndTransform,
+    error::{Error, Result},
+    migration::{migrate, WORLD_VERSION},
+};
+
+/// A named group of objects which can be inserted into worlds, for structures like staircases or spike pits
+/// which are used in many levels.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct Prefab {
+    pub name: String,
+    /// The objects, positioned relative to the center of the prefab.
+    /// The lowest object has depth 0.
+    pub objects: Vec<ObjectAndTransform>,
+}
+
+impl Prefab {
+    /// A prefab of the objects, which are moved so their center is at the origin.
+    pub fn new(name: String, mut objects: Vec<ObjectAndTransform>) -> Prefab {
+        let (min, max) = objects.iter().fold(
+            ([f32::INFINITY; 3], [f32::NEG_INFINITY; 3]),
+            |(mut min, mut max), object_and_transform| {
+                for ((low, high), position) in min
+                    .iter_mut()
+                    .zip(&mut max)
+                    .zip(object_and_transform.position)
+                {
+                    *low = low.min(position);
+                    *high = high.max(position);
+                }
+                (min, max)
+            },
+        );
+        let offset = [(min[0] + max[0]) / 2.0, (min[1] + max[1]) / 2.0, min[2]];
+        for object_and_transform in &mut objects {
+            for (position, offset) in object_and_transform.position.iter_mut().zip(offset) {
+                *position -= offset;
+            }
+        }
+        Prefab { name, objects }
+    }
+
+    /// The objects of the prefab with its center at `position`, and the lowest object at depth `depth`.
+    pub fn objects_at(&self, position: [f32; 2], depth: f32) -> Vec<ObjectAndTransform> {
+        self.objects
+            .iter()
+            .map(|object_and_transform| {
+                let [x, y, z] = object_and_transform.position;
+                ObjectAndTransform {
+                    position: [x + position[0], y + position[1], z + depth],
+                    ..object_and_transform.clone()
+                }
+            })
+            .collect()
+    }
+
+    /// Reads a prefab from a JSON file.
+    pub fn load(path: impl AsRef<Path>) -> Result<Prefab> {
+        let path = path.as_ref();
+        let contents = fs::read_to_string(path).map_err(|source| Error::PrefabIo {
+            path: path.to_path_buf(),
+            source,
+        })?;
+        Prefab::from_json(&contents)
+    }
+
+    /// Writes the prefab to a JSON file.
+    pub fn save(&self, path: impl AsRef<Path>) -> Result<()> {
+        let path = path.as_ref();
+        fs::write(path, self.to_json()?).map_err(|source| Error::PrefabIo {
+            path: path.to_path_buf(),
+            source,
+        })
+    }
+
+    /// Reads a prefab from JSON, updating its objects from an older format like [`World::from_json`](crate::World::from_json).
+    pub fn from_json(json: &str) -> Result<Prefab> {
+        let mut prefab: Value = serde_json::from_str(json).map_err(Error::PrefabFormat)?;
+        migrate(&mut prefab).map_err(|error| match error {
+            Error::WorldFormat(error) => Error::PrefabFormat(error),
+            Error::WorldVersion { version, supported } => {
+                Error::PrefabVersion { version, supported }
+            }
+            error => error,
+        })?;
+        serde_json::from_value(prefab).map_err(Error::PrefabFormat)
+    }
+
+    /// The prefab as JSON, with the version of the world format, which its objects are saved in.
+    pub fn to_json(&self) -> Result<String> {
+        serde_json::to_string(&VersionedPrefab {
+            version: WORLD_VERSION,
+            prefab: self,
+        })
+        .map_err(Error::PrefabFormat)
+    }
+}
+
+// The prefab as it's saved, with the version of the format like worlds.
+#[derive(Serialize)]
+struct VersionedPrefab<'a> {
+    version: u32,
+    #[serde(flatten)]
+    prefab: &'a Prefab,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Prefab;
+    use crate::{common::WorldObject, migration::WORLD_VERSION};
+
+    #[test]
+    fn old_prefab_is_migrated() {
+        let json = r#"{
+            "name": "Goal",
+            "objects": [
+                {"object": "Goal", "position": [0.0, 0.0, 0.0], "scale": [50.0, 50.0], "rotation": 0.0}
+            ]
+        }"#;
+        let prefab = Prefab::from_json(json).unwrap();
+        assert!(matches!(prefab.objects[0].object, WorldObject::Goal { .. }));
+    }
+
+    #[test]
+    fn saved_prefab_has_version() {
+        let prefab = Prefab::from_json(r#"{"name": "Empty", "objects": []}"#).unwrap();
+        let json: serde_json::Value = serde_json::from_str(&prefab.to_json().unwrap()).unwrap();
+        assert_eq!(json["version"], WORLD_VERSION);
+        assert!(Prefab::from_json(&prefab.to_json().unwrap()).is_ok());
+    }
+}
@@ -0,0 +1,60 @@
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+
+use crate::common::{World, WorldObject};
+
+/// Random changes applied to a world by [`Environment::from_world_randomized`](crate::Environment::from_world_randomized),
+/// so agents don't overfit to a single level.
+///
+/// The default doesn't change the world.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, Default, PartialEq)]
+#[serde(default)]
+pub struct Randomization {
+    /// Range the gravity is sampled from, in physics units. The world's gravity is used when None.
+    pub gravity: Option<[f32; 2]>,
+    /// Range the friction coefficient is sampled from. The world's friction is used when None.
+    pub friction: Option<[f32; 2]>,
+    /// Largest offset added to each coordinate of a block's position, in world units.
+    pub block_offset: f32,
+    /// Largest fraction of its size a block grows or shrinks by, along each axis.
+    pub block_scale: f32,
+}
+
+impl Randomization {
+    /// Whether the randomization changes worlds.
+    pub fn is_enabled(&self) -> bool {
+        *self != Randomization::default()
+    }
+
+    /// A copy of the world with random changes applied.
+    pub fn apply(&self, world: &World, rng: &mut impl Rng) -> World {
+        let mut world = world.clone();
+        if let Some(gravity) = self.gravity {
+            world.physics.gravity = sample(rng, gravity);
+        }
+        if let Some(friction) = self.friction {
+            world.physics.friction = sample(rng, friction).max(0.0);
+        }
+        for object_and_transform in &mut world.objects {
+            if !matches!(object_and_transform.object, WorldObject::Block { .. }) {
+                continue;
+            }
+            for position in &mut object_and_transform.position[..2] {
+                *position += sample(rng, [-self.block_offset, self.block_offset]);
+            }
+            for scale in &mut object_and_transform.scale {
+                *scale *= (1.0 + sample(rng, [-self.block_scale, self.block_scale])).max(0.0);
+            }
+        }
+        world
+    }
+}
+
+// A uniform sample from the range, or its start if the range is empty.
+fn sample(rng: &mut impl Rng, [start, end]: [f32; 2]) -> f32 {
+    if start < end {
+        rng.gen_range(start..end)
+    } else {
+        start
+    }
+}
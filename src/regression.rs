@@ -0,0 +1,242 @@
+//! Micro-levels with analytically known outcomes, used to detect changes in
+//! the physics behaviour agents depend on (for example after upgrading the crate or rapier).
+
+use std::f32::consts::PI;
+
+use rapier2d::prelude::IntegrationParameters;
+
+use crate::common::{
+    Environment, Move, ObjectAndTransform, World, WorldObject, BEVY_TO_PHYSICS_SCALE, GRAVITY,
+    JUMP_IMPULSE, PLAYER_DEPTH, PLAYER_RADIUS,
+};
+
+const FALL_DISTANCE: f32 = 200.0;
+const SLOPE_ANGLE: f32 = PI / 4.0;
+const SLOPE_FRICTION: f32 = 0.5; // Rapier's default friction.
+const SLIDE_STEPS: usize = 60;
+const SETTLE_STEPS: usize = 30;
+// Measurements waiting for an event give up after this many steps, so a broken case fails instead of hanging.
+const MAX_MEASURE_STEPS: usize = 10_000;
+
+/// A micro-level along with a measurement and its expected value.
+pub struct RegressionCase {
+    pub name: &'static str,
+    pub world: World,
+    pub expected: f32,
+    pub tolerance: f32,
+    measure: fn(&mut Environment) -> f32,
+}
+
+/// The outcome of running a [`RegressionCase`].
+#[derive(Debug, Clone)]
+pub struct RegressionResult {
+    pub name: &'static str,
+    pub expected: f32,
+    pub measured: f32,
+    pub tolerance: f32,
+}
+
+impl RegressionResult {
+    pub fn passed(&self) -> bool {
+        (self.measured - self.expected).abs() <= self.tolerance
+    }
+}
+
+impl RegressionCase {
+    pub fn run(&self) -> RegressionResult {
+        let (mut environment, _) = Environment::from_world(&self.world);
+        RegressionResult {
+            name: self.name,
+            expected: self.expected,
+            measured: (self.measure)(&mut environment),
+            tolerance: self.tolerance,
+        }
+    }
+}
+
+/// The regression cases shipped with the crate:
+/// - `free_fall_time`: seconds taken by the player to fall a fixed distance.
+/// - `slope_slide_distance`: distance a dynamic block slides down a 45° slope in one second.
+/// - `jump_apex_height`: height gained by the player from a single jump on flat ground.
+///
+/// Distances are in the same units as [`World`] positions.
+/// A measurement which doesn't finish within a bounded number of steps is infinite, so the case fails.
+pub fn regression_cases() -> Vec<RegressionCase> {
+    let dt = IntegrationParameters::default().dt;
+    let gravity = GRAVITY / BEVY_TO_PHYSICS_SCALE;
+
+    vec![
+        RegressionCase {
+            name: "free_fall_time",
+            world: World::default(),
+            expected: (2.0 * FALL_DISTANCE / gravity).sqrt(),
+            tolerance: 2.0 * dt,
+            measure: |environment| {
+                let start = player_y(environment);
+                let mut steps = 0;
+                while start - player_y(environment) < FALL_DISTANCE {
+                    if steps == MAX_MEASURE_STEPS {
+                        return f32::INFINITY;
+                    }
+                    environment.step(Move::default());
+                    steps += 1;
+                }
+                steps as f32 * IntegrationParameters::default().dt
+            },
+        },
+        slope_case(dt, gravity),
+        jump_case(gravity),
+    ]
+}
+
+/// Runs all the cases from [`regression_cases`].
+pub fn run_regression_suite() -> Vec<RegressionResult> {
+    regression_cases().iter().map(RegressionCase::run).collect()
+}
+
+fn player_y(environment: &Environment) -> f32 {
+    environment.rigid_body_set()[environment.player_handle()]
+        .translation()
+        .y
+        / BEVY_TO_PHYSICS_SCALE
+}
+
+fn slope_case(dt: f32, gravity: f32) -> RegressionCase {
+    let normal = [SLOPE_ANGLE.sin(), SLOPE_ANGLE.cos()];
+    let slope_thickness = 100.0;
+    let block_size = 40.0;
+    let block_offset = (slope_thickness + block_size) / 2.0;
+
+    let world = World {
+        // Keep the player away from the slope.
+        player_position: [0.0, 2000.0],
+        objects: vec![
+            ObjectAndTransform {
+                object: WorldObject::Block {
+                    fixed: true,
+                    animation: None,
+                    ccd: false,
+                    friction: None,
+                    restitution: None,
+                    density: 1.0,
+                    material: None,
+                },
+                position: [0.0, 0.0, 0.0],
+                scale: [4000.0, slope_thickness],
+                rotation: -SLOPE_ANGLE,
+                color: None,
+            },
+            ObjectAndTransform {
+                object: WorldObject::Block {
+                    fixed: false,
+                    animation: None,
+                    ccd: false,
+                    friction: None,
+                    restitution: None,
+                    density: 1.0,
+                    material: None,
+                },
+                position: [normal[0] * block_offset, normal[1] * block_offset, 0.0],
+                scale: [block_size, block_size],
+                rotation: -SLOPE_ANGLE,
+                color: None,
+            },
+        ],
+        ..World::default()
+    };
+
+    let acceleration = gravity * (SLOPE_ANGLE.sin() - SLOPE_FRICTION * SLOPE_ANGLE.cos());
+    let time = SLIDE_STEPS as f32 * dt;
+    let expected = 0.5 * acceleration * time.powi(2);
+
+    RegressionCase {
+        name: "slope_slide_distance",
+        world,
+        expected,
+        tolerance: 0.1 * expected,
+        measure: |environment| {
+            let block = environment
+                .rigid_body_set()
+                .iter()
+                .find(|(handle, _)| *handle != environment.player_handle())
+                .map(|(handle, _)| handle)
+                .unwrap();
+            let start = *environment.rigid_body_set()[block].translation();
+            for _ in 0..SLIDE_STEPS {
+                environment.step(Move::default());
+            }
+            let end = *environment.rigid_body_set()[block].translation();
+            let down_slope = [SLOPE_ANGLE.cos(), -SLOPE_ANGLE.sin()];
+            ((end.x - start.x) * down_slope[0] + (end.y - start.y) * down_slope[1])
+                / BEVY_TO_PHYSICS_SCALE
+        },
+    }
+}
+
+fn jump_case(gravity: f32) -> RegressionCase {
+    let floor_thickness = 100.0;
+    let world = World {
+        player_position: [0.0, PLAYER_RADIUS + PLAYER_DEPTH / 2.0],
+        objects: vec![ObjectAndTransform {
+            object: WorldObject::Block {
+                fixed: true,
+                animation: None,
+                ccd: false,
+                friction: None,
+                restitution: None,
+                density: 1.0,
+                material: None,
+            },
+            position: [0.0, -floor_thickness / 2.0, 0.0],
+            scale: [1000.0, floor_thickness],
+            rotation: 0.0,
+            color: None,
+        }],
+        ..World::default()
+    };
+
+    // The expected value depends on the player's mass, so we compute it from a fresh environment.
+    let (environment, _) = Environment::from_world(&world);
+    let mass = environment.rigid_body_set()[environment.player_handle()].mass();
+    let velocity = JUMP_IMPULSE / mass / BEVY_TO_PHYSICS_SCALE;
+    let expected = velocity.powi(2) / (2.0 * gravity);
+
+    RegressionCase {
+        name: "jump_apex_height",
+        world,
+        expected,
+        tolerance: 0.05 * expected,
+        measure: |environment| {
+            for _ in 0..SETTLE_STEPS {
+                environment.step(Move::default());
+            }
+            let start = player_y(environment);
+            environment.step(Move {
+                up: true,
+                ..Move::default()
+            });
+            let mut apex = player_y(environment);
+            for _ in 0..MAX_MEASURE_STEPS {
+                environment.step(Move::default());
+                let y = player_y(environment);
+                if y <= apex {
+                    return apex - start;
+                }
+                apex = y;
+            }
+            f32::INFINITY
+        },
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::run_regression_suite;
+
+    #[test]
+    fn regression_suite_passes() {
+        for result in run_regression_suite() {
+            assert!(result.passed(), "{result:?}");
+        }
+    }
+}
@@ -0,0 +1,102 @@
+use crate::common::{Environment, Move};
+
+/// Computes the reward returned by [`Environment::step_full`].
+///
+/// [`DenseReward`] covers the common cases. Closures taking a [`Transition`]
+/// and the environment after the step also implement this trait.
+pub trait RewardFn: Send + Sync {
+    fn reward(&self, transition: &Transition, environment: &Environment) -> f32;
+}
+
+impl<F: Fn(&Transition, &Environment) -> f32 + Send + Sync> RewardFn for F {
+    fn reward(&self, transition: &Transition, environment: &Environment) -> f32 {
+        self(transition, environment)
+    }
+}
+
+/// Information about a single step, passed to a [`RewardFn`].
+#[derive(Debug, Clone)]
+pub struct Transition {
+    pub player_move: Move,
+    /// None if the environment doesn't have any goals.
+    /// This is the path distance if the environment has a distance field, see [`Environment::path_distance_to_goals`].
+    pub previous_distance_to_goals: Option<f32>,
+    /// None if the environment doesn't have any goals.
+    pub distance_to_goals: Option<f32>,
+    /// Whether the environment was won during this step.
+    pub just_won: bool,
+    /// Whether the episode ended with this step, by winning, leaving the bounds or being killed, see [`Environment::done`].
+    pub done: bool,
+    /// Sum of the rewards of the goals first reached during this step, see [`Environment::goal_reward`].
+    pub goal_reward: f32,
+    /// Sum of the rewards of the hazards which killed players during this step, see [`Environment::hazard_reward`].
+    pub hazard_reward: f32,
+    /// Whether the player is touching something other than the ground after the step.
+    pub wall_contact: bool,
+}
+
+/// A weighted sum of common reward terms.
+/// The default is the decrease in the distance to goals.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct DenseReward {
+    /// Multiplies the decrease in the distance to goals.
+    pub distance_weight: f32,
+    /// Subtracted every step, so finishing sooner gives a higher return.
+    pub time_penalty: f32,
+    /// Subtracted every step the player touches something other than the ground.
+    pub contact_penalty: f32,
+    /// Added on the step the environment is won.
+    pub win_bonus: f32,
+    /// Multiplies the rewards of the goals reached during the step.
+    pub goal_weight: f32,
+    /// Added on the step the environment is won, scaled by how quickly it was won:
+    /// the full bonus for winning on the first step, decreasing linearly to 0.0 at `completion_steps` steps.
+    pub completion_bonus: f32,
+    /// The number of steps after which winning no longer gives a completion bonus.
+    pub completion_steps: usize,
+    /// Multiplies the rewards of the hazards which killed players during the step.
+    pub hazard_weight: f32,
+}
+
+impl Default for DenseReward {
+    fn default() -> Self {
+        DenseReward {
+            distance_weight: 1.0,
+            time_penalty: 0.0,
+            contact_penalty: 0.0,
+            win_bonus: 0.0,
+            goal_weight: 0.0,
+            completion_bonus: 0.0,
+            completion_steps: 1000,
+            hazard_weight: 1.0,
+        }
+    }
+}
+
+impl RewardFn for DenseReward {
+    fn reward(&self, transition: &Transition, environment: &Environment) -> f32 {
+        let mut reward = -self.time_penalty;
+        if let (Some(previous_distance), Some(distance)) = (
+            transition.previous_distance_to_goals,
+            transition.distance_to_goals,
+        ) {
+            // Path distances are infinite where the goals can't be reached.
+            if previous_distance.is_finite() && distance.is_finite() {
+                reward += self.distance_weight * (previous_distance - distance);
+            }
+        }
+        if transition.wall_contact {
+            reward -= self.contact_penalty;
+        }
+        if transition.just_won {
+            reward += self.win_bonus;
+            let remaining = 1.0
+                - environment.steps().saturating_sub(1) as f32
+                    / self.completion_steps.max(1) as f32;
+            reward += self.completion_bonus * remaining.max(0.0);
+        }
+        reward += self.goal_weight * transition.goal_reward;
+        reward += self.hazard_weight * transition.hazard_reward;
+        reward
+    }
+}
@@ -0,0 +1,56 @@
+use crate::{common::Environment, policy::Policy, trajectory::Trajectory};
+
+/// The outcome of [`Environment::rollout`].
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct RolloutResult {
+    /// Number of steps taken.
+    pub steps: usize,
+    /// Minimum distance to goals over the steps, including the starting state.
+    /// See [`Environment::path_distance_to_goals`].
+    /// None if the environment doesn't have any goals.
+    pub min_distance: Option<f32>,
+    /// The number of steps taken when the environment was won, or None if it wasn't won.
+    pub steps_to_win: Option<usize>,
+    /// The recorded trajectory, if recording was requested.
+    pub trajectory: Option<Trajectory>,
+}
+
+impl Environment {
+    /// Lets the policy play from the current state for up to `max_steps` steps, stopping early once the episode is done.
+    /// If `record` is true, the trajectory is recorded with [`Environment::record`], replacing any previous recording.
+    pub fn rollout<P: Policy>(
+        &mut self,
+        policy: &mut P,
+        max_steps: usize,
+        record: bool,
+    ) -> RolloutResult {
+        if record {
+            self.record();
+        }
+
+        let mut result = RolloutResult {
+            min_distance: self.path_distance_to_goals().ok(),
+            ..RolloutResult::default()
+        };
+        while result.steps < max_steps && !self.done() {
+            let player_move = policy.get_move(self);
+            self.step(player_move);
+            result.steps += 1;
+            if let Ok(distance) = self.path_distance_to_goals() {
+                result.min_distance = Some(
+                    result
+                        .min_distance
+                        .map_or(distance, |min| min.min(distance)),
+                );
+            }
+            if self.won() {
+                result.steps_to_win = Some(result.steps);
+            }
+        }
+
+        if record {
+            result.trajectory = self.stop_recording();
+        }
+        result
+    }
+}
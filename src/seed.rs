@@ -0,0 +1,135 @@
+#[cfg(feature = "gui")]
+use bevy::prelude::Resource;
+#[cfg(feature = "gui")]
+use bevy_egui::egui::{self, Ui};
+use rand::prelude::*;
+
+/// Derives an independent seed from `seed` and `index`, for example a seed per run from a master seed.
+pub fn derive_seed(seed: u64, index: u64) -> u64 {
+    // SplitMix64.
+    let mut z = seed.wrapping_add(index.wrapping_add(1).wrapping_mul(0x9E37_79B9_7F4A_7C15));
+    z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+    z ^ (z >> 31)
+}
+
+/// A seed used by a training run.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct RunSeed {
+    pub master: u64,
+    /// Index of the run among the runs with the same master seed.
+    pub index: u64,
+    pub seed: u64,
+}
+
+/// The session's master seed, and the seeds derived from it for the training runs,
+/// so a run can be reproduced by using the same master seed.
+#[derive(Clone, Debug)]
+#[cfg_attr(feature = "gui", derive(Resource))]
+pub struct Seeds {
+    master: u64,
+    runs: Vec<RunSeed>,
+    // The master seed being edited in the UI.
+    master_text: String,
+}
+
+impl Default for Seeds {
+    fn default() -> Self {
+        Seeds::new(thread_rng().gen())
+    }
+}
+
+impl Seeds {
+    pub fn new(master: u64) -> Seeds {
+        Seeds {
+            master,
+            runs: vec![],
+            master_text: master.to_string(),
+        }
+    }
+
+    pub fn master(&self) -> u64 {
+        self.master
+    }
+
+    pub fn set_master(&mut self, master: u64) {
+        self.master = master;
+        self.master_text = master.to_string();
+    }
+
+    /// Replaces the master seed by a random one.
+    pub fn reroll(&mut self) {
+        self.set_master(thread_rng().gen());
+    }
+
+    /// The seeds of the runs started in this session, oldest first.
+    pub fn runs(&self) -> &[RunSeed] {
+        &self.runs
+    }
+
+    // Index of the next run with the current master seed.
+    fn next_index(&self) -> u64 {
+        self.runs
+            .iter()
+            .filter(|run| run.master == self.master)
+            .count() as u64
+    }
+
+    /// Derives and records the seed for a new run.
+    pub fn next_run(&mut self) -> RunSeed {
+        let index = self.next_index();
+        let run = RunSeed {
+            master: self.master,
+            index,
+            seed: derive_seed(self.master, index),
+        };
+        self.runs.push(run);
+        run
+    }
+
+    /// Shows the master seed with reroll and copy controls, and the seeds of the previous runs.
+    #[cfg(feature = "gui")]
+    pub fn ui(&mut self, ui: &mut Ui) {
+        ui.horizontal(|ui| {
+            ui.label("Master seed: ");
+            let response = ui.text_edit_singleline(&mut self.master_text);
+            if response.lost_focus() {
+                match self.master_text.trim().parse() {
+                    Ok(master) => self.master = master,
+                    Err(_) => self.master_text = self.master.to_string(),
+                }
+            }
+            if ui.button("Reroll").clicked() {
+                self.reroll();
+            }
+            if ui.button("Copy").clicked() {
+                let master = self.master.to_string();
+                ui.output_mut(|output| output.copied_text = master);
+            }
+        });
+        ui.label(format!(
+            "Next run seed: {}",
+            derive_seed(self.master, self.next_index())
+        ));
+
+        if !self.runs.is_empty() {
+            ui.add_space(5.0);
+            egui::Grid::new("Run seeds grid")
+                .spacing([25.0, 5.0])
+                .show(ui, |ui| {
+                    for (number, run) in self.runs.iter().enumerate().rev() {
+                        ui.label(format!("Run {}", number + 1));
+                        ui.label(format!(
+                            "{} (master {}, #{})",
+                            run.seed, run.master, run.index
+                        ));
+                        if ui.button("Copy").clicked() {
+                            let seed = run.seed.to_string();
+                            ui.output_mut(|output| output.copied_text = seed);
+                        }
+                        ui.end_row();
+                    }
+                });
+        }
+    }
+}
@@ -0,0 +1,164 @@
+use crate::{
+    common::Environment,
+    reward::{DenseReward, RewardFn, Transition},
+};
+
+/// The potential-based shaping term `gamma * next_potential - potential`.
+///
+/// Adding this to the rewards doesn't change the optimal policy (Ng, Harada and Russell, 1999),
+/// as long as terminal states have a potential of 0.
+pub fn potential_shaping(gamma: f32, potential: f32, next_potential: f32) -> f32 {
+    gamma * next_potential - potential
+}
+
+/// The potential `-scale * distance`, which increases as the player gets closer to the goals.
+/// Missing or infinite distances (no goals, or goals which can't be reached) have a potential of 0.
+pub fn distance_potential(distance: Option<f32>, scale: f32) -> f32 {
+    distance
+        .filter(|distance| distance.is_finite())
+        .map_or(0.0, |distance| -scale * distance)
+}
+
+/// The shaping term of a transition, using [`distance_potential`] as the potential.
+/// The states ending the episode (winning, leaving the bounds or being killed) are terminal, so their potential is 0.
+pub fn distance_shaping(transition: &Transition, gamma: f32, scale: f32) -> f32 {
+    let potential = distance_potential(transition.previous_distance_to_goals, scale);
+    let next_potential = if transition.done {
+        0.0
+    } else {
+        distance_potential(transition.distance_to_goals, scale)
+    };
+    potential_shaping(gamma, potential, next_potential)
+}
+
+/// A reward function with distance based potential shaping added to a base reward,
+/// giving dense rewards without changing which policies are optimal for the base reward.
+///
+/// The default base reward is sparse, 1.0 for winning.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct ShapedReward<R = DenseReward> {
+    pub base: R,
+    /// The discount factor used by the learning algorithm.
+    pub gamma: f32,
+    /// Multiplies the distance to goals in the potential.
+    pub scale: f32,
+}
+
+impl Default for ShapedReward {
+    fn default() -> Self {
+        ShapedReward {
+            base: DenseReward {
+                distance_weight: 0.0,
+                win_bonus: 1.0,
+                ..DenseReward::default()
+            },
+            gamma: 0.99,
+            scale: 1.0,
+        }
+    }
+}
+
+impl<R: RewardFn> RewardFn for ShapedReward<R> {
+    fn reward(&self, transition: &Transition, environment: &Environment) -> f32 {
+        self.base.reward(transition, environment)
+            + distance_shaping(transition, self.gamma, self.scale)
+    }
+}
+
+/// Potential-based shaping with a custom potential, for rewards computed outside of [`Environment::step_full`].
+///
+/// Call [`PotentialShaping::reset`] at the start of every episode and [`PotentialShaping::shape`] after every step.
+pub struct PotentialShaping<P> {
+    potential: P,
+    gamma: f32,
+    previous_potential: f32,
+}
+
+impl<P: Fn(&Environment) -> f32> PotentialShaping<P> {
+    pub fn new(potential: P, gamma: f32) -> PotentialShaping<P> {
+        PotentialShaping {
+            potential,
+            gamma,
+            previous_potential: 0.0,
+        }
+    }
+
+    /// Starts an episode from the environment's current state.
+    pub fn reset(&mut self, environment: &Environment) {
+        self.previous_potential = (self.potential)(environment);
+    }
+
+    /// The shaping term for the step which led to the environment's current state.
+    /// Done environments are terminal, so their potential is 0.
+    pub fn shape(&mut self, environment: &Environment) -> f32 {
+        let potential = if environment.done() {
+            0.0
+        } else {
+            (self.potential)(environment)
+        };
+        let shaping = potential_shaping(self.gamma, self.previous_potential, potential);
+        self.previous_potential = potential;
+        shaping
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::ShapedReward;
+    use crate::{
+        common::{
+            Environment, Move, ObjectAndTransform, World, WorldObject, PLAYER_DEPTH, PLAYER_RADIUS,
+        },
+        reward::DenseReward,
+    };
+
+    #[test]
+    fn hazard_death_is_terminal() {
+        let world = World {
+            player_position: [0.0, PLAYER_RADIUS + PLAYER_DEPTH / 2.0 + 10.0],
+            objects: vec![
+                ObjectAndTransform {
+                    object: WorldObject::Hazard { reward: -1.0 },
+                    position: [0.0, -50.0, 0.0],
+                    scale: [1000.0, 100.0],
+                    rotation: 0.0,
+                    color: None,
+                },
+                ObjectAndTransform {
+                    object: WorldObject::Goal {
+                        reward: 1.0,
+                        optional: false,
+                    },
+                    position: [2000.0, 2000.0, 0.0],
+                    scale: [50.0, 50.0],
+                    rotation: 0.0,
+                    color: None,
+                },
+            ],
+            ..World::default()
+        };
+        let (mut environment, _) = Environment::from_world(&world);
+        // Only the shaping term is left in the reward.
+        environment.set_reward_fn(ShapedReward {
+            base: DenseReward {
+                distance_weight: 0.0,
+                hazard_weight: 0.0,
+                ..DenseReward::default()
+            },
+            gamma: 0.99,
+            scale: 1.0,
+        });
+
+        for _ in 0..100 {
+            let previous_distance = environment.distance_to_goals().unwrap();
+            let result = environment.step_full(Move::default());
+            if result.done {
+                assert!(environment.dead());
+                // The terminal potential is 0, so the shaping is only minus the previous potential.
+                assert!((result.reward - previous_distance).abs() < 1e-3);
+                return;
+            }
+        }
+        panic!("the player didn't fall on the hazard");
+    }
+}
@@ -0,0 +1,69 @@
+use bevy_egui::egui::{self, Color32, Pos2, Sense, Stroke, Ui, Vec2};
+
+use crate::{
+    algorithm::Agent,
+    common::{Environment, World},
+};
+
+// Maximum number of points kept in a sparkline path.
+const SPARKLINE_POINTS: usize = 64;
+const SPARKLINE_SIZE: [f32; 2] = [80.0, 24.0];
+
+/// Runs a copy of the agent headlessly for up to `max_steps` steps (or until it wins)
+/// and returns a downsampled path of the player's positions, for use with [`sparkline_ui`].
+///
+/// This is meant to be called on the training thread, so the path can be sent along with the agent.
+pub fn sparkline_path<AgentType: Agent>(
+    world: &World,
+    agent: &AgentType,
+    max_steps: usize,
+) -> Vec<[f32; 2]> {
+    let mut agent = agent.clone();
+    let (mut environment, _) = Environment::from_world_with_config(world, agent.env_config());
+
+    environment
+        .rollout(&mut agent, max_steps, true)
+        .trajectory
+        .unwrap()
+        .downsampled_positions(SPARKLINE_POINTS)
+}
+
+/// Draws a small polyline of a path created by [`sparkline_path`].
+/// The path is scaled uniformly to fit, with y pointing up.
+pub fn sparkline_ui(ui: &mut Ui, path: &[[f32; 2]]) -> egui::Response {
+    let (response, painter) = ui.allocate_painter(Vec2::from(SPARKLINE_SIZE), Sense::hover());
+    let rect = response.rect.shrink(2.0);
+    painter.rect_filled(response.rect, 2.0, Color32::from_gray(235));
+
+    if path.len() < 2 {
+        return response;
+    }
+
+    let (mut min, mut max) = ([f32::INFINITY; 2], [f32::NEG_INFINITY; 2]);
+    for point in path {
+        for axis in 0..2 {
+            min[axis] = min[axis].min(point[axis]);
+            max[axis] = max[axis].max(point[axis]);
+        }
+    }
+    let scale = (rect.width() / (max[0] - min[0]))
+        .min(rect.height() / (max[1] - min[1]))
+        .min(1.0);
+    let center = [(min[0] + max[0]) / 2.0, (min[1] + max[1]) / 2.0];
+
+    let points: Vec<_> = path
+        .iter()
+        .map(|point| {
+            Pos2::new(
+                rect.center().x + (point[0] - center[0]) * scale,
+                rect.center().y - (point[1] - center[1]) * scale,
+            )
+        })
+        .collect();
+    painter.circle_filled(points[0], 2.0, Color32::GRAY);
+    painter.add(egui::Shape::line(
+        points,
+        Stroke::new(1.0, Color32::DARK_BLUE),
+    ));
+    response
+}
@@ -0,0 +1,576 @@
+use std::{fs, path::Path};
+
+use glam::{Affine2, Vec2};
+use roxmltree::{Document, Node};
+
+use crate::{
+    common::{ObjectAndTransform, WorldObject},
+    error::{Error, Result},
+    prefab::Prefab,
+};
+
+// Number of line segments replacing each curve of a path.
+const CURVE_SEGMENTS: usize = 8;
+
+impl Prefab {
+    /// Imports the shapes of an SVG file as a prefab named after the file, see [`Prefab::from_svg`].
+    pub fn import_svg(path: impl AsRef<Path>) -> Result<Prefab> {
+        let path = path.as_ref();
+        let contents = fs::read_to_string(path).map_err(|source| Error::SvgIo {
+            path: path.to_path_buf(),
+            source,
+        })?;
+        let name = path
+            .file_stem()
+            .map_or_else(String::new, |name| name.to_string_lossy().into_owned());
+        Prefab::from_svg(name, &contents)
+    }
+
+    /// Imports the shapes of an SVG image, for levels drawn in vector editors like Inkscape.
+    ///
+    /// Rectangles become fixed blocks, or polygons when they're skewed. Polygons become polygons,
+    /// and each subpath of a path becomes a polygon. Curves are replaced by line segments and arcs by straight lines.
+    /// Shapes filled with a color written as `#rrggbb` or `#rgb` keep it as their custom color.
+    /// Other elements, and shapes in definitions like `<defs>`, are ignored.
+    ///
+    /// One unit of the image is one world unit, and the image's y axis points down so it's flipped.
+    pub fn from_svg(name: String, svg: &str) -> Result<Prefab> {
+        let document = Document::parse(svg).map_err(Error::SvgXml)?;
+        let mut objects = vec![];
+        for node in document.root_element().descendants() {
+            if node.ancestors().any(|node| {
+                matches!(
+                    node.tag_name().name(),
+                    "defs" | "clipPath" | "mask" | "pattern" | "symbol" | "marker"
+                ) || node.attribute("display") == Some("none")
+            }) {
+                continue;
+            }
+            let shapes = match node.tag_name().name() {
+                "rect" => {
+                    let corner = Vec2::new(number(node, "x")?, number(node, "y")?);
+                    let size = Vec2::new(number(node, "width")?, number(node, "height")?);
+                    vec![Shape::Rect([
+                        corner,
+                        corner + Vec2::new(size.x, 0.0),
+                        corner + size,
+                        corner + Vec2::new(0.0, size.y),
+                    ])]
+                }
+                "polygon" => {
+                    let numbers = parse_numbers(node.attribute("points").unwrap_or(""))?;
+                    vec![Shape::Polygon(
+                        numbers
+                            .chunks_exact(2)
+                            .map(|point| Vec2::new(point[0], point[1]))
+                            .collect(),
+                    )]
+                }
+                "path" => parse_path(node.attribute("d").unwrap_or(""))?
+                    .into_iter()
+                    .map(Shape::Polygon)
+                    .collect(),
+                _ => continue,
+            };
+
+            let transform = node_transform(node)?;
+            let color = fill_color(node);
+            for shape in shapes {
+                let object_and_transform = match shape {
+                    Shape::Rect(corners) => {
+                        rect_object(corners.map(|corner| world_point(transform, corner)))
+                    }
+                    Shape::Polygon(points) => polygon_object(
+                        points
+                            .into_iter()
+                            .map(|point| world_point(transform, point))
+                            .collect(),
+                    ),
+                };
+                if let Some(mut object_and_transform) = object_and_transform {
+                    object_and_transform.position[2] = objects.len() as f32;
+                    object_and_transform.color = color;
+                    objects.push(object_and_transform);
+                }
+            }
+        }
+        Ok(Prefab::new(name, objects))
+    }
+}
+
+enum Shape {
+    // The corners, in order around the rectangle.
+    Rect([Vec2; 4]),
+    Polygon(Vec<Vec2>),
+}
+
+// A point of the image in world coordinates, where the y axis points up.
+fn world_point(transform: Affine2, point: Vec2) -> Vec2 {
+    let point = transform.transform_point2(point);
+    Vec2::new(point.x, -point.y)
+}
+
+// A block, or a polygon if the transform skewed the rectangle.
+fn rect_object(corners: [Vec2; 4]) -> Option<ObjectAndTransform> {
+    let x_axis = corners[1] - corners[0];
+    let y_axis = corners[3] - corners[0];
+    if x_axis.dot(y_axis).abs() > 1e-3 * x_axis.length() * y_axis.length() {
+        return polygon_object(corners.to_vec());
+    }
+    if x_axis.length() == 0.0 || y_axis.length() == 0.0 {
+        return None;
+    }
+    let center = (corners[0] + corners[2]) / 2.0;
+    Some(ObjectAndTransform {
+        object: WorldObject::Block {
+            fixed: true,
+            animation: None,
+            ccd: false,
+            friction: None,
+            restitution: None,
+            density: 1.0,
+            material: None,
+        },
+        position: [center.x, center.y, 0.0],
+        scale: [x_axis.length(), y_axis.length()],
+        rotation: x_axis.y.atan2(x_axis.x),
+        color: None,
+    })
+}
+
+// A polygon scaled to the bounding box of the points, None if the points don't cover an area.
+fn polygon_object(mut points: Vec<Vec2>) -> Option<ObjectAndTransform> {
+    // Closed subpaths end with their first point.
+    if points.len() > 1 && points.first() == points.last() {
+        points.pop();
+    }
+    let min = points.iter().copied().reduce(Vec2::min)?;
+    let max = points.iter().copied().reduce(Vec2::max)?;
+    let size = max - min;
+    if points.len() < 3 || size.x == 0.0 || size.y == 0.0 {
+        return None;
+    }
+    let center = (min + max) / 2.0;
+    Some(ObjectAndTransform {
+        object: WorldObject::Polygon {
+            points: points
+                .into_iter()
+                .map(|point| ((point - center) / size).to_array())
+                .collect(),
+        },
+        position: [center.x, center.y, 0.0],
+        scale: size.to_array(),
+        rotation: 0.0,
+        color: None,
+    })
+}
+
+fn number(node: Node, name: &str) -> Result<f32> {
+    let Some(value) = node.attribute(name) else {
+        return Ok(0.0);
+    };
+    // Lengths can have units, which are ignored.
+    let value = value.trim_end_matches(|character: char| character.is_ascii_alphabetic());
+    value
+        .trim()
+        .parse()
+        .map_err(|_| Error::SvgFormat(format!("invalid {name} \"{value}\"")))
+}
+
+// The color the shape is filled with, from its style or its attributes, or the ones of the groups it's in.
+fn fill_color(node: Node) -> Option<[f32; 4]> {
+    let fill = node.ancestors().find_map(|node| {
+        node.attribute("style")
+            .and_then(|style| {
+                style.split(';').find_map(|declaration| {
+                    let (property, value) = declaration.split_once(':')?;
+                    (property.trim() == "fill").then_some(value.trim())
+                })
+            })
+            .or_else(|| node.attribute("fill"))
+    })?;
+    let hex = fill.strip_prefix('#').filter(|hex| hex.is_ascii())?;
+    let component = |digits: &str| u8::from_str_radix(digits, 16).ok();
+    let [red, green, blue] = match hex.len() {
+        6 => [
+            component(&hex[0..2])?,
+            component(&hex[2..4])?,
+            component(&hex[4..6])?,
+        ],
+        // #rgb is the same as #rrggbb.
+        3 => [
+            component(&hex[0..1])? * 17,
+            component(&hex[1..2])? * 17,
+            component(&hex[2..3])? * 17,
+        ],
+        _ => return None,
+    };
+    Some([
+        red as f32 / 255.0,
+        green as f32 / 255.0,
+        blue as f32 / 255.0,
+        1.0,
+    ])
+}
+
+// The transform from the node's coordinates to the image's coordinates, combining the transforms of its groups.
+fn node_transform(node: Node) -> Result<Affine2> {
+    let mut transform = Affine2::IDENTITY;
+    for node in node.ancestors() {
+        if let Some(value) = node.attribute("transform") {
+            transform = parse_transform(value)? * transform;
+        }
+    }
+    Ok(transform)
+}
+
+// Parses a transform list like "translate(10, 20) rotate(45)".
+fn parse_transform(value: &str) -> Result<Affine2> {
+    let invalid = || Error::SvgFormat(format!("invalid transform \"{value}\""));
+    let mut transform = Affine2::IDENTITY;
+    let mut rest = value.trim();
+    while !rest.is_empty() {
+        let (name, arguments) = rest.split_once('(').ok_or_else(invalid)?;
+        let (arguments, remaining) = arguments.split_once(')').ok_or_else(invalid)?;
+        rest = remaining
+            .trim_start_matches(|character: char| character.is_whitespace() || character == ',');
+        let arguments = parse_numbers(arguments)?;
+        let argument = |index: usize| arguments.get(index).copied();
+        transform = transform
+            * match (name.trim(), arguments.len()) {
+                ("matrix", 6) => Affine2::from_cols_array(&[
+                    arguments[0],
+                    arguments[1],
+                    arguments[2],
+                    arguments[3],
+                    arguments[4],
+                    arguments[5],
+                ]),
+                ("translate", 1 | 2) => {
+                    Affine2::from_translation(Vec2::new(arguments[0], argument(1).unwrap_or(0.0)))
+                }
+                ("scale", 1 | 2) => Affine2::from_scale(Vec2::new(
+                    arguments[0],
+                    argument(1).unwrap_or(arguments[0]),
+                )),
+                ("rotate", 1 | 3) => {
+                    let center = Vec2::new(argument(1).unwrap_or(0.0), argument(2).unwrap_or(0.0));
+                    Affine2::from_translation(center)
+                        * Affine2::from_angle(arguments[0].to_radians())
+                        * Affine2::from_translation(-center)
+                }
+                ("skewX", 1) => Affine2::from_cols_array(&[
+                    1.0,
+                    0.0,
+                    arguments[0].to_radians().tan(),
+                    1.0,
+                    0.0,
+                    0.0,
+                ]),
+                ("skewY", 1) => Affine2::from_cols_array(&[
+                    1.0,
+                    arguments[0].to_radians().tan(),
+                    0.0,
+                    1.0,
+                    0.0,
+                    0.0,
+                ]),
+                _ => return Err(invalid()),
+            };
+    }
+    Ok(transform)
+}
+
+// Parses numbers separated by whitespace or commas, like the points of a polygon.
+fn parse_numbers(value: &str) -> Result<Vec<f32>> {
+    let mut parser = PathParser::new(value);
+    let mut numbers = vec![];
+    while let Some(number) = parser.number()? {
+        numbers.push(number);
+    }
+    if !parser.is_done() {
+        return Err(Error::SvgFormat(format!("invalid numbers \"{value}\"")));
+    }
+    Ok(numbers)
+}
+
+// Parses path data into its subpaths, as the points of their line segments.
+fn parse_path(data: &str) -> Result<Vec<Vec<Vec2>>> {
+    let mut parser = PathParser::new(data);
+    let mut subpaths: Vec<Vec<Vec2>> = vec![];
+    let mut current = Vec2::ZERO;
+    let mut start = Vec2::ZERO;
+    // The second control point of the last curve, reflected by the smooth curve commands.
+    let mut last_control = None;
+    let mut command = None;
+    // Whether the last subpath was closed, drawing after closing it starts a new subpath at the same point.
+    let mut closed = false;
+    loop {
+        if let Some(next) = parser.command() {
+            command = Some(next);
+        } else if parser.is_done() {
+            break;
+        }
+        let Some(name) = command else {
+            return Err(Error::SvgFormat(format!("invalid path \"{data}\"")));
+        };
+        let relative = name.is_ascii_lowercase();
+        if closed && !matches!(name, 'M' | 'm' | 'Z' | 'z') {
+            subpaths.push(vec![current]);
+        }
+        closed = false;
+        let origin = if relative { current } else { Vec2::ZERO };
+        let mut control = None;
+        match name.to_ascii_uppercase() {
+            'M' => {
+                current = origin + parser.point()?;
+                start = current;
+                subpaths.push(vec![current]);
+                // Pairs after the first one are lines.
+                command = Some(if relative { 'l' } else { 'L' });
+            }
+            'L' => current = origin + parser.point()?,
+            'H' => current.x = origin.x + parser.required_number()?,
+            'V' => current.y = origin.y + parser.required_number()?,
+            'Z' => {
+                current = start;
+                command = None;
+                closed = true;
+            }
+            'C' | 'S' => {
+                let first = if name.to_ascii_uppercase() == 'C' {
+                    origin + parser.point()?
+                } else {
+                    last_control.map_or(current, |last_control| 2.0 * current - last_control)
+                };
+                let second = origin + parser.point()?;
+                let end = origin + parser.point()?;
+                let points = subpaths.last_mut().ok_or_else(|| invalid_path(data))?;
+                points.extend((1..=CURVE_SEGMENTS).map(|segment| {
+                    let t = segment as f32 / CURVE_SEGMENTS as f32;
+                    let u = 1.0 - t;
+                    u * u * u * current
+                        + 3.0 * u * u * t * first
+                        + 3.0 * u * t * t * second
+                        + t * t * t * end
+                }));
+                control = Some(second);
+                current = end;
+            }
+            'Q' | 'T' => {
+                let first = if name.to_ascii_uppercase() == 'Q' {
+                    origin + parser.point()?
+                } else {
+                    last_control.map_or(current, |last_control| 2.0 * current - last_control)
+                };
+                let end = origin + parser.point()?;
+                let points = subpaths.last_mut().ok_or_else(|| invalid_path(data))?;
+                points.extend((1..=CURVE_SEGMENTS).map(|segment| {
+                    let t = segment as f32 / CURVE_SEGMENTS as f32;
+                    let u = 1.0 - t;
+                    u * u * current + 2.0 * u * t * first + t * t * end
+                }));
+                control = Some(first);
+                current = end;
+            }
+            'A' => {
+                // The radii, rotation and flags of the arc are skipped, it's drawn as a line.
+                for _ in 0..3 {
+                    parser.required_number()?;
+                }
+                for _ in 0..2 {
+                    parser.flag()?;
+                }
+                current = origin + parser.point()?;
+            }
+            _ => return Err(invalid_path(data)),
+        }
+        last_control = control;
+        if !matches!(
+            name,
+            'M' | 'm' | 'C' | 'c' | 'S' | 's' | 'Q' | 'q' | 'T' | 't'
+        ) {
+            subpaths
+                .last_mut()
+                .ok_or_else(|| invalid_path(data))?
+                .push(current);
+        }
+    }
+    Ok(subpaths)
+}
+
+fn invalid_path(data: &str) -> Error {
+    Error::SvgFormat(format!("invalid path \"{data}\""))
+}
+
+// Reads the commands and numbers of path data, which can be separated by whitespace or commas,
+// or not separated at all when it's unambiguous, like in "M10-20L.5.5".
+struct PathParser<'a> {
+    data: &'a str,
+    index: usize,
+}
+
+impl<'a> PathParser<'a> {
+    fn new(data: &'a str) -> PathParser<'a> {
+        PathParser { data, index: 0 }
+    }
+
+    fn skip_separators(&mut self) {
+        let rest = &self.data[self.index..];
+        let trimmed = rest
+            .trim_start_matches(|character: char| character.is_whitespace() || character == ',');
+        self.index += rest.len() - trimmed.len();
+    }
+
+    fn is_done(&mut self) -> bool {
+        self.skip_separators();
+        self.index == self.data.len()
+    }
+
+    fn command(&mut self) -> Option<char> {
+        self.skip_separators();
+        let character = self.data[self.index..].chars().next()?;
+        // The exponent of a number isn't a command.
+        if character.is_ascii_alphabetic() && !matches!(character, 'e' | 'E') {
+            self.index += 1;
+            Some(character)
+        } else {
+            None
+        }
+    }
+
+    fn number(&mut self) -> Result<Option<f32>> {
+        self.skip_separators();
+        let bytes = self.data.as_bytes();
+        let start = self.index;
+        let mut end = start;
+        if matches!(bytes.get(end), Some(b'+' | b'-')) {
+            end += 1;
+        }
+        let mut seen_point = false;
+        while let Some(byte) = bytes.get(end) {
+            match byte {
+                b'0'..=b'9' => {}
+                b'.' if !seen_point => seen_point = true,
+                _ => break,
+            }
+            end += 1;
+        }
+        if matches!(bytes.get(end), Some(b'e' | b'E')) {
+            end += 1;
+            if matches!(bytes.get(end), Some(b'+' | b'-')) {
+                end += 1;
+            }
+            while matches!(bytes.get(end), Some(b'0'..=b'9')) {
+                end += 1;
+            }
+        }
+        if end == start {
+            return Ok(None);
+        }
+        let number = &self.data[start..end];
+        self.index = end;
+        number
+            .parse()
+            .map(Some)
+            .map_err(|_| Error::SvgFormat(format!("invalid number \"{number}\"")))
+    }
+
+    fn required_number(&mut self) -> Result<f32> {
+        self.number()?
+            .ok_or_else(|| Error::SvgFormat(format!("missing number in \"{}\"", self.data)))
+    }
+
+    fn point(&mut self) -> Result<Vec2> {
+        Ok(Vec2::new(self.required_number()?, self.required_number()?))
+    }
+
+    // The flags of arcs are a single character, so they don't need to be separated, like in "a5 5 0 0110 10".
+    fn flag(&mut self) -> Result<bool> {
+        self.skip_separators();
+        let flag = match self.data.as_bytes().get(self.index) {
+            Some(b'0') => false,
+            Some(b'1') => true,
+            _ => {
+                return Err(Error::SvgFormat(format!(
+                    "missing arc flag in \"{}\"",
+                    self.data
+                )))
+            }
+        };
+        self.index += 1;
+        Ok(flag)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use glam::Vec2;
+
+    use super::parse_path;
+
+    fn points(subpaths: &[&[[f32; 2]]]) -> Vec<Vec<Vec2>> {
+        subpaths
+            .iter()
+            .map(|subpath| subpath.iter().copied().map(Vec2::from).collect())
+            .collect()
+    }
+
+    #[test]
+    fn relative_commands() {
+        assert_eq!(
+            parse_path("m10 10 l5 0 h-2 v4 z").unwrap(),
+            points(&[&[
+                [10.0, 10.0],
+                [15.0, 10.0],
+                [13.0, 10.0],
+                [13.0, 14.0],
+                [10.0, 10.0]
+            ]])
+        );
+    }
+
+    #[test]
+    fn implicit_lines_after_move() {
+        assert_eq!(
+            parse_path("M0 0 10 0 10 10").unwrap(),
+            points(&[&[[0.0, 0.0], [10.0, 0.0], [10.0, 10.0]]])
+        );
+        assert_eq!(
+            parse_path("m1 1 2 0 0 2").unwrap(),
+            points(&[&[[1.0, 1.0], [3.0, 1.0], [3.0, 3.0]]])
+        );
+    }
+
+    #[test]
+    fn drawing_after_close() {
+        assert_eq!(
+            parse_path("M0 0 L10 0 Z L0 10").unwrap(),
+            points(&[
+                &[[0.0, 0.0], [10.0, 0.0], [0.0, 0.0]],
+                &[[0.0, 0.0], [0.0, 10.0]]
+            ])
+        );
+    }
+
+    #[test]
+    fn exponents_and_compact_numbers() {
+        assert_eq!(
+            parse_path("M1e1-2E-1L.5.5").unwrap(),
+            points(&[&[[10.0, -0.2], [0.5, 0.5]]])
+        );
+    }
+
+    #[test]
+    fn compact_arc_flags() {
+        assert_eq!(
+            parse_path("M0 0a5 5 0 0110 10").unwrap(),
+            points(&[&[[0.0, 0.0], [10.0, 10.0]]])
+        );
+        assert_eq!(
+            parse_path("M0 0A5,5,30,1,0,10,10").unwrap(),
+            points(&[&[[0.0, 0.0], [10.0, 10.0]]])
+        );
+        assert!(parse_path("M0 0a5 5 0 2 0 10 10").is_err());
+    }
+}
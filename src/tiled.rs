@@ -0,0 +1,488 @@
+use std::{collections::HashMap, f32::consts::TAU, fs, path::Path, str::FromStr};
+
+use glam::Vec2;
+use roxmltree::{Document, Node};
+
+use crate::{
+    common::{ObjectAndTransform, World, WorldObject},
+    error::{Error, Result},
+};
+
+// Number of sides of the polygons approximating ellipse objects.
+const ELLIPSE_SIDES: usize = 16;
+
+impl World {
+    /// Imports a map made with the [Tiled](https://www.mapeditor.org) editor from a TMX file.
+    /// See [`World::from_tmx`].
+    pub fn import_tmx(path: impl AsRef<Path>) -> Result<World> {
+        let path = path.as_ref();
+        let contents = fs::read_to_string(path).map_err(|source| Error::TiledIo {
+            path: path.to_path_buf(),
+            source,
+        })?;
+        World::from_tmx(&contents)
+    }
+
+    /// Imports a map made with the [Tiled](https://www.mapeditor.org) editor from the contents of a TMX file.
+    ///
+    /// The objects of the object layers become world objects depending on their class (their type before Tiled 1.9):
+    /// - no class or `block`: a fixed block, or a polygon for polygon objects.
+    /// - `movable`: a block which isn't fixed.
+    /// - `goal` and `hazard`: a goal or a hazard, with the reward given by their `reward` property if they have one.
+    ///   Goals with a true `optional` property are optional.
+    /// - `coin` and `checkpoint`: a coin or a checkpoint.
+    /// - `player`: the position of the first player, or of another player if the map already has one.
+    ///
+    /// Ellipse blocks become polygons with 16 sides approximating them, other ellipse objects fill their bounding rectangle.
+    /// Objects with other classes, polyline and text objects, and point objects other than players are ignored.
+    /// Each tile of the tile layers becomes part of a fixed block, the tiles next to each other in a row are merged.
+    /// Hidden layers are ignored.
+    ///
+    /// One pixel of the map is one world unit, and the map's y axis points down so it's flipped.
+    /// Only orthogonal maps with a fixed size, whose tile layers are saved as CSV or XML, are supported.
+    pub fn from_tmx(tmx: &str) -> Result<World> {
+        let document = Document::parse(tmx)?;
+        let map = document.root_element();
+        if !map.has_tag_name("map") {
+            return Err(Error::TiledMap("the file isn't a Tiled map".to_string()));
+        }
+        if let Some(orientation) = map.attribute("orientation") {
+            if orientation != "orthogonal" {
+                return Err(Error::TiledMap(format!(
+                    "{orientation} maps aren't supported, only orthogonal maps are"
+                )));
+            }
+        }
+        if map.attribute("infinite") == Some("1") {
+            return Err(Error::TiledMap(
+                "infinite maps aren't supported".to_string(),
+            ));
+        }
+        let tile_size = Vec2::new(
+            required_attribute(map, "tilewidth")?,
+            required_attribute(map, "tileheight")?,
+        );
+
+        let mut world = World::default();
+        let mut player_found = false;
+        for layer in map.descendants() {
+            // Tilesets have object groups for the collision shapes of their tiles.
+            if !(layer.has_tag_name("layer") || layer.has_tag_name("objectgroup"))
+                || layer.ancestors().any(|node| node.has_tag_name("tileset"))
+            {
+                continue;
+            }
+            // Layers are hidden and offset with the group layers they're in.
+            let mut offset = Vec2::ZERO;
+            let mut visible = true;
+            for node in layer.ancestors() {
+                offset.x += attribute(node, "offsetx")?.unwrap_or(0.0);
+                offset.y += attribute(node, "offsety")?.unwrap_or(0.0);
+                visible &= node.attribute("visible") != Some("0");
+            }
+            if !visible {
+                continue;
+            }
+
+            if layer.has_tag_name("layer") {
+                for (corner, size) in tile_layer_rects(layer, tile_size)? {
+                    let (position, rotation) = world_transform(offset + corner, size, 0.0, false);
+                    world.objects.push(object_and_transform(
+                        block(true),
+                        position,
+                        size,
+                        rotation,
+                        world.objects.len(),
+                    ));
+                }
+                continue;
+            }
+
+            for object in layer.children().filter(|node| node.has_tag_name("object")) {
+                let class = object
+                    .attribute("class")
+                    .or_else(|| object.attribute("type"))
+                    .unwrap_or("")
+                    .to_lowercase();
+                let corner = offset
+                    + Vec2::new(
+                        attribute(object, "x")?.unwrap_or(0.0),
+                        attribute(object, "y")?.unwrap_or(0.0),
+                    );
+                let size = Vec2::new(
+                    attribute(object, "width")?.unwrap_or(0.0),
+                    attribute(object, "height")?.unwrap_or(0.0),
+                );
+                let rotation = attribute(object, "rotation")?.unwrap_or(0.0);
+                // Tile objects are positioned by their bottom left corner instead of their top left corner.
+                let tile_object = object.attribute("gid").is_some();
+                let has_child = |name: &str| object.children().any(|node| node.has_tag_name(name));
+                if has_child("text") {
+                    continue;
+                }
+
+                if class == "player" {
+                    let (position, _) = world_transform(corner, size, rotation, tile_object);
+                    if player_found {
+                        world.other_players.push(position);
+                    } else {
+                        world.player_position = position;
+                        player_found = true;
+                    }
+                    continue;
+                }
+                // Polylines aren't closed, and points have no size so only players can be points.
+                if has_child("polyline") || has_child("point") {
+                    continue;
+                }
+                let properties = properties(object)?;
+                let world_object = match class.as_str() {
+                    "" | "block" => {
+                        if let Some(polygon) =
+                            object.children().find(|node| node.has_tag_name("polygon"))
+                        {
+                            let (world_object, position, size, rotation) =
+                                polygon_object(polygon, corner, rotation)?;
+                            world.objects.push(object_and_transform(
+                                world_object,
+                                position,
+                                size,
+                                rotation,
+                                world.objects.len(),
+                            ));
+                            continue;
+                        }
+                        if has_child("ellipse") {
+                            let (position, rotation) =
+                                world_transform(corner, size, rotation, false);
+                            world.objects.push(object_and_transform(
+                                ellipse_polygon(),
+                                position,
+                                size,
+                                rotation,
+                                world.objects.len(),
+                            ));
+                            continue;
+                        }
+                        block(true)
+                    }
+                    "movable" => block(false),
+                    "goal" => WorldObject::Goal {
+                        reward: property(&properties, "reward")?.unwrap_or(1.0),
+                        optional: property(&properties, "optional")?.unwrap_or(false),
+                    },
+                    "hazard" => WorldObject::Hazard {
+                        reward: property(&properties, "reward")?.unwrap_or(-1.0),
+                    },
+                    "coin" => WorldObject::Coin,
+                    "checkpoint" => WorldObject::Checkpoint,
+                    _ => continue,
+                };
+                let (position, rotation) = world_transform(corner, size, rotation, tile_object);
+                world.objects.push(object_and_transform(
+                    world_object,
+                    position,
+                    size,
+                    rotation,
+                    world.objects.len(),
+                ));
+            }
+        }
+        Ok(world)
+    }
+}
+
+fn block(fixed: bool) -> WorldObject {
+    WorldObject::Block {
+        fixed,
+        animation: None,
+        ccd: false,
+        friction: None,
+        restitution: None,
+        density: 1.0,
+        material: None,
+    }
+}
+
+// Objects are added in front of the objects before them, like in Tiled.
+fn object_and_transform(
+    object: WorldObject,
+    position: [f32; 2],
+    size: Vec2,
+    rotation: f32,
+    depth: usize,
+) -> ObjectAndTransform {
+    ObjectAndTransform {
+        object,
+        position: [position[0], position[1], depth as f32],
+        scale: size.to_array(),
+        rotation,
+        color: None,
+    }
+}
+
+// The world position of the center of a rectangle and its rotation in radians, from the rectangle's corner
+// in Tiled's coordinates (with the y axis pointing down) and its clockwise rotation in degrees around the corner.
+fn world_transform(
+    corner: Vec2,
+    size: Vec2,
+    rotation: f32,
+    bottom_corner: bool,
+) -> ([f32; 2], f32) {
+    let rotation = rotation.to_radians();
+    let half_size = if bottom_corner {
+        Vec2::new(size.x, -size.y) / 2.0
+    } else {
+        size / 2.0
+    };
+    // Rotating clockwise with the y axis pointing down is rotating counterclockwise with it pointing up.
+    let center = corner + Vec2::from_angle(rotation).rotate(half_size);
+    ([center.x, -center.y], -rotation)
+}
+
+// A polygon object, with its position, size and rotation.
+fn polygon_object(
+    polygon: Node,
+    corner: Vec2,
+    rotation: f32,
+) -> Result<(WorldObject, [f32; 2], Vec2, f32)> {
+    let points = polygon
+        .attribute("points")
+        .unwrap_or("")
+        .split_whitespace()
+        .map(|point| {
+            let (x, y) = point
+                .split_once(',')
+                .ok_or_else(|| Error::TiledMap(format!("invalid polygon point \"{point}\"")))?;
+            Ok(Vec2::new(parse(x)?, parse(y)?))
+        })
+        .collect::<Result<Vec<_>>>()?;
+    let min = points.iter().copied().reduce(Vec2::min).unwrap_or_default();
+    let max = points.iter().copied().reduce(Vec2::max).unwrap_or_default();
+    let size = max - min;
+    let center = (min + max) / 2.0;
+    // The points are relative to the corner, so the bounding box is rotated around it too.
+    let (position, rotation) = world_transform(
+        corner + Vec2::from_angle(rotation.to_radians()).rotate(min),
+        size,
+        rotation,
+        false,
+    );
+    let points = points
+        .into_iter()
+        .map(|point| {
+            let point = (point - center) / size.max(Vec2::splat(f32::EPSILON));
+            [point.x, -point.y]
+        })
+        .collect();
+    Ok((WorldObject::Polygon { points }, position, size, rotation))
+}
+
+// A polygon approximating the ellipse filling its bounding rectangle.
+fn ellipse_polygon() -> WorldObject {
+    let points = (0..ELLIPSE_SIDES)
+        .map(|side| {
+            let point = 0.5 * Vec2::from_angle(side as f32 * TAU / ELLIPSE_SIDES as f32);
+            point.to_array()
+        })
+        .collect();
+    WorldObject::Polygon { points }
+}
+
+// The rectangles covered by the tiles of a tile layer, relative to the layer's top left corner.
+fn tile_layer_rects(layer: Node, tile_size: Vec2) -> Result<Vec<(Vec2, Vec2)>> {
+    let width: usize = required_attribute(layer, "width")?;
+    let Some(data) = layer.children().find(|node| node.has_tag_name("data")) else {
+        return Ok(vec![]);
+    };
+    // The ids of the tiles, 0 for empty tiles.
+    let tiles = match data.attribute("encoding") {
+        Some("csv") => data
+            .text()
+            .unwrap_or("")
+            .split(',')
+            .map(|tile| parse::<u32>(tile.trim()))
+            .collect::<Result<Vec<_>>>()?,
+        None => data
+            .children()
+            .filter(|node| node.has_tag_name("tile"))
+            .map(|tile| Ok(attribute::<u32>(tile, "gid")?.unwrap_or(0)))
+            .collect::<Result<Vec<_>>>()?,
+        Some(encoding) => {
+            return Err(Error::TiledMap(format!(
+                "tile layers with the {encoding} encoding aren't supported, \
+                 save the map with the CSV tile layer format"
+            )))
+        }
+    };
+
+    let mut rects = vec![];
+    for (row, tiles) in tiles.chunks(width.max(1)).enumerate() {
+        let mut column = 0;
+        while column < tiles.len() {
+            if tiles[column] == 0 {
+                column += 1;
+                continue;
+            }
+            let start = column;
+            while column < tiles.len() && tiles[column] != 0 {
+                column += 1;
+            }
+            rects.push((
+                Vec2::new(start as f32, row as f32) * tile_size,
+                Vec2::new((column - start) as f32, 1.0) * tile_size,
+            ));
+        }
+    }
+    Ok(rects)
+}
+
+// The custom properties of an object, by name.
+fn properties<'a>(object: Node<'a, '_>) -> Result<HashMap<&'a str, &'a str>> {
+    let mut properties = HashMap::new();
+    for property in object
+        .children()
+        .filter(|node| node.has_tag_name("properties"))
+        .flat_map(|node| node.children())
+        .filter(|node| node.has_tag_name("property"))
+    {
+        let name = property
+            .attribute("name")
+            .ok_or_else(|| Error::TiledMap("a property doesn't have a name".to_string()))?;
+        properties.insert(name, property.attribute("value").unwrap_or(""));
+    }
+    Ok(properties)
+}
+
+fn property<T: FromStr>(properties: &HashMap<&str, &str>, name: &str) -> Result<Option<T>> {
+    properties.get(name).map(|value| parse(value)).transpose()
+}
+
+fn attribute<T: FromStr>(node: Node, name: &str) -> Result<Option<T>> {
+    node.attribute(name).map(parse).transpose()
+}
+
+fn required_attribute<T: FromStr>(node: Node, name: &str) -> Result<T> {
+    attribute(node, name)?.ok_or_else(|| {
+        Error::TiledMap(format!(
+            "a {} doesn't have the {name} attribute",
+            node.tag_name().name()
+        ))
+    })
+}
+
+fn parse<T: FromStr>(value: &str) -> Result<T> {
+    value
+        .parse()
+        .map_err(|_| Error::TiledMap(format!("invalid value \"{value}\"")))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::common::{World, WorldObject};
+
+    #[test]
+    fn csv_tile_layer() {
+        let world = World::from_tmx(
+            r#"<map orientation="orthogonal" width="3" height="2" tilewidth="16" tileheight="16">
+                <layer id="1" name="Tiles" width="3" height="2">
+                    <data encoding="csv">
+1,1,0,
+0,1,1
+</data>
+                </layer>
+            </map>"#,
+        )
+        .unwrap();
+        let rects: Vec<_> = world
+            .objects
+            .iter()
+            .map(|object| (object.position, object.scale))
+            .collect();
+        assert_eq!(
+            rects,
+            [
+                ([16.0, -8.0, 0.0], [32.0, 16.0]),
+                ([32.0, -24.0, 1.0], [32.0, 16.0])
+            ]
+        );
+        assert!(world
+            .objects
+            .iter()
+            .all(|object| matches!(object.object, WorldObject::Block { fixed: true, .. })));
+    }
+
+    #[test]
+    fn polygon_object() {
+        let world = World::from_tmx(
+            r#"<map orientation="orthogonal" tilewidth="16" tileheight="16">
+                <objectgroup id="1" name="Objects">
+                    <object id="1" x="100" y="50">
+                        <polygon points="0,0 20,0 20,10"/>
+                    </object>
+                </objectgroup>
+            </map>"#,
+        )
+        .unwrap();
+        assert_eq!(world.objects.len(), 1);
+        let object = &world.objects[0];
+        assert_eq!(object.position, [110.0, -55.0, 0.0]);
+        assert_eq!(object.scale, [20.0, 10.0]);
+        let WorldObject::Polygon { points } = &object.object else {
+            panic!("expected a polygon, got {:?}", object.object);
+        };
+        assert_eq!(points, &[[-0.5, 0.5], [0.5, 0.5], [0.5, -0.5]]);
+    }
+
+    #[test]
+    fn offset_group_layer() {
+        let world = World::from_tmx(
+            r#"<map orientation="orthogonal" tilewidth="16" tileheight="16">
+                <group id="1" name="Group" offsetx="10" offsety="20">
+                    <objectgroup id="2" name="Goals" offsetx="5">
+                        <object id="1" type="goal" x="0" y="0" width="10" height="10"/>
+                    </objectgroup>
+                </group>
+                <group id="3" name="Hidden" visible="0">
+                    <objectgroup id="4" name="Coins">
+                        <object id="2" type="coin" x="0" y="0" width="10" height="10"/>
+                    </objectgroup>
+                </group>
+            </map>"#,
+        )
+        .unwrap();
+        assert_eq!(world.objects.len(), 1);
+        let object = &world.objects[0];
+        // The y axis is flipped, so the goal is below the origin.
+        assert_eq!(object.position, [20.0, -25.0, 0.0]);
+        assert_eq!(object.scale, [10.0, 10.0]);
+        assert!(matches!(
+            object.object,
+            WorldObject::Goal {
+                optional: false,
+                ..
+            }
+        ));
+    }
+
+    #[test]
+    fn point_text_and_ellipse_objects() {
+        let world = World::from_tmx(
+            r#"<map orientation="orthogonal" tilewidth="16" tileheight="16">
+                <objectgroup id="1" name="Objects">
+                    <object id="1" type="player" x="30" y="40"><point/></object>
+                    <object id="2" x="0" y="0"><point/></object>
+                    <object id="3" x="0" y="0" width="50" height="20"><text>Hello</text></object>
+                    <object id="4" x="0" y="0" width="40" height="20"><ellipse/></object>
+                </objectgroup>
+            </map>"#,
+        )
+        .unwrap();
+        assert_eq!(world.player_position, [30.0, -40.0]);
+        assert_eq!(world.objects.len(), 1);
+        let object = &world.objects[0];
+        assert_eq!(object.position, [20.0, -10.0, 0.0]);
+        assert_eq!(object.scale, [40.0, 20.0]);
+        assert!(matches!(&object.object, WorldObject::Polygon { points } if points.len() == 16));
+    }
+}
@@ -1,12 +1,15 @@
 use crate::{
     algorithm::{Agent, Algorithm, TrainingDetails},
     common::{
-        AppState, Environment, World, WorldObject, BEVY_TO_PHYSICS_SCALE, PLAYER_DEPTH,
-        PLAYER_RADIUS,
+        player_color, ActionNoise, AdaptiveStep, AppState, Disturbance, EnvConfig, Environment,
+        Move, World, BEVY_TO_PHYSICS_SCALE, PLAYER_RADIUS,
     },
+    overlay::OverlayPainter,
+    policy::{Policy, PolicyInspector, PolicyTable},
+    seed::{RunSeed, Seeds},
 };
 
-use bevy::{prelude::*, sprite::MaterialMesh2dBundle};
+use bevy::{prelude::*, sprite::MaterialMesh2dBundle, window::PrimaryWindow};
 use bevy_egui::{egui, EguiContexts};
 use crossbeam::channel::bounded;
 use rapier2d::prelude::*;
@@ -47,16 +50,18 @@ fn ui_system<
     mut meshes: ResMut<Assets<Mesh>>,
     mut materials: ResMut<Assets<ColorMaterial>>,
     world: Res<World>,
+    mut seeds: ResMut<Seeds>,
     visualization_objects: Query<Entity, With<VisualizationObject>>,
 ) {
     egui::Window::new("Train agents")
         .scroll2([false, true])
         .show(contexts.ctx_mut(), |ui| {
+            let ui_state = &mut *ui_state;
             if let Some(agent_receiver) = &mut ui_state.agent_receiver {
                 agent_receiver.receive_messages();
             }
 
-            match &ui_state.view {
+            match &mut ui_state.view {
                 View::Select => {
                     if ui.button("Back to editor").clicked() {
                         next_state.set(AppState::Editor);
@@ -68,6 +73,116 @@ fn ui_system<
 
                     ui.add_space(10.0);
 
+                    if let Some(env_config) = ui_state.agent.env_config_mut() {
+                        ui.collapsing("Environment settings", |ui| {
+                            ui.horizontal(|ui| {
+                                ui.label("Decision interval: ");
+                                ui.add(
+                                    egui::DragValue::new(&mut env_config.decision_interval)
+                                        .clamp_range(1..=100),
+                                );
+                            });
+                            // Only the physics steps of a single decision are merged, see AdaptiveStep.
+                            let decision_interval = env_config.decision_interval;
+                            if decision_interval < 2 {
+                                env_config.adaptive_step = None;
+                            }
+                            let mut adaptive = env_config.adaptive_step.is_some();
+                            ui.add_enabled(
+                                decision_interval >= 2,
+                                egui::Checkbox::new(
+                                    &mut adaptive,
+                                    "Merge physics steps away from goals",
+                                ),
+                            )
+                            .on_hover_text(
+                                "Faster, but less accurate. \
+                                Disable to verify the results.",
+                            )
+                            .on_disabled_hover_text(
+                                "Only the physics steps between two decisions are merged, \
+                                so the decision interval must be at least 2.",
+                            );
+                            if adaptive != env_config.adaptive_step.is_some() {
+                                env_config.adaptive_step = adaptive.then(AdaptiveStep::default);
+                            }
+                            if let Some(adaptive_step) = &mut env_config.adaptive_step {
+                                adaptive_step.coarse_factor =
+                                    adaptive_step.coarse_factor.min(decision_interval);
+                                ui.horizontal(|ui| {
+                                    ui.label("Steps merged: ");
+                                    ui.add(
+                                        egui::DragValue::new(&mut adaptive_step.coarse_factor)
+                                            .clamp_range(1..=decision_interval.min(4)),
+                                    );
+                                });
+                                ui.horizontal(|ui| {
+                                    ui.label("Minimum distance to goals: ");
+                                    ui.add(
+                                        egui::DragValue::new(&mut adaptive_step.fine_distance)
+                                            .clamp_range(0.0..=f32::INFINITY),
+                                    );
+                                });
+                            }
+                            let mut noisy = env_config.action_noise.is_some();
+                            ui.checkbox(&mut noisy, "Action noise (sticky actions)");
+                            if noisy != env_config.action_noise.is_some() {
+                                env_config.action_noise = noisy.then(ActionNoise::default);
+                            }
+                            if let Some(action_noise) = &mut env_config.action_noise {
+                                ui.horizontal(|ui| {
+                                    ui.label("Repeat probability: ");
+                                    ui.add(
+                                        egui::DragValue::new(&mut action_noise.repeat_probability)
+                                            .speed(0.01)
+                                            .clamp_range(0.0..=1.0),
+                                    );
+                                });
+                                ui.horizontal(|ui| {
+                                    ui.label("Flip probability: ");
+                                    ui.add(
+                                        egui::DragValue::new(&mut action_noise.flip_probability)
+                                            .speed(0.01)
+                                            .clamp_range(0.0..=1.0),
+                                    );
+                                });
+                            }
+                            let mut path_distance = env_config.path_distance.is_some();
+                            ui.checkbox(&mut path_distance, "Score with path distance to goals")
+                                .on_hover_text(
+                                    "Measures the distance around fixed blocks \
+                                    instead of in a straight line.",
+                                );
+                            if path_distance != env_config.path_distance.is_some() {
+                                env_config.path_distance = path_distance.then_some(PLAYER_RADIUS);
+                            }
+                            if let Some(cell_size) = &mut env_config.path_distance {
+                                ui.horizontal(|ui| {
+                                    ui.label("Cell size: ");
+                                    ui.add(
+                                        egui::DragValue::new(cell_size).clamp_range(1.0..=500.0),
+                                    );
+                                });
+                            }
+                            ui.horizontal(|ui| {
+                                ui.label("Spawn seed: ");
+                                ui.add(egui::DragValue::new(&mut env_config.spawn_seed));
+                            })
+                            .response
+                            .on_hover_text("Used when the world has a spawn region.");
+                            ui.add_space(5.0);
+                            ui.label("Disturbances: ");
+                            disturbances_ui(ui, &mut env_config.disturbances);
+                        });
+                        ui.add_space(10.0);
+                    }
+
+                    ui.collapsing("Seeds", |ui| {
+                        seeds.ui(ui);
+                    });
+
+                    ui.add_space(10.0);
+
                     if ui.button("Train").clicked() {
                         ui_state.view = View::Train;
                         let (sender, receiver) = bounded(1000);
@@ -75,16 +190,53 @@ fn ui_system<
                         ui_state.agent_receiver =
                             Some(ui_state.agent.training_details_receiver(&world, receiver));
 
+                        let run_seed = seeds.next_run();
+                        ui_state.run_seed = Some(run_seed);
+
                         let world = world.clone();
-                        let algorithm = ui_state.agent.clone();
+                        let mut algorithm = ui_state.agent.clone();
+                        algorithm.set_seed(run_seed.seed);
                         std::thread::spawn(move || algorithm.train(world, sender));
                     }
+
+                    ui.add_space(10.0);
+
+                    if ui
+                        .button("Import policy table")
+                        .on_hover_text("Visualizes a policy table exported from a trained agent.")
+                        .clicked()
+                    {
+                        if let Some(path) = rfd::FileDialog::new()
+                            .add_filter("JSON", &["json"])
+                            .pick_file()
+                        {
+                            match PolicyTable::load(path) {
+                                Ok(policy_table) => {
+                                    ui_state.view = setup_visualization(
+                                        &world,
+                                        VisualizedAgent::Table(
+                                            policy_table,
+                                            PolicyInspector::default(),
+                                        ),
+                                        ui_state.agent.env_config(),
+                                        &mut commands,
+                                        &mut meshes,
+                                        &mut materials,
+                                    );
+                                }
+                                Err(error) => {
+                                    println!("Couldn't import the policy table: {error}.");
+                                }
+                            }
+                        }
+                    }
                 }
                 View::Train => {
                     let UiState {
+                        agent: algorithm,
                         view,
                         agent_receiver,
-                        ..
+                        run_seed,
                     } = &mut *ui_state;
                     if ui.button("Back to select").clicked() {
                         *view = View::Select;
@@ -93,11 +245,23 @@ fn ui_system<
 
                     ui.add_space(10.0);
 
+                    if let Some(run_seed) = run_seed {
+                        ui.horizontal(|ui| {
+                            ui.label(format!("Seed: {}", run_seed.seed));
+                            if ui.button("Copy").clicked() {
+                                let seed = run_seed.seed.to_string();
+                                ui.output_mut(|output| output.copied_text = seed);
+                            }
+                        });
+                        ui.add_space(10.0);
+                    }
+
                     if let Some(receiver) = agent_receiver {
                         if let Some(agent) = receiver.details_ui(ui) {
                             *view = setup_visualization(
                                 &world,
-                                agent,
+                                VisualizedAgent::Trained(agent.clone()),
+                                algorithm.env_config(),
                                 &mut commands,
                                 &mut meshes,
                                 &mut materials,
@@ -105,30 +269,132 @@ fn ui_system<
                         }
                     }
                 }
-                View::Visualize { agent, environment } => {
+                View::Visualize {
+                    agent,
+                    environment,
+                    config_mismatch,
+                } => {
+                    // Imported policy tables aren't from a training run.
+                    let training = ui_state.agent_receiver.is_some();
                     let mut back_to_train = false;
-                    if ui.button("Go back to training").clicked() {
+                    let back_text = if training {
+                        "Go back to training"
+                    } else {
+                        "Back to select"
+                    };
+                    if ui.button(back_text).clicked() {
                         back_to_train = true;
                     }
                     ui.add_space(10.0);
-                    if let Some(distance) = environment.distance_to_goals() {
+                    if *config_mismatch {
+                        ui.colored_label(
+                            egui::Color32::RED,
+                            "Warning: the agent's environment configuration differs from the \
+                            training configuration, so the visualization may not match the reported score.",
+                        );
+                        ui.add_space(10.0);
+                    }
+                    if let Ok(distance) = environment.distance_to_goals() {
                         ui.label(format!("Distance to goals: {:.3}", distance));
                     }
                     if environment.won() {
                         ui.add_space(10.0);
                         ui.label("Won");
+                    } else if environment.out_of_bounds() {
+                        ui.add_space(10.0);
+                        ui.label("Out of bounds");
+                    } else if environment.dead() {
+                        ui.add_space(10.0);
+                        ui.label("Killed by a hazard or an enemy");
+                    }
+                    let mut inspected_table = None;
+                    match agent {
+                        VisualizedAgent::Trained(agent) => {
+                            if let Some(policy_table) = agent.policy_table() {
+                                ui.add_space(10.0);
+                                ui.horizontal(|ui| {
+                                    if ui.button("Export policy table").clicked() {
+                                        export_policy_table(&policy_table);
+                                    }
+                                    if ui
+                                        .button("Inspect policy table")
+                                        .on_hover_text(
+                                            "Plays the agent's policy table instead, \
+                                            whose moves can be edited.",
+                                        )
+                                        .clicked()
+                                    {
+                                        inspected_table = Some(policy_table);
+                                    }
+                                });
+                            }
+                            ui.add_space(10.0);
+                            agent.details_ui(ui, environment);
+                        }
+                        VisualizedAgent::Table(policy_table, inspector) => {
+                            ui.add_space(10.0);
+                            if ui.button("Export policy table").clicked() {
+                                export_policy_table(policy_table);
+                            }
+                            ui.add_space(10.0);
+                            policy_table.details_ui(ui, environment);
+                            ui.add_space(10.0);
+                            let current_bin = policy_table.current_bin(environment).ok();
+                            inspector.ui(ui, policy_table, current_bin);
+                        }
+                    }
+                    if let Some(policy_table) = inspected_table {
+                        *agent = VisualizedAgent::Table(policy_table, PolicyInspector::default());
                     }
-                    ui.add_space(10.0);
-                    agent.details_ui(ui, environment);
                     if back_to_train {
                         cleanup_visulazation(&mut commands, &visualization_objects);
-                        ui_state.view = View::Train;
+                        ui_state.view = if training { View::Train } else { View::Select };
                     }
                 }
             }
         });
 }
 
+fn export_policy_table(policy_table: &PolicyTable) {
+    if let Some(path) = rfd::FileDialog::new()
+        .add_filter("JSON", &["json"])
+        .save_file()
+    {
+        if let Err(error) = policy_table.save(path) {
+            println!("Couldn't export the policy table: {error}.");
+        }
+    }
+}
+
+// Editor for impulses applied to the player at given steps.
+fn disturbances_ui(ui: &mut egui::Ui, disturbances: &mut Vec<Disturbance>) {
+    let mut removed = None;
+    egui::Grid::new("Disturbances grid")
+        .spacing([10.0, 5.0])
+        .show(ui, |ui| {
+            for (index, disturbance) in disturbances.iter_mut().enumerate() {
+                ui.label("Step: ");
+                ui.add(egui::DragValue::new(&mut disturbance.step));
+                ui.label("Impulse: ");
+                ui.add(egui::DragValue::new(&mut disturbance.impulse[0]));
+                ui.add(egui::DragValue::new(&mut disturbance.impulse[1]));
+                if ui.button("Remove").clicked() {
+                    removed = Some(index);
+                }
+                ui.end_row();
+            }
+        });
+    if let Some(index) = removed {
+        disturbances.remove(index);
+    }
+    if ui.button("Add disturbance").clicked() {
+        disturbances.push(Disturbance {
+            step: 200,
+            impulse: [10.0, 0.0],
+        });
+    }
+}
+
 fn update_visualization<
     AgentType: Agent,
     Message: Send + Sync + 'static,
@@ -136,18 +402,31 @@ fn update_visualization<
     AlgorithmType: Algorithm<AgentType, Message, TrainingDetailsType>,
 >(
     mut ui_state: ResMut<UiState<AgentType, TrainingDetailsType, AlgorithmType>>,
-    mut rigid_bodies: Query<(&mut Transform, &RigidBodyId)>,
+    mut rigid_bodies: Query<(&mut Transform, &mut Visibility, &RigidBodyId)>,
     mut camera: Query<&mut Transform, (With<Camera>, Without<RigidBodyId>)>,
+    windows: Query<&Window, With<PrimaryWindow>>,
+    mut contexts: EguiContexts,
 ) {
-    if let View::Visualize { environment, agent } = &mut ui_state.view {
+    if let View::Visualize {
+        environment, agent, ..
+    } = &mut ui_state.view
+    {
         let player_move = agent.get_move(environment);
         environment.step(player_move);
 
-        for (mut transform, RigidBodyId(rigid_body_handle)) in rigid_bodies.iter_mut() {
+        for (mut transform, mut visibility, RigidBodyId(rigid_body_handle)) in
+            rigid_bodies.iter_mut()
+        {
             let rigid_body = &environment.rigid_body_set()[*rigid_body_handle];
             transform.translation.x = rigid_body.translation().x / BEVY_TO_PHYSICS_SCALE;
             transform.translation.y = rigid_body.translation().y / BEVY_TO_PHYSICS_SCALE;
             transform.rotation = Quat::from_rotation_z(rigid_body.rotation().angle());
+            // Collected coins are disabled, they reappear when the environment is reset.
+            *visibility = if rigid_body.is_enabled() {
+                Visibility::Inherited
+            } else {
+                Visibility::Hidden
+            };
         }
 
         let player_translation =
@@ -155,6 +434,17 @@ fn update_visualization<
         let mut camera_transform = camera.iter_mut().next().unwrap();
         camera_transform.translation.x = player_translation.x / BEVY_TO_PHYSICS_SCALE;
         camera_transform.translation.y = player_translation.y / BEVY_TO_PHYSICS_SCALE;
+
+        let mut overlay = OverlayPainter::default();
+        agent.draw_overlay(&mut overlay, environment);
+        if let (false, Ok(window)) = (overlay.is_empty(), windows.get_single()) {
+            overlay.paint_in_background(
+                contexts.ctx_mut(),
+                "Agent overlay",
+                &camera_transform,
+                window,
+            );
+        }
     }
 }
 
@@ -176,78 +466,56 @@ fn cleanup_train<
 
 fn setup_visualization<AgentType: Agent>(
     world: &Res<World>,
-    agent: &AgentType,
+    agent: VisualizedAgent<AgentType>,
+    training_config: EnvConfig,
     commands: &mut Commands,
     meshes: &mut ResMut<Assets<Mesh>>,
     materials: &mut ResMut<Assets<ColorMaterial>>,
 ) -> View<AgentType> {
-    let mut environment = Environment::new(world.player_position);
-
-    let capsule = bevy::prelude::shape::Capsule {
-        radius: PLAYER_RADIUS,
-        rings: 5,
-        depth: PLAYER_DEPTH,
-        latitudes: 10,
-        longitudes: 10,
-        uv_profile: bevy::prelude::shape::CapsuleUvProfile::Uniform,
-    };
-    let mut player = commands.spawn(MaterialMesh2dBundle {
-        mesh: meshes.add(capsule.into()).into(),
-        material: materials.add(ColorMaterial::from(Color::GRAY)),
-        transform: Transform::from_translation(Vec3::new(
-            world.player_position[0],
-            world.player_position[1],
-            0.0,
-        )),
-        ..default()
-    });
-    player.insert(VisualizationObject);
-    player.insert(Player);
-    player.insert(RigidBodyId(environment.player_handle()));
-
-    for object_and_transform in world.objects.iter() {
+    let agent_config = agent.env_config();
+    let config_mismatch = agent_config != training_config;
+    let (environment, rigid_body_handles) =
+        Environment::from_world_with_config(world, agent_config);
+
+    let player_positions =
+        std::iter::once(world.player_position).chain(world.other_players.iter().copied());
+    for (index, (position, player_handle)) in player_positions
+        .zip(environment.player_handles())
+        .enumerate()
+    {
+        let mut player = commands.spawn(MaterialMesh2dBundle {
+            mesh: meshes.add(world.player_shape.mesh()).into(),
+            material: materials.add(ColorMaterial::from(player_color(index))),
+            transform: Transform::from_translation(Vec3::new(position[0], position[1], 0.0)),
+            ..default()
+        });
+        player.insert(VisualizationObject);
+        player.insert(Player);
+        player.insert(RigidBodyId(player_handle));
+    }
+
+    for (object_and_transform, rigid_body_handle) in world.objects.iter().zip(rigid_body_handles) {
         let object = &object_and_transform.object;
+        if !object.visible() {
+            continue;
+        }
         let transform = object_and_transform.transform();
-        let rigid_body_handle = environment.add_object(object_and_transform);
-        match object {
-            WorldObject::Block { fixed } => {
-                let color = if *fixed {
-                    Color::BLACK
-                } else {
-                    Color::DARK_GRAY
-                };
-                let mut block = commands.spawn(MaterialMesh2dBundle {
-                    mesh: meshes
-                        .add(Mesh::from(bevy::prelude::shape::Quad::new(Vec2::ONE)))
-                        .into(),
-                    material: materials.add(ColorMaterial::from(color)),
-                    transform,
-                    ..default()
-                });
-                block.insert(VisualizationObject);
-                if let Some(rigid_body_handle) = rigid_body_handle {
-                    block.insert(RigidBodyId(rigid_body_handle));
-                }
-            }
-            WorldObject::Goal => {
-                commands
-                    .spawn(MaterialMesh2dBundle {
-                        mesh: meshes
-                            .add(Mesh::from(bevy::prelude::shape::Quad::new(Vec2::ONE)))
-                            .into(),
-                        material: materials
-                            .add(ColorMaterial::from(Color::rgba(0.0, 1.0, 0.0, 0.5))),
-                        transform,
-                        ..default()
-                    })
-                    .insert(VisualizationObject);
-            }
+        let mut entity = commands.spawn(MaterialMesh2dBundle {
+            mesh: meshes.add(object.mesh()).into(),
+            material: materials.add(ColorMaterial::from(object_and_transform.color())),
+            transform,
+            ..default()
+        });
+        entity.insert(VisualizationObject);
+        if let Some(rigid_body_handle) = rigid_body_handle {
+            entity.insert(RigidBodyId(rigid_body_handle));
         }
     }
 
     View::Visualize {
-        agent: agent.clone(),
+        agent,
         environment: Box::new(environment),
+        config_mismatch,
     }
 }
 
@@ -265,6 +533,8 @@ struct UiState<Agent, TrainingDetails, Algorithm> {
     agent: Algorithm,
     view: View<Agent>,
     agent_receiver: Option<TrainingDetails>,
+    // The seed of the current training run.
+    run_seed: Option<RunSeed>,
 }
 
 impl<Agent, TrainingDetails, Algorithm: Default> Default
@@ -275,6 +545,7 @@ impl<Agent, TrainingDetails, Algorithm: Default> Default
             agent: Algorithm::default(),
             view: View::default(),
             agent_receiver: None,
+            run_seed: None,
         }
     }
 }
@@ -285,11 +556,44 @@ enum View<Agent> {
     Select,
     Train,
     Visualize {
-        agent: Agent,
+        agent: VisualizedAgent<Agent>,
         environment: Box<Environment>,
+        config_mismatch: bool,
     },
 }
 
+// The agent played by the visualization.
+enum VisualizedAgent<Agent> {
+    Trained(Agent),
+    // A policy table imported from a file or taken from the trained agent, whose moves can be edited while it plays.
+    Table(PolicyTable, PolicyInspector),
+}
+
+impl<AgentType: Agent> VisualizedAgent<AgentType> {
+    fn get_move(&mut self, environment: &Environment) -> Move {
+        match self {
+            VisualizedAgent::Trained(agent) => agent.get_move(environment),
+            VisualizedAgent::Table(policy_table, _) => policy_table.get_move(environment),
+        }
+    }
+
+    fn env_config(&self) -> EnvConfig {
+        match self {
+            VisualizedAgent::Trained(agent) => agent.env_config(),
+            VisualizedAgent::Table(policy_table, _) => policy_table.env_config(),
+        }
+    }
+
+    fn draw_overlay(&self, painter: &mut OverlayPainter, environment: &Environment) {
+        match self {
+            VisualizedAgent::Trained(agent) => agent.draw_overlay(painter, environment),
+            VisualizedAgent::Table(policy_table, _) => {
+                policy_table.draw_overlay(painter, environment)
+            }
+        }
+    }
+}
+
 #[derive(Component)]
 struct VisualizationObject;
 
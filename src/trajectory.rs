@@ -0,0 +1,101 @@
+use serde::{Deserialize, Serialize};
+
+use crate::common::Move;
+
+/// The state of the player after a step, see [`Trajectory`].
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq)]
+pub struct TrajectoryStep {
+    /// In the same units as [`World`](crate::World) positions.
+    pub position: [f32; 2],
+    /// In world units per second.
+    pub velocity: [f32; 2],
+    /// The move applied during the step, after action noise.
+    pub player_move: Move,
+}
+
+/// The steps of the first player recorded by [`Environment::record`](crate::Environment::record).
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq)]
+pub struct Trajectory {
+    /// The position of the player when recording started.
+    pub start: [f32; 2],
+    pub steps: Vec<TrajectoryStep>,
+    /// Whether the environment was won during the recorded steps.
+    pub won: bool,
+}
+
+impl Trajectory {
+    pub fn new(start: [f32; 2]) -> Trajectory {
+        Trajectory {
+            start,
+            steps: vec![],
+            won: false,
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.steps.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.steps.is_empty()
+    }
+
+    /// The start position followed by the position after every step.
+    pub fn positions(&self) -> impl Iterator<Item = [f32; 2]> + '_ {
+        std::iter::once(self.start).chain(self.steps.iter().map(|step| step.position))
+    }
+
+    /// Total distance travelled by the player.
+    pub fn path_length(&self) -> f32 {
+        self.positions()
+            .zip(self.positions().skip(1))
+            .map(|(from, to)| ((to[0] - from[0]).powi(2) + (to[1] - from[1]).powi(2)).sqrt())
+            .sum()
+    }
+
+    /// Distance between the start and end positions.
+    pub fn displacement(&self) -> f32 {
+        let end = self.steps.last().map_or(self.start, |step| step.position);
+        ((end[0] - self.start[0]).powi(2) + (end[1] - self.start[1]).powi(2)).sqrt()
+    }
+
+    /// Largest speed of the player after a step, or 0.0 if no steps were recorded.
+    pub fn max_speed(&self) -> f32 {
+        self.steps
+            .iter()
+            .map(|step| (step.velocity[0].powi(2) + step.velocity[1].powi(2)).sqrt())
+            .fold(0.0, f32::max)
+    }
+
+    /// The smallest rectangle containing every position, as (bottom left, top right).
+    pub fn extent(&self) -> ([f32; 2], [f32; 2]) {
+        self.positions()
+            .fold((self.start, self.start), |(min, max), position| {
+                (
+                    [min[0].min(position[0]), min[1].min(position[1])],
+                    [max[0].max(position[0]), max[1].max(position[1])],
+                )
+            })
+    }
+
+    /// The number of steps each move was applied for, indexed by [`Move::to_index`].
+    pub fn move_counts(&self) -> [usize; Move::COUNT] {
+        let mut counts = [0; Move::COUNT];
+        for step in &self.steps {
+            counts[step.player_move.to_index()] += 1;
+        }
+        counts
+    }
+
+    /// Every few positions, so about `max_points` remain, always including the last one.
+    pub fn downsampled_positions(&self, max_points: usize) -> Vec<[f32; 2]> {
+        let positions: Vec<_> = self.positions().collect();
+        let last = *positions.last().unwrap();
+        let stride = positions.len().div_ceil(max_points.max(1));
+        let mut positions: Vec<_> = positions.into_iter().step_by(stride).collect();
+        if positions.last() != Some(&last) {
+            positions.push(last);
+        }
+        positions
+    }
+}
@@ -0,0 +1,153 @@
+use std::fmt;
+
+use rapier2d::parry::query;
+
+use crate::common::{Environment, WinCondition, World, WorldObject};
+
+// Size of the cells of the grid used to check whether a goal can be reached, in world units.
+const REACHABILITY_CELL_SIZE: f32 = 10.0;
+
+/// A problem with a world, found by [`World::validate`].
+#[derive(Clone, Debug, PartialEq)]
+pub enum ValidationIssue {
+    /// The world has no goal which wins it, so it can't be won when reaching a goal or pushing a block into one.
+    NoGoal,
+    /// The world has no coins, so it can't be won when collecting all coins.
+    NoCoins,
+    /// A player starts overlapping a block which doesn't move out of the way.
+    /// The first player is checked at [`World::player_position`], ignoring the spawn region.
+    /// `player` is 0 for the first player and 1 + the index in [`World::other_players`] for the other players.
+    PlayerInsideBlock { player: usize },
+    /// No goal can be reached from the first player's position by moving around the fixed blocks.
+    /// The check ignores gravity, so worlds with this issue can't be won, but worlds without it may still be impossible.
+    GoalUnreachable,
+}
+
+impl ValidationIssue {
+    /// Errors make a world impossible to play as intended, the other issues are warnings.
+    pub fn is_error(&self) -> bool {
+        !matches!(self, ValidationIssue::GoalUnreachable)
+    }
+}
+
+impl fmt::Display for ValidationIssue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ValidationIssue::NoGoal => write!(f, "The world doesn't have a goal."),
+            ValidationIssue::NoCoins => write!(f, "The world doesn't have any coins."),
+            ValidationIssue::PlayerInsideBlock { player: 0 } => {
+                write!(f, "The player starts inside a block.")
+            }
+            ValidationIssue::PlayerInsideBlock { player } => {
+                write!(f, "Player {} starts inside a block.", player + 1)
+            }
+            ValidationIssue::GoalUnreachable => {
+                write!(f, "The goals can't be reached from the player's position.")
+            }
+        }
+    }
+}
+
+impl World {
+    /// Checks the world for problems which would make it impossible to play, before playing or training on it.
+    pub fn validate(&self) -> Vec<ValidationIssue> {
+        let mut issues = vec![];
+        let has_goal = self.objects.iter().any(|object_and_transform| {
+            matches!(
+                object_and_transform.object,
+                WorldObject::Goal {
+                    optional: false,
+                    ..
+                }
+            )
+        });
+        let has_coins = self
+            .objects
+            .iter()
+            .any(|object_and_transform| matches!(object_and_transform.object, WorldObject::Coin));
+        match self.win_condition {
+            WinCondition::ReachGoal | WinCondition::PushBlock if !has_goal => {
+                issues.push(ValidationIssue::NoGoal);
+            }
+            WinCondition::CollectCoins if !has_coins => issues.push(ValidationIssue::NoCoins),
+            _ => {}
+        }
+
+        // The random position in the spawn region would make the checks disagree with each other and between runs.
+        let world = World {
+            spawn_region: None,
+            ..self.clone()
+        };
+        let (environment, _) = Environment::from_world(&world);
+        let rigid_body_set = environment.rigid_body_set();
+        let collider_set = environment.collider_set();
+        for (player, handle) in environment.player_handles().into_iter().enumerate() {
+            let player_collider = &collider_set[rigid_body_set[handle].colliders()[0]];
+            let inside_block = collider_set.iter().any(|(_, collider)| {
+                !collider.is_sensor()
+                    && collider
+                        .parent()
+                        .map_or(true, |parent| !rigid_body_set[parent].is_dynamic())
+                    && query::intersection_test(
+                        player_collider.position(),
+                        player_collider.shape(),
+                        collider.position(),
+                        collider.shape(),
+                    )
+                    .unwrap_or(false)
+            });
+            if inside_block {
+                issues.push(ValidationIssue::PlayerInsideBlock { player });
+            }
+        }
+
+        if self.win_condition == WinCondition::ReachGoal && has_goal {
+            let distance_field = environment.compute_distance_field(REACHABILITY_CELL_SIZE);
+            if distance_field.distance(self.player_position).is_infinite() {
+                issues.push(ValidationIssue::GoalUnreachable);
+            }
+        }
+        issues
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::ValidationIssue;
+    use crate::{
+        common::{ObjectAndTransform, World, WorldObject},
+        events::Bounds,
+    };
+
+    #[test]
+    fn player_checked_at_player_position() {
+        let world = World {
+            player_position: [0.0, 0.0],
+            spawn_region: Some(Bounds {
+                min: [1000.0, 1000.0],
+                max: [1100.0, 1100.0],
+            }),
+            objects: vec![ObjectAndTransform {
+                object: WorldObject::Block {
+                    fixed: true,
+                    animation: None,
+                    ccd: false,
+                    friction: None,
+                    restitution: None,
+                    material: None,
+                    density: 1.0,
+                },
+                position: [0.0, 0.0, 0.0],
+                scale: [100.0, 100.0],
+                rotation: 0.0,
+                color: None,
+            }],
+            ..World::default()
+        };
+        for _ in 0..3 {
+            assert!(world
+                .validate()
+                .contains(&ValidationIssue::PlayerInsideBlock { player: 0 }));
+        }
+    }
+}
@@ -0,0 +1,113 @@
+use rayon::prelude::*;
+
+use crate::{
+    common::{EnvConfig, EnvInfo, Environment, Move, StepInfo, World},
+    error::{Error, Result},
+};
+
+/// A batch of independent environments which are stepped in parallel.
+///
+/// Useful for population based training and deep reinforcement learning,
+/// where many episodes are simulated at once.
+pub struct VecEnvironment {
+    environments: Vec<Environment>,
+}
+
+/// The transitions returned by [`VecEnvironment::step`], with one entry per environment.
+#[derive(Debug, Clone, Default)]
+pub struct VecStepResult {
+    pub observations: Vec<Vec<f32>>,
+    pub rewards: Vec<f32>,
+    pub dones: Vec<bool>,
+    pub infos: Vec<StepInfo>,
+    pub env_infos: Vec<EnvInfo>,
+}
+
+impl VecEnvironment {
+    pub fn new(environments: Vec<Environment>) -> VecEnvironment {
+        VecEnvironment { environments }
+    }
+
+    /// Creates `count` environments from the world with the given configuration.
+    pub fn from_world(world: &World, config: EnvConfig, count: usize) -> VecEnvironment {
+        VecEnvironment {
+            environments: (0..count)
+                .map(|_| Environment::from_world_with_config(world, config.clone()).0)
+                .collect(),
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.environments.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.environments.is_empty()
+    }
+
+    pub fn environments(&self) -> &[Environment] {
+        &self.environments
+    }
+
+    pub fn environments_mut(&mut self) -> &mut [Environment] {
+        &mut self.environments
+    }
+
+    /// The observations of all the environments, see [`Environment::state`].
+    pub fn states(&self) -> Vec<Vec<f32>> {
+        self.environments
+            .par_iter()
+            .map(|environment| environment.state())
+            .collect()
+    }
+
+    /// Steps every environment with its move, see [`Environment::step_full`].
+    /// Returns [`Error::BatchSize`] if there isn't exactly one move per environment.
+    ///
+    /// Environments aren't reset when they are done, see [`VecEnvironment::reset_done`].
+    pub fn step(&mut self, moves: &[Move]) -> Result<VecStepResult> {
+        if moves.len() != self.environments.len() {
+            return Err(Error::BatchSize {
+                expected: self.environments.len(),
+                actual: moves.len(),
+            });
+        }
+
+        let step_results: Vec<_> = self
+            .environments
+            .par_iter_mut()
+            .zip(moves)
+            .map(|(environment, player_move)| environment.step_full(*player_move))
+            .collect();
+
+        let mut result = VecStepResult::default();
+        for step_result in step_results {
+            result.observations.push(step_result.observation);
+            result.rewards.push(step_result.reward);
+            result.dones.push(step_result.done);
+            result.infos.push(step_result.info);
+            result.env_infos.push(step_result.env_info);
+        }
+        Ok(result)
+    }
+
+    /// Resets every environment, see [`Environment::reset`].
+    pub fn reset(&mut self) {
+        self.environments
+            .par_iter_mut()
+            .for_each(|environment| environment.reset());
+    }
+
+    /// Resets the environments which are done and returns their indices.
+    pub fn reset_done(&mut self) -> Vec<usize> {
+        self.environments
+            .iter_mut()
+            .enumerate()
+            .filter(|(_, environment)| environment.done())
+            .map(|(index, environment)| {
+                environment.reset();
+                index
+            })
+            .collect()
+    }
+}